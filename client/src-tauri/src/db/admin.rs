@@ -0,0 +1,900 @@
+use super::connection::{recheck_on_connection_error, PoolRegistry};
+use super::credentials::CredentialStore;
+use super::metadata::qualified_table_name;
+use super::types::{
+    with_context, DatabaseError, DatabaseErrorContext, DatabaseType, DatabaseUser,
+    IntegrityCheckResult, LockInfo, RefreshResult, ReplicationSlotInfo, ReplicationStatus,
+    SessionInfo, TablePrivilege, VacuumResult,
+};
+use super::utils::quote_identifier;
+use sqlx::Row;
+use std::time::Instant;
+use tauri::{AppHandle, State};
+
+/// List the sessions connected to the database server, except our own
+///
+/// On MySQL `state` is the command of the thread (such as `Query` or `Sleep`) and
+/// `wait_event` is what it is currently doing. SQLite has no sessions.
+pub async fn collect_active_sessions(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<SessionInfo>, DatabaseError> {
+    let creds = store.get(database_id)?;
+
+    let query = match creds.db_type {
+        DatabaseType::Postgres => {
+            "SELECT pid::bigint AS pid,
+                datname::text AS database_name,
+                usename::text AS username,
+                state::text AS state,
+                query::text AS query,
+                query_start::text AS query_start,
+                EXTRACT(EPOCH FROM (now() - query_start))::float8 AS duration_secs,
+                wait_event::text AS wait_event
+            FROM pg_stat_activity
+            WHERE pid <> pg_backend_pid()
+            ORDER BY query_start NULLS LAST"
+        }
+        DatabaseType::MySQL => {
+            "SELECT CAST(ID AS SIGNED) AS pid,
+                CAST(DB AS CHAR) AS database_name,
+                CAST(USER AS CHAR) AS username,
+                CAST(COMMAND AS CHAR) AS state,
+                CAST(INFO AS CHAR) AS query,
+                CAST(NOW() - INTERVAL TIME SECOND AS CHAR) AS query_start,
+                CAST(TIME AS DOUBLE) AS duration_secs,
+                CAST(STATE AS CHAR) AS wait_event
+            FROM INFORMATION_SCHEMA.PROCESSLIST
+            WHERE ID <> CONNECTION_ID()
+            ORDER BY TIME DESC"
+        }
+        DatabaseType::SQLite => return Ok(Vec::new()),
+        DatabaseType::ClickHouse => return Err(DatabaseError::UnsupportedType),
+    };
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let rows = sqlx::query(query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, query))?;
+
+    let mut sessions = Vec::new();
+    for row in rows {
+        let optional = |column: &str| -> Option<String> {
+            row.try_get(column)
+                .or_else(|_| row.try_get(column.to_uppercase().as_str()))
+                .unwrap_or(None)
+        };
+
+        sessions.push(SessionInfo {
+            pid: row
+                .try_get("pid")
+                .or_else(|_| row.try_get("PID"))
+                .map_err(DatabaseError::from)?,
+            database: optional("database_name"),
+            username: optional("username"),
+            state: optional("state"),
+            query: optional("query"),
+            query_start: optional("query_start"),
+            duration_secs: row
+                .try_get("duration_secs")
+                .or_else(|_| row.try_get("DURATION_SECS"))
+                .unwrap_or(None),
+            wait_event: optional("wait_event"),
+        });
+    }
+
+    Ok(sessions)
+}
+
+/// End a session on the database server, rolling back its open transaction
+pub async fn kill_session(
+    database_id: &str,
+    pid: i64,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<(), DatabaseError> {
+    let creds = store.get(database_id)?;
+    if matches!(creds.db_type, DatabaseType::SQLite | DatabaseType::ClickHouse) {
+        return Err(DatabaseError::UnsupportedType);
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    match creds.db_type {
+        DatabaseType::Postgres => {
+            let row = sqlx::query("SELECT pg_terminate_backend($1::integer) AS terminated")
+                .bind(pid)
+                .fetch_one(&*pool)
+                .await
+                .map_err(DatabaseError::from)?;
+            let terminated: bool = row.try_get("terminated").unwrap_or(false);
+            if !terminated {
+                return Err(DatabaseError::Other(format!("No session with pid {}", pid)));
+            }
+        }
+        _ => {
+            // KILL does not accept a bind parameter; pid is an integer so it is safe to format
+            let sql = format!("KILL {}", pid);
+            sqlx::query(&sql)
+                .execute(&*pool)
+                .await
+                .map_err(|e| DatabaseError::query_failed(e, &sql))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// List the locks held or awaited by other Postgres backends
+///
+/// The blocker of a lock that is not granted comes from `pg_blocking_pids`, which
+/// knows which lock modes conflict. Only when it reports none (the blocker may have
+/// just finished) is another backend holding a lock on the same relation used.
+/// MySQL and SQLite are not supported.
+pub async fn collect_locks(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<LockInfo>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Err(DatabaseError::UnsupportedType);
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let query = "SELECT l.pid::bigint AS pid,
+            c.relname::text AS relation_name,
+            l.locktype::text AS lock_type,
+            l.mode::text AS mode,
+            l.granted AS granted,
+            CASE WHEN NOT l.granted
+                THEN COALESCE((pg_blocking_pids(l.pid))[1], holder.pid)::bigint
+            END AS blocking_pid,
+            a.query::text AS query
+        FROM pg_locks l
+        LEFT JOIN pg_class c ON c.oid = l.relation
+        LEFT JOIN pg_stat_activity a ON a.pid = l.pid
+        LEFT JOIN LATERAL (
+            SELECT other.pid
+            FROM pg_locks other
+            WHERE other.granted
+                AND other.relation = l.relation
+                AND other.pid <> l.pid
+            ORDER BY other.pid
+            LIMIT 1
+        ) holder ON NOT l.granted
+        WHERE l.pid <> pg_backend_pid()
+        ORDER BY l.granted, l.pid";
+
+    let rows = sqlx::query(query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, query))?;
+
+    let mut locks = Vec::new();
+    for row in rows {
+        locks.push(LockInfo {
+            pid: row
+                .try_get("pid")
+                .map_err(DatabaseError::from)?,
+            relation_name: row.try_get("relation_name").unwrap_or(None),
+            lock_type: row.try_get("lock_type").unwrap_or_default(),
+            mode: row.try_get("mode").unwrap_or_default(),
+            granted: row.try_get("granted").unwrap_or(true),
+            blocking_pid: row.try_get("blocking_pid").unwrap_or(None),
+            query: row.try_get("query").unwrap_or(None),
+        });
+    }
+
+    Ok(locks)
+}
+
+/// List the users and roles of the database server with the roles granted to them
+///
+/// Postgres reads `pg_roles` and `pg_auth_members`, leaving out the predefined
+/// `pg_` roles. MySQL reads `mysql.user` and `mysql.role_edges`, which needs
+/// SELECT on the `mysql` schema; `can_login` is false for locked accounts.
+pub async fn collect_database_users(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<DatabaseUser>, DatabaseError> {
+    let creds = store.get(database_id)?;
+
+    // Role names are joined with newlines since arrays cannot be decoded
+    let query = match creds.db_type {
+        DatabaseType::Postgres => {
+            "SELECT r.rolname::text AS name,
+                r.rolsuper AS superuser,
+                r.rolcreatedb AS can_create_db,
+                r.rolcanlogin AS can_login,
+                (SELECT string_agg(g.rolname::text, E'\\n' ORDER BY g.rolname)
+                    FROM pg_auth_members m
+                    JOIN pg_roles g ON g.oid = m.roleid
+                    WHERE m.member = r.oid) AS roles
+            FROM pg_roles r
+            WHERE r.rolname NOT LIKE 'pg\\_%'
+            ORDER BY r.rolname"
+        }
+        DatabaseType::MySQL => {
+            "SELECT CAST(CONCAT(u.User, '@', u.Host) AS CHAR) AS name,
+                CAST(u.Super_priv = 'Y' AS SIGNED) AS superuser,
+                CAST(u.Create_priv = 'Y' AS SIGNED) AS can_create_db,
+                CAST(u.account_locked = 'N' AS SIGNED) AS can_login,
+                CAST((SELECT GROUP_CONCAT(CONCAT(e.FROM_USER, '@', e.FROM_HOST)
+                        ORDER BY e.FROM_USER SEPARATOR '\\n')
+                    FROM mysql.role_edges e
+                    WHERE e.TO_USER = u.User AND e.TO_HOST = u.Host) AS CHAR) AS roles
+            FROM mysql.user u
+            ORDER BY u.User, u.Host"
+        }
+        DatabaseType::SQLite | DatabaseType::ClickHouse => {
+            return Err(DatabaseError::UnsupportedType)
+        }
+    };
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let rows = sqlx::query(query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, query))?;
+
+    let mut users = Vec::new();
+    for row in rows {
+        // MySQL has no boolean type and returns the flags as integers
+        let flag = |column: &str| -> bool {
+            row.try_get::<bool, _>(column)
+                .or_else(|_| row.try_get::<i64, _>(column).map(|v| v != 0))
+                .unwrap_or(false)
+        };
+        let roles: Option<String> = row.try_get("roles").unwrap_or(None);
+
+        users.push(DatabaseUser {
+            name: row
+                .try_get("name")
+                .map_err(DatabaseError::from)?,
+            superuser: flag("superuser"),
+            can_create_db: flag("can_create_db"),
+            can_login: flag("can_login"),
+            roles: roles
+                .map(|roles| roles.lines().map(str::to_string).collect())
+                .unwrap_or_default(),
+        });
+    }
+
+    Ok(users)
+}
+
+/// List the privileges granted on a table
+///
+/// Postgres reads `information_schema.role_table_grants` in `schema` (`public` by
+/// default), which only shows grants involving roles the current user is a member
+/// of. MySQL has no such view and reads
+/// `information_schema.table_privileges`; privileges granted on the whole database
+/// or server are not included.
+pub async fn collect_table_privileges(
+    database_id: &str,
+    table_name: &str,
+    schema: Option<&str>,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<TablePrivilege>, DatabaseError> {
+    let creds = store.get(database_id)?;
+
+    let query = match creds.db_type {
+        DatabaseType::Postgres => format!(
+            "SELECT grantee::text AS grantee,
+                privilege_type::text AS privilege_type,
+                is_grantable::text AS is_grantable
+            FROM information_schema.role_table_grants
+            WHERE table_schema = '{}' AND table_name = '{}'
+            ORDER BY grantee, privilege_type",
+            schema.unwrap_or("public").replace('\'', "''"),
+            table_name.replace('\'', "''")
+        ),
+        DatabaseType::MySQL => format!(
+            "SELECT CAST(GRANTEE AS CHAR) AS grantee,
+                CAST(PRIVILEGE_TYPE AS CHAR) AS privilege_type,
+                CAST(IS_GRANTABLE AS CHAR) AS is_grantable
+            FROM information_schema.TABLE_PRIVILEGES
+            WHERE TABLE_SCHEMA = '{}' AND TABLE_NAME = '{}'
+            ORDER BY GRANTEE, PRIVILEGE_TYPE",
+            creds.database.replace('\'', "''"),
+            table_name.replace('\'', "''")
+        ),
+        DatabaseType::SQLite | DatabaseType::ClickHouse => {
+            return Err(DatabaseError::UnsupportedType)
+        }
+    };
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let rows = sqlx::query(&query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, &query))?;
+
+    let mut privileges = Vec::new();
+    for row in rows {
+        let is_grantable: String = row.try_get("is_grantable").unwrap_or_default();
+        privileges.push(TablePrivilege {
+            grantee: row
+                .try_get("grantee")
+                .map_err(DatabaseError::from)?,
+            privilege_type: row.try_get("privilege_type").unwrap_or_default(),
+            is_grantable: is_grantable == "YES",
+        });
+    }
+
+    Ok(privileges)
+}
+
+/// Get the WAL position, standby lag and replication slots of a Postgres server
+///
+/// On a primary, `replay_lsn` and `lag_bytes` are those of the slowest standby in
+/// `pg_stat_replication`. On a standby they compare the received and replayed WAL.
+/// Other databases get an empty status with a warning instead of an error.
+pub async fn get_replication_status(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<ReplicationStatus, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Ok(ReplicationStatus {
+            warning: Some("Replication status is only available for PostgreSQL".to_string()),
+            ..Default::default()
+        });
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    // pg_current_wal_lsn() fails during recovery, so standbys use the received WAL
+    let query = "SELECT pg_is_in_recovery() AS is_in_recovery,
+            (CASE WHEN pg_is_in_recovery() THEN pg_last_wal_receive_lsn()
+                ELSE pg_current_wal_lsn() END)::text AS wal_lsn,
+            (CASE WHEN pg_is_in_recovery() THEN pg_last_wal_replay_lsn()
+                ELSE (SELECT replay_lsn FROM pg_stat_replication
+                    ORDER BY replay_lsn NULLS FIRST LIMIT 1) END)::text AS replay_lsn,
+            (CASE WHEN pg_is_in_recovery()
+                THEN pg_wal_lsn_diff(pg_last_wal_receive_lsn(), pg_last_wal_replay_lsn())
+                ELSE (SELECT max(pg_wal_lsn_diff(pg_current_wal_lsn(), replay_lsn))
+                    FROM pg_stat_replication) END)::bigint AS lag_bytes";
+    let row = sqlx::query(query)
+        .fetch_one(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, query))?;
+
+    let slots_query = "SELECT slot_name::text AS name,
+            COALESCE(plugin::text, '') AS plugin,
+            active,
+            pg_wal_lsn_diff(
+                CASE WHEN pg_is_in_recovery() THEN pg_last_wal_receive_lsn()
+                    ELSE pg_current_wal_lsn() END,
+                restart_lsn
+            )::bigint AS lag_bytes
+        FROM pg_replication_slots
+        ORDER BY slot_name";
+    let slot_rows = sqlx::query(slots_query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, slots_query))?;
+
+    let mut slots = Vec::new();
+    for slot in slot_rows {
+        slots.push(ReplicationSlotInfo {
+            name: slot
+                .try_get("name")
+                .map_err(DatabaseError::from)?,
+            plugin: slot.try_get("plugin").unwrap_or_default(),
+            active: slot.try_get("active").unwrap_or(false),
+            lag_bytes: slot.try_get("lag_bytes").unwrap_or(None),
+        });
+    }
+
+    Ok(ReplicationStatus {
+        is_in_recovery: row
+            .try_get("is_in_recovery")
+            .map_err(DatabaseError::from)?,
+        wal_lsn: row.try_get("wal_lsn").unwrap_or(None),
+        replay_lsn: row.try_get("replay_lsn").unwrap_or(None),
+        lag_bytes: row.try_get("lag_bytes").unwrap_or(None),
+        slots,
+        warning: None,
+    })
+}
+
+/// Move the sequence behind a serial or identity column to the column's largest value
+///
+/// Bulk loads that set ids explicitly, such as COPY, leave the sequence behind and
+/// the next insert fails on a duplicate key. This writes to the database, so it is
+/// its own command instead of going through `validate_query`. An empty table gets
+/// the sequence back to its start. Returns the new value; Postgres only.
+pub async fn reset_sequence(
+    database_id: &str,
+    table_name: &str,
+    column_name: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<i64, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Err(DatabaseError::UnsupportedType);
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    // pg_get_serial_sequence parses the table name as SQL, so it is passed quoted,
+    // while the column name is taken as is
+    let table = quote_identifier(table_name, &creds.db_type);
+    let sql = format!(
+        "SELECT setval(
+            pg_get_serial_sequence($1, $2),
+            COALESCE(MAX({column}), 1),
+            MAX({column}) IS NOT NULL
+        )::bigint AS value
+        FROM {table}",
+        column = quote_identifier(column_name, &creds.db_type),
+        table = table
+    );
+    let row = sqlx::query(&sql)
+        .bind(&table)
+        .bind(column_name)
+        .fetch_one(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, &sql))?;
+
+    row.try_get::<Option<i64>, _>("value")
+        .map_err(DatabaseError::from)?
+        .ok_or_else(|| {
+            DatabaseError::Other(format!(
+                "No sequence is attached to {}.{}",
+                table_name, column_name
+            ))
+        })
+}
+
+/// Recompute the contents of a Postgres materialized view
+///
+/// `get_tables` lists materialized views with `TableType::MaterializedView`. Like
+/// `reset_sequence` this writes, so it does not go through `validate_query`. A
+/// concurrent refresh keeps the view readable while it runs but needs a unique
+/// index on the view and a view that was populated before.
+pub async fn refresh_materialized_view(
+    database_id: &str,
+    view_name: &str,
+    schema: Option<&str>,
+    concurrently: bool,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<RefreshResult, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Err(DatabaseError::UnsupportedType);
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let sql = format!(
+        "REFRESH MATERIALIZED VIEW {}{}",
+        if concurrently { "CONCURRENTLY " } else { "" },
+        qualified_table_name(view_name, schema, &creds.db_type)
+    );
+
+    let start = Instant::now();
+    sqlx::query(&sql).execute(&*pool).await.map_err(|e| {
+        // object_not_in_prerequisite_state, raised when CONCURRENTLY cannot be used
+        let prerequisite = e
+            .as_database_error()
+            .and_then(|db| db.code())
+            .is_some_and(|code| code == "55000");
+        if concurrently && prerequisite {
+            DatabaseError::QueryError(format!(
+                "{} cannot be refreshed concurrently. CONCURRENTLY needs a unique index \
+                without a WHERE clause on the view and a view that has been populated; \
+                refresh it without CONCURRENTLY instead.\n\n{}",
+                view_name, e
+            ))
+        } else {
+            DatabaseError::query_failed(e, &sql)
+        }
+    })?;
+
+    Ok(RefreshResult {
+        view_name: view_name.to_string(),
+        duration_ms: start.elapsed().as_millis() as u64,
+    })
+}
+
+/// Run VACUUM, with FULL and ANALYZE when asked, on a Postgres table
+///
+/// VACUUM cannot run inside a transaction block, so it is sent on a connection
+/// acquired from the pool in its default auto-commit mode instead of through
+/// `begin()`. VACUUM FULL rewrites the table and holds an exclusive lock on it
+/// until it is done.
+pub async fn vacuum_table(
+    database_id: &str,
+    table_name: &str,
+    schema: Option<&str>,
+    analyze: bool,
+    full: bool,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<VacuumResult, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Err(DatabaseError::UnsupportedType);
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let sql = format!(
+        "VACUUM {}{}{}",
+        if full { "FULL " } else { "" },
+        if analyze { "ANALYZE " } else { "" },
+        qualified_table_name(table_name, schema, &creds.db_type)
+    );
+
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
+    let start = Instant::now();
+    sqlx::query(&sql)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, &sql))?;
+
+    Ok(VacuumResult {
+        duration_ms: start.elapsed().as_millis() as u64,
+        success: true,
+    })
+}
+
+/// Check a SQLite database file for corruption
+///
+/// `quick` runs `PRAGMA quick_check`, which skips verifying that indexes match their
+/// tables and is much faster on large files. Both pragmas return a single `ok` row
+/// for a healthy database and one row per problem otherwise.
+pub async fn check_sqlite_integrity(
+    database_id: &str,
+    quick: bool,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<IntegrityCheckResult, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::SQLite) {
+        return Err(DatabaseError::UnsupportedType);
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let sql = if quick {
+        "PRAGMA quick_check"
+    } else {
+        "PRAGMA integrity_check"
+    };
+    let rows = sqlx::query(sql)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, sql))?;
+
+    let mut messages = Vec::new();
+    for row in rows {
+        messages.push(
+            row.try_get::<String, _>(0)
+                .map_err(DatabaseError::from)?,
+        );
+    }
+
+    if messages.len() == 1 && messages[0] == "ok" {
+        return Ok(IntegrityCheckResult {
+            ok: true,
+            errors: Vec::new(),
+        });
+    }
+    Ok(IntegrityCheckResult {
+        ok: false,
+        errors: messages,
+    })
+}
+
+// Tauri commands for server administration
+#[tauri::command]
+pub async fn get_active_sessions(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<SessionInfo>, DatabaseErrorContext> {
+    let result = collect_active_sessions(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "collect_active_sessions"))
+}
+
+#[tauri::command]
+pub async fn terminate_session(
+    database_id: String,
+    pid: i64,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<(), DatabaseErrorContext> {
+    let result = kill_session(&database_id, pid, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "kill_session"))
+}
+
+#[tauri::command]
+pub async fn reset_table_sequence(
+    database_id: String,
+    table_name: String,
+    column_name: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<i64, DatabaseErrorContext> {
+    let result = reset_sequence(&database_id, &table_name, &column_name, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "reset_sequence").with_table(&table_name)
+        })
+}
+
+#[tauri::command]
+pub async fn refresh_database_materialized_view(
+    database_id: String,
+    view_name: String,
+    schema: Option<String>,
+    concurrently: bool,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<RefreshResult, DatabaseErrorContext> {
+    let result = refresh_materialized_view(
+        &database_id,
+        &view_name,
+        schema.as_deref(),
+        concurrently,
+        &store,
+        &pools,
+    )
+    .await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "refresh_materialized_view")
+                .with_table(&view_name)
+        })
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn vacuum_database_table(
+    database_id: String,
+    table_name: String,
+    schema: Option<String>,
+    analyze: bool,
+    full: bool,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<VacuumResult, DatabaseErrorContext> {
+    let result = vacuum_table(
+        &database_id,
+        &table_name,
+        schema.as_deref(),
+        analyze,
+        full,
+        &store,
+        &pools,
+    )
+    .await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "vacuum_table").with_table(&table_name)
+        })
+}
+
+#[tauri::command]
+pub async fn check_database_integrity(
+    database_id: String,
+    quick: bool,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<IntegrityCheckResult, DatabaseErrorContext> {
+    let result = check_sqlite_integrity(&database_id, quick, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "check_sqlite_integrity"))
+}
+
+#[tauri::command]
+pub async fn get_database_locks(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<LockInfo>, DatabaseErrorContext> {
+    let result = collect_locks(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "collect_locks"))
+}
+
+#[tauri::command]
+pub async fn get_database_replication_status(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<ReplicationStatus, DatabaseErrorContext> {
+    let result = get_replication_status(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_replication_status"))
+}
+
+#[tauri::command]
+pub async fn get_database_users(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<DatabaseUser>, DatabaseErrorContext> {
+    let result = collect_database_users(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "collect_database_users"))
+}
+
+#[tauri::command]
+pub async fn get_table_privileges(
+    database_id: String,
+    table_name: String,
+    schema: Option<String>,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<TablePrivilege>, DatabaseErrorContext> {
+    let result =
+        collect_table_privileges(&database_id, &table_name, schema.as_deref(), &store, &pools)
+            .await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "collect_table_privileges")
+                .with_table(&table_name)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_fixtures::SqliteFixture;
+
+    #[tokio::test]
+    async fn test_sqlite_has_no_sessions() {
+        let db = SqliteFixture::new();
+
+        let sessions = collect_active_sessions("sqlite-1", &db.store, &db.pools)
+            .await
+            .unwrap();
+        assert!(sessions.is_empty());
+        assert!(matches!(
+            kill_session("sqlite-1", 1, &db.store, &db.pools).await,
+            Err(DatabaseError::UnsupportedType)
+        ));
+        assert!(matches!(
+            collect_locks("sqlite-1", &db.store, &db.pools).await,
+            Err(DatabaseError::UnsupportedType)
+        ));
+        assert!(matches!(
+            reset_sequence("sqlite-1", "items", "id", &db.store, &db.pools).await,
+            Err(DatabaseError::UnsupportedType)
+        ));
+
+        assert!(matches!(
+            collect_database_users("sqlite-1", &db.store, &db.pools).await,
+            Err(DatabaseError::UnsupportedType)
+        ));
+        assert!(matches!(
+            collect_table_privileges("sqlite-1", "items", None, &db.store, &db.pools).await,
+            Err(DatabaseError::UnsupportedType)
+        ));
+
+        assert!(matches!(
+            refresh_materialized_view("sqlite-1", "items", None, false, &db.store, &db.pools).await,
+            Err(DatabaseError::UnsupportedType)
+        ));
+        assert!(matches!(
+            vacuum_table("sqlite-1", "items", None, true, false, &db.store, &db.pools).await,
+            Err(DatabaseError::UnsupportedType)
+        ));
+
+        let replication = get_replication_status("sqlite-1", &db.store, &db.pools)
+            .await
+            .unwrap();
+        assert!(!replication.is_in_recovery);
+        assert!(replication.wal_lsn.is_none());
+        assert!(replication.slots.is_empty());
+        assert!(replication.warning.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_integrity_check() {
+        let db = SqliteFixture::new();
+
+        let pool = db.pool().await;
+        for sql in [
+            "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT)",
+            "CREATE INDEX idx_items_name ON items (name)",
+            "INSERT INTO items (name) VALUES ('a'), ('b')",
+        ] {
+            sqlx::query(sql).execute(&*pool).await.unwrap();
+        }
+
+        let full = check_sqlite_integrity("sqlite-1", false, &db.store, &db.pools)
+            .await
+            .unwrap();
+        assert!(full.ok);
+        assert!(full.errors.is_empty());
+
+        let quick = check_sqlite_integrity("sqlite-1", true, &db.store, &db.pools)
+            .await
+            .unwrap();
+        assert!(quick.ok);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, set INSPEKTOR_TEST_POSTGRES_URL"]
+    async fn test_postgres_reset_sequence() {
+        let url = std::env::var("INSPEKTOR_TEST_POSTGRES_URL").unwrap();
+        let dir = std::env::temp_dir().join(format!("inspektor-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let creds = crate::db::types::DatabaseCredentials::from_url(&url).unwrap();
+        let store = CredentialStore::new(dir.join("credentials.json"));
+        store.add(creds.clone()).unwrap();
+        let pools = PoolRegistry::new();
+
+        let table = format!("inspektor_seq_{}", uuid::Uuid::new_v4().simple());
+        let pool = pools.get_or_create(&creds.id, &creds).await.unwrap();
+        for sql in [
+            format!("CREATE TABLE {} (id SERIAL PRIMARY KEY, name TEXT, qty INTEGER)", table),
+            format!("INSERT INTO {} (id, name) VALUES (1, 'a'), (2, 'b'), (7, 'c')", table),
+            format!("SELECT setval(pg_get_serial_sequence('{}', 'id'), 3)", table),
+        ] {
+            sqlx::query(&sql).execute(&*pool).await.unwrap();
+        }
+
+        let value = reset_sequence(&creds.id, &table, "id", &store, &pools).await;
+        let next: Result<i64, _> = sqlx::query_scalar(&format!(
+            "INSERT INTO {} (name) VALUES ('d') RETURNING id::bigint",
+            table
+        ))
+        .fetch_one(&*pool)
+        .await;
+        let missing = reset_sequence(&creds.id, &table, "qty", &store, &pools).await;
+
+        sqlx::query(&format!("DROP TABLE {}", table))
+            .execute(&*pool)
+            .await
+            .unwrap();
+        pools.evict(&creds.id).await;
+        std::fs::remove_dir_all(dir).ok();
+
+        assert_eq!(value.unwrap(), 7);
+        assert_eq!(next.unwrap(), 8);
+        assert!(missing.is_err());
+    }
+}
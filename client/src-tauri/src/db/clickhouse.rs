@@ -0,0 +1,284 @@
+use super::connection::{connection_target, resolve_credentials};
+use super::types::{
+    ColumnInfo, DatabaseCredentials, DatabaseError, NormalizedType, SslMode, TableInfo,
+    TableSchema, TableType,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use url::Url;
+
+/// Port of the ClickHouse HTTP interface when the credentials do not set one
+pub const DEFAULT_CLICKHOUSE_PORT: u16 = 8123;
+
+/// Rows returned by a ClickHouse query, with the column types it reported
+#[derive(Debug, Clone, Default)]
+pub struct ClickHouseRows {
+    pub columns: Vec<String>,
+    pub column_types: Vec<NormalizedType>,
+    pub rows: Vec<HashMap<String, Value>>,
+}
+
+/// Run one statement through the ClickHouse HTTP interface
+///
+/// sqlx has no ClickHouse driver, so statements are posted to the server instead of
+/// going through a pool. Each call opens its own connection (and SSH tunnel, if one
+/// is configured). Results are requested in the `JSON` format with 64-bit integers
+/// as numbers; statements that return no rows (DDL, INSERT) give an empty result.
+/// The server cancels a read-only query when the request is dropped, e.g. because
+/// it timed out.
+pub async fn run_query(
+    creds: &DatabaseCredentials,
+    sql: &str,
+) -> Result<ClickHouseRows, DatabaseError> {
+    let resolved = resolve_credentials(creds, &HashMap::new())?;
+    let (target, _tunnel) = connection_target(creds).await?;
+    let target = Url::parse(&target)
+        .map_err(|e| DatabaseError::CredentialsError(format!("Invalid connection URL: {}", e)))?;
+
+    let https = matches!(
+        resolved.ssl_mode,
+        Some(SslMode::Require | SslMode::VerifyCa | SslMode::VerifyFull)
+    );
+    let mut endpoint = Url::parse(&format!(
+        "{}://{}:{}/",
+        if https { "https" } else { "http" },
+        target.host_str().unwrap_or("localhost"),
+        target.port().unwrap_or(DEFAULT_CLICKHOUSE_PORT)
+    ))
+    .map_err(|e| DatabaseError::CredentialsError(format!("Invalid connection URL: {}", e)))?;
+    endpoint
+        .query_pairs_mut()
+        .append_pair("database", &resolved.database)
+        .append_pair("default_format", "JSON")
+        .append_pair("output_format_json_quote_64bit_integers", "0")
+        .append_pair("cancel_http_readonly_queries_on_client_close", "1")
+        .extend_pairs(target.query_pairs());
+
+    let response = http_client(&resolved)?
+        .post(endpoint)
+        .header(
+            "X-ClickHouse-User",
+            resolved.username.as_deref().unwrap_or("default"),
+        )
+        .header(
+            "X-ClickHouse-Key",
+            resolved.password.as_deref().unwrap_or(""),
+        )
+        .body(sql.to_string())
+        .send()
+        .await
+        .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
+
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
+    if !status.is_success() {
+        return Err(DatabaseError::QueryError(format!(
+            "{}\n\nSQL Query:\n{}",
+            body.trim(),
+            sql
+        )));
+    }
+
+    parse_json_response(&body)
+}
+
+/// HTTP client honoring the connect timeout and TLS settings of the credentials
+///
+/// `SslMode::Require` encrypts without verifying the server certificate, like the
+/// Postgres mode of the same name.
+fn http_client(creds: &DatabaseCredentials) -> Result<reqwest::Client, DatabaseError> {
+    let read_file = |path: &String| {
+        std::fs::read(path).map_err(|e| {
+            DatabaseError::CredentialsError(format!("Failed to read {}: {}", path, e))
+        })
+    };
+
+    let mut builder = reqwest::Client::builder();
+    if let Some(secs) = creds.connect_timeout {
+        builder = builder.connect_timeout(Duration::from_secs(secs));
+    }
+    if matches!(creds.ssl_mode, Some(SslMode::Require)) {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    if let Some(path) = &creds.ssl_ca_cert_path {
+        let cert = reqwest::Certificate::from_pem(&read_file(path)?)
+            .map_err(|e| DatabaseError::CredentialsError(format!("{}: {}", path, e)))?;
+        builder = builder.add_root_certificate(cert);
+    }
+    if let (Some(cert_path), Some(key_path)) =
+        (&creds.ssl_client_cert_path, &creds.ssl_client_key_path)
+    {
+        let mut pem = read_file(cert_path)?;
+        pem.extend(read_file(key_path)?);
+        let identity = reqwest::Identity::from_pem(&pem)
+            .map_err(|e| DatabaseError::CredentialsError(format!("{}: {}", cert_path, e)))?;
+        builder = builder.identity(identity);
+    }
+
+    builder
+        .build()
+        .map_err(|e| DatabaseError::ConnectionError(e.to_string()))
+}
+
+/// Parse the body of a `FORMAT JSON` response
+fn parse_json_response(body: &str) -> Result<ClickHouseRows, DatabaseError> {
+    if body.trim().is_empty() {
+        return Ok(ClickHouseRows::default());
+    }
+
+    let parsed: Value = serde_json::from_str(body).map_err(|e| {
+        DatabaseError::QueryError(format!("Invalid response from ClickHouse: {}", e))
+    })?;
+
+    let mut result = ClickHouseRows::default();
+    for column in parsed["meta"].as_array().into_iter().flatten() {
+        result
+            .columns
+            .push(column["name"].as_str().unwrap_or_default().to_string());
+        result.column_types.push(NormalizedType::from_data_type(
+            column["type"].as_str().unwrap_or_default(),
+        ));
+    }
+    for row in parsed["data"].as_array().into_iter().flatten() {
+        if let Value::Object(values) = row {
+            result
+                .rows
+                .push(values.iter().map(|(k, v)| (k.clone(), v.clone())).collect());
+        }
+    }
+
+    Ok(result)
+}
+
+/// Value of `column` in the first row as a string
+fn first_string(result: &ClickHouseRows, column: &str) -> Option<String> {
+    result
+        .rows
+        .first()
+        .and_then(|row| row.get(column))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// Server version from `SELECT version()`
+pub async fn server_version(creds: &DatabaseCredentials) -> Result<String, DatabaseError> {
+    let result = run_query(creds, "SELECT version() AS version").await?;
+    first_string(&result, "version")
+        .ok_or_else(|| DatabaseError::QueryError("Missing server version".to_string()))
+}
+
+/// List the tables and views of the credentials' database from `system.tables`
+pub async fn get_tables(creds: &DatabaseCredentials) -> Result<Vec<TableInfo>, DatabaseError> {
+    let query = format!(
+        "SELECT name, database, engine, total_rows FROM system.tables \
+         WHERE database = '{}' ORDER BY name",
+        creds.database.replace('\'', "''")
+    );
+    let result = run_query(creds, &query).await?;
+
+    Ok(result
+        .rows
+        .iter()
+        .map(|row| {
+            let table_type = match row.get("engine").and_then(Value::as_str) {
+                Some("View") => TableType::View,
+                Some("MaterializedView") => TableType::MaterializedView,
+                _ => TableType::BaseTable,
+            };
+            TableInfo {
+                name: row["name"].as_str().unwrap_or_default().to_string(),
+                schema: row
+                    .get("database")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                row_count: row.get("total_rows").and_then(Value::as_i64),
+                table_type,
+                parent_table: None,
+            }
+        })
+        .collect())
+}
+
+/// Column details for the tables in `table_names` (a list such as `('events', 'users')`)
+/// from `system.columns`
+///
+/// ClickHouse only marks columns as nullable through their type, e.g. `Nullable(String)`.
+pub async fn get_table_schema(
+    creds: &DatabaseCredentials,
+    table_names: &str,
+) -> Result<Vec<TableSchema>, DatabaseError> {
+    let query = format!(
+        "SELECT table, name, type, default_expression, is_in_primary_key FROM system.columns \
+         WHERE database = '{}' AND table IN {} ORDER BY table, position",
+        creds.database.replace('\'', "''"),
+        table_names
+    );
+    let result = run_query(creds, &query).await?;
+
+    let mut schemas: Vec<TableSchema> = Vec::new();
+    for row in &result.rows {
+        let table_name = row["table"].as_str().unwrap_or_default().to_string();
+        let data_type = row["type"].as_str().unwrap_or_default().to_string();
+        let column = ColumnInfo {
+            name: row["name"].as_str().unwrap_or_default().to_string(),
+            is_nullable: data_type.starts_with("Nullable("),
+            is_primary_key: row.get("is_in_primary_key").and_then(Value::as_u64) == Some(1),
+            default_value: row
+                .get("default_expression")
+                .and_then(Value::as_str)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string),
+            data_type,
+            character_max_length: None,
+            is_unique: false,
+            unique_constraint_name: None,
+            is_inherited: false,
+        };
+
+        match schemas.last_mut() {
+            Some(schema) if schema.table_name == table_name => schema.columns.push(column),
+            _ => schemas.push(TableSchema {
+                table_name,
+                schema: Some(creds.database.clone()),
+                columns: vec![column],
+            }),
+        }
+    }
+
+    Ok(schemas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_response() {
+        let body = r#"{
+            "meta": [
+                {"name": "id", "type": "UInt64"},
+                {"name": "label", "type": "Nullable(String)"}
+            ],
+            "data": [{"id": 1, "label": "a"}, {"id": 2, "label": null}],
+            "rows": 2
+        }"#;
+        let result = parse_json_response(body).unwrap();
+        assert_eq!(result.columns, vec!["id", "label"]);
+        assert_eq!(
+            result.column_types,
+            vec![NormalizedType::Integer, NormalizedType::Text]
+        );
+        assert_eq!(result.rows.len(), 2);
+        assert_eq!(result.rows[1]["label"], Value::Null);
+
+        // DDL and INSERT statements come back without a body
+        let empty = parse_json_response("").unwrap();
+        assert!(empty.columns.is_empty() && empty.rows.is_empty());
+
+        assert!(parse_json_response("Code: 62. DB::Exception").is_err());
+    }
+}
@@ -1,26 +1,243 @@
-use super::types::{DatabaseCredentials, DatabaseError};
+use super::connection::{test_connection, PoolRegistry};
+use super::encryption::{DerivedKey, EncryptedConnection, EncryptionService};
+use super::types::{
+    with_context, DatabaseCredentials, DatabaseError, DatabaseErrorContext, DatabaseType,
+};
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
 use serde_json;
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
 use tauri::State;
 
-/// In-memory credential store (encrypted storage would be better for production)
-/// For now, we'll use Tauri's built-in store plugin
+/// Version of the on-disk credentials file format
+const CREDENTIALS_FILE_VERSION: u8 = 1;
+
+/// On-disk representation of the credential store
+#[derive(Debug, Serialize, Deserialize)]
+struct CredentialsFile {
+    version: u8,
+    credentials: Vec<StoredCredential>,
+}
+
+/// Credentials as written to disk: the password and SSH key passphrase are kept out
+/// of the plaintext record and stored encrypted next to it
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredCredential {
+    credentials: DatabaseCredentials,
+    encrypted_password: Option<EncryptedConnection>,
+    #[serde(default)]
+    encrypted_ssh_passphrase: Option<EncryptedConnection>,
+}
+
+/// Version of the credential backup format
+const CREDENTIAL_BACKUP_VERSION: u8 = 1;
+
+/// Password-protected export of saved connections, for moving them between machines
+///
+/// Each entry holds one `DatabaseCredentials` serialized as JSON and encrypted with
+/// the backup password, including its database password.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CredentialBackup {
+    pub version: u8,
+    pub entries: Vec<EncryptedConnection>,
+}
+
+/// What to do when an imported credential has the id of an existing one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionStrategy {
+    /// Keep the existing credential and drop the imported one
+    Skip,
+    /// Replace the existing credential with the imported one
+    Overwrite,
+    /// Keep both, giving the imported credential a new id and an "(imported)" suffix
+    RenameImported,
+}
+
+/// Credential store persisted as a JSON file in the app data directory
+///
+/// Every mutation is written to disk before it becomes visible in memory, using a
+/// temporary file and a rename so a crash never leaves a half-written file behind.
 pub struct CredentialStore {
     credentials: Mutex<HashMap<String, DatabaseCredentials>>,
+    path: PathBuf,
+    name_validator: RwLock<NameValidator>,
+    /// Key derived from the machine secret, kept so writes do not run Argon2 again
+    at_rest_key: Mutex<Option<DerivedKey>>,
+}
+
+/// Rule applied to connection names before credentials are added or updated
+pub type NameValidator = Box<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+/// Maximum length of a connection name accepted by the default validator
+const MAX_CONNECTION_NAME_LENGTH: usize = 100;
+
+/// Default rule: any non-empty name of at most 100 characters
+fn default_name_validator(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Connection name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_CONNECTION_NAME_LENGTH {
+        return Err(format!(
+            "Connection name cannot be longer than {} characters",
+            MAX_CONNECTION_NAME_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+/// Validator that also requires a name to start with one of `prefixes`
+fn prefix_name_validator(prefixes: Vec<String>) -> NameValidator {
+    Box::new(move |name| {
+        default_name_validator(name)?;
+        if prefixes.iter().any(|prefix| name.starts_with(prefix.as_str())) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Connection name must start with one of: {}",
+                prefixes.join(", ")
+            ))
+        }
+    })
 }
 
 impl CredentialStore {
-    pub fn new() -> Self {
+    /// Create a store backed by `path`, loading existing credentials when the file is
+    /// readable and starting empty otherwise
+    pub fn new(path: PathBuf) -> Self {
+        Self::load(path.clone()).unwrap_or_else(|_| Self::empty(path))
+    }
+
+    /// Load the store from `path`; a missing file yields an empty store
+    pub fn load(path: PathBuf) -> Result<Self, DatabaseError> {
+        if !path.exists() {
+            return Ok(Self::empty(path));
+        }
+
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            DatabaseError::StorageError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        let file: CredentialsFile = serde_json::from_str(&contents)
+            .map_err(|e| DatabaseError::StorageError(format!("Invalid credentials file: {}", e)))?;
+
+        let data_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let mut keys = KeyCache::new(machine_secret(data_dir)?);
+        let mut legacy_keys = KeyCache::new(legacy_machine_key());
+        let mut migrated = false;
+        let mut decrypt = |encrypted: EncryptedConnection| {
+            keys.decrypt(&encrypted).or_else(|_| {
+                migrated = true;
+                legacy_keys.decrypt(&encrypted)
+            })
+        };
+
+        let mut credentials = HashMap::new();
+        for stored in file.credentials {
+            let mut creds = stored.credentials;
+            creds.password = stored.encrypted_password.map(&mut decrypt).transpose()?;
+            creds.ssh_private_key_passphrase =
+                stored.encrypted_ssh_passphrase.map(&mut decrypt).transpose()?;
+            credentials.insert(creds.id.clone(), creds);
+        }
+
+        let store = Self {
+            credentials: Mutex::new(HashMap::new()),
+            path,
+            name_validator: RwLock::new(Box::new(default_name_validator)),
+            at_rest_key: Mutex::new(keys.keys.pop()),
+        };
+        // Files written before the machine secret existed are re-encrypted with it
+        if migrated {
+            store.persist(&credentials)?;
+        }
+        *store.credentials.lock().unwrap() = credentials;
+        Ok(store)
+    }
+
+    fn empty(path: PathBuf) -> Self {
         Self {
             credentials: Mutex::new(HashMap::new()),
+            path,
+            name_validator: RwLock::new(Box::new(default_name_validator)),
+            at_rest_key: Mutex::new(None),
+        }
+    }
+
+    /// Replace the rule used to validate connection names, e.g. to require a
+    /// `prod-`, `dev-` or `staging-` prefix
+    pub fn set_name_validator(&self, validator: NameValidator) {
+        *self.name_validator.write().unwrap() = validator;
+    }
+
+    /// Check a connection name against the current validator
+    pub fn validate_name(&self, name: &str) -> Result<(), DatabaseError> {
+        let validator = self.name_validator.read().unwrap();
+        validator(name).map_err(DatabaseError::CredentialsError)
+    }
+
+    /// Write the given credentials to disk atomically
+    fn persist(&self, credentials: &HashMap<String, DatabaseCredentials>) -> Result<(), DatabaseError> {
+        let key = self.at_rest_key()?;
+        let mut stored = Vec::with_capacity(credentials.len());
+        for creds in credentials.values() {
+            let encrypted_password = creds
+                .password
+                .as_deref()
+                .map(|password| EncryptionService::encrypt_with_key(password, &key, &creds.name))
+                .transpose()?;
+            let encrypted_ssh_passphrase = creds
+                .ssh_private_key_passphrase
+                .as_deref()
+                .map(|passphrase| {
+                    EncryptionService::encrypt_with_key(passphrase, &key, &creds.name)
+                })
+                .transpose()?;
+
+            let mut plain = creds.clone();
+            plain.password = None;
+            plain.ssh_private_key_passphrase = None;
+            stored.push(StoredCredential {
+                credentials: plain,
+                encrypted_password,
+                encrypted_ssh_passphrase,
+            });
+        }
+
+        let file = CredentialsFile {
+            version: CREDENTIALS_FILE_VERSION,
+            credentials: stored,
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| DatabaseError::StorageError(e.to_string()))?;
+
+        write_atomically(&self.path, json.as_bytes())
+    }
+
+    /// Key for encrypting passwords at rest, derived from the machine secret on first use
+    fn at_rest_key(&self) -> Result<DerivedKey, DatabaseError> {
+        let mut cached = self.at_rest_key.lock().unwrap();
+        if let Some(key) = cached.as_ref() {
+            return Ok(key.clone());
         }
+        let key = EncryptionService::new_key(&machine_secret(self.data_dir())?, None)?;
+        *cached = Some(key.clone());
+        Ok(key)
     }
 
-    pub fn add(&self, creds: DatabaseCredentials) -> Result<(), DatabaseError> {
+    /// Add credentials, returning the strength of the password when one is set
+    pub fn add(&self, creds: DatabaseCredentials) -> Result<Option<PasswordStrength>, DatabaseError> {
+        self.validate_name(&creds.name)?;
+        let strength = creds.password.as_deref().map(validate_password_strength);
         let mut store = self.credentials.lock().unwrap();
-        store.insert(creds.id.clone(), creds);
-        Ok(())
+        let mut updated = store.clone();
+        updated.insert(creds.id.clone(), creds);
+        self.persist(&updated)?;
+        *store = updated;
+        Ok(strength)
     }
 
     pub fn get(&self, id: &str) -> Result<DatabaseCredentials, DatabaseError> {
@@ -31,20 +248,62 @@ impl CredentialStore {
             .ok_or_else(|| DatabaseError::CredentialsError(format!("Credentials not found: {}", id)))
     }
 
-    pub fn list(&self) -> Result<Vec<DatabaseCredentials>, DatabaseError> {
+    /// List the credentials that have `tag` and belong to `group`, when given
+    ///
+    /// Like `search`, the results never include secrets; use `get` for the
+    /// credentials needed to connect.
+    pub fn list_filtered(
+        &self,
+        tag: Option<&str>,
+        group: Option<&str>,
+    ) -> Result<Vec<DatabaseCredentials>, DatabaseError> {
+        let store = self.credentials.lock().unwrap();
+        Ok(store
+            .values()
+            .filter(|creds| tag.is_none_or(|tag| creds.tags.iter().any(|t| t == tag)))
+            .filter(|creds| group.is_none_or(|group| creds.group.as_deref() == Some(group)))
+            .map(without_secrets)
+            .collect())
+    }
+
+    /// Find credentials whose name, host or database contains `query`, ignoring case
+    ///
+    /// The results never include secrets, see `without_secrets`.
+    pub fn search(
+        &self,
+        query: &str,
+        db_type: Option<&DatabaseType>,
+    ) -> Result<Vec<DatabaseCredentials>, DatabaseError> {
+        let query = query.to_lowercase();
         let store = self.credentials.lock().unwrap();
-        Ok(store.values().cloned().collect())
+        let mut matches: Vec<DatabaseCredentials> = store
+            .values()
+            .filter(|creds| db_type.is_none_or(|db_type| &creds.db_type == db_type))
+            .filter(|creds| {
+                [Some(creds.name.as_str()), creds.host.as_deref(), Some(creds.database.as_str())]
+                    .into_iter()
+                    .flatten()
+                    .any(|field| field.to_lowercase().contains(&query))
+            })
+            .map(without_secrets)
+            .collect();
+        matches.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(matches)
     }
 
     pub fn remove(&self, id: &str) -> Result<(), DatabaseError> {
         let mut store = self.credentials.lock().unwrap();
-        store
+        let mut updated = store.clone();
+        updated
             .remove(id)
             .ok_or_else(|| DatabaseError::CredentialsError(format!("Credentials not found: {}", id)))?;
+        self.persist(&updated)?;
+        *store = updated;
         Ok(())
     }
 
     pub fn update(&self, creds: DatabaseCredentials) -> Result<(), DatabaseError> {
+        self.validate_name(&creds.name)?;
         let mut store = self.credentials.lock().unwrap();
         if !store.contains_key(&creds.id) {
             return Err(DatabaseError::CredentialsError(format!(
@@ -52,9 +311,323 @@ impl CredentialStore {
                 creds.id
             )));
         }
-        store.insert(creds.id.clone(), creds);
+        let mut updated = store.clone();
+        updated.insert(creds.id.clone(), creds);
+        self.persist(&updated)?;
+        *store = updated;
+        Ok(())
+    }
+
+    /// Replace the password of stored credentials, leaving every other field as is
+    pub fn update_password(&self, id: &str, password: String) -> Result<(), DatabaseError> {
+        self.modify(id, |creds| creds.password = Some(password))
+    }
+
+    /// Save a copy of stored credentials under a new id and `new_name`, returning the new id
+    pub fn duplicate(&self, id: &str, new_name: &str) -> Result<String, DatabaseError> {
+        let mut copy = self.get(id)?;
+        copy.id = uuid::Uuid::new_v4().to_string();
+        copy.name = new_name.to_string();
+
+        let new_id = copy.id.clone();
+        self.add(copy)?;
+        Ok(new_id)
+    }
+
+    /// Add a tag to stored credentials; adding a tag they already have does nothing
+    pub fn add_tag(&self, id: &str, tag: &str) -> Result<(), DatabaseError> {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return Err(DatabaseError::CredentialsError("Tag cannot be empty".to_string()));
+        }
+        self.modify(id, |creds| {
+            if !creds.tags.iter().any(|t| t == tag) {
+                creds.tags.push(tag.to_string());
+            }
+        })
+    }
+
+    pub fn remove_tag(&self, id: &str, tag: &str) -> Result<(), DatabaseError> {
+        self.modify(id, |creds| creds.tags.retain(|t| t != tag.trim()))
+    }
+
+    /// Apply `change` to stored credentials and persist the result
+    fn modify(
+        &self,
+        id: &str,
+        change: impl FnOnce(&mut DatabaseCredentials),
+    ) -> Result<(), DatabaseError> {
+        let mut store = self.credentials.lock().unwrap();
+        let mut updated = store.clone();
+        change(updated.get_mut(id).ok_or_else(|| {
+            DatabaseError::CredentialsError(format!("Credentials not found: {}", id))
+        })?);
+        self.persist(&updated)?;
+        *store = updated;
         Ok(())
     }
+
+    /// Encrypt the given credentials with `password` for export to another machine
+    pub fn export_backup(
+        &self,
+        ids: &[String],
+        password: &str,
+    ) -> Result<CredentialBackup, DatabaseError> {
+        let mut entries = Vec::with_capacity(ids.len());
+        for id in ids {
+            let creds = self.get(id)?;
+            let json = serde_json::to_string(&creds)
+                .map_err(|e| DatabaseError::StorageError(e.to_string()))?;
+            entries.push(EncryptionService::encrypt(&json, password, &creds.name, None)?);
+        }
+
+        Ok(CredentialBackup {
+            version: CREDENTIAL_BACKUP_VERSION,
+            entries,
+        })
+    }
+
+    /// Decrypt and add the credentials of a backup, returning the ids that were added
+    ///
+    /// Every entry is decrypted before anything is stored, so a wrong password or a
+    /// corrupt entry leaves the store untouched.
+    pub fn import_backup(
+        &self,
+        backup: &CredentialBackup,
+        password: &str,
+        collision_strategy: CollisionStrategy,
+    ) -> Result<Vec<String>, DatabaseError> {
+        if backup.version != CREDENTIAL_BACKUP_VERSION {
+            return Err(DatabaseError::StorageError(format!(
+                "Unsupported backup version: {}",
+                backup.version
+            )));
+        }
+
+        let mut imported = Vec::with_capacity(backup.entries.len());
+        for entry in &backup.entries {
+            let json = EncryptionService::decrypt(entry, password)?;
+            let creds: DatabaseCredentials = serde_json::from_str(&json).map_err(|e| {
+                DatabaseError::CredentialsError(format!("Invalid credentials in backup: {}", e))
+            })?;
+            self.validate_name(&creds.name)?;
+            imported.push(creds);
+        }
+
+        let mut store = self.credentials.lock().unwrap();
+        let mut updated = store.clone();
+        let mut added = Vec::with_capacity(imported.len());
+        for mut creds in imported {
+            if updated.contains_key(&creds.id) {
+                match collision_strategy {
+                    CollisionStrategy::Skip => continue,
+                    CollisionStrategy::Overwrite => {}
+                    CollisionStrategy::RenameImported => {
+                        creds.id = uuid::Uuid::new_v4().to_string();
+                        creds.name = format!("{} (imported)", creds.name);
+                        self.validate_name(&creds.name)?;
+                    }
+                }
+            }
+            added.push(creds.id.clone());
+            updated.insert(creds.id.clone(), creds);
+        }
+        self.persist(&updated)?;
+        *store = updated;
+        Ok(added)
+    }
+
+    /// Directory holding the credentials file, shared with other app data files
+    pub fn data_dir(&self) -> &Path {
+        self.path.parent().unwrap_or_else(|| Path::new("."))
+    }
+}
+
+/// Copy of the credentials without the password, SSH key passphrase and environment
+/// variable values, for listings sent to the frontend
+///
+/// Variable names are kept so the connection form can show them; their values are
+/// often the password itself.
+fn without_secrets(creds: &DatabaseCredentials) -> DatabaseCredentials {
+    let mut redacted = creds.clone();
+    redacted.password = None;
+    redacted.ssh_private_key_passphrase = None;
+    if let Some(variables) = redacted.environment_variables.as_mut() {
+        variables.values_mut().for_each(String::clear);
+    }
+    redacted
+}
+
+/// Write `contents` to a temporary file next to `path`, then rename it into place
+pub(crate) fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), DatabaseError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            DatabaseError::StorageError(format!("Failed to create {}: {}", parent.display(), e))
+        })?;
+    }
+
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, contents).map_err(|e| {
+        DatabaseError::StorageError(format!("Failed to write {}: {}", tmp_path.display(), e))
+    })?;
+    fs::rename(&tmp_path, path).map_err(|e| {
+        DatabaseError::StorageError(format!("Failed to replace {}: {}", path.display(), e))
+    })?;
+
+    Ok(())
+}
+
+/// Keys derived from one password, so values sharing a salt only run Argon2 once
+struct KeyCache {
+    password: String,
+    keys: Vec<DerivedKey>,
+}
+
+impl KeyCache {
+    fn new(password: String) -> Self {
+        Self {
+            password,
+            keys: Vec::new(),
+        }
+    }
+
+    fn decrypt(&mut self, encrypted: &EncryptedConnection) -> Result<String, DatabaseError> {
+        if let Some(key) = self.keys.iter().find(|key| key.matches(encrypted)) {
+            return EncryptionService::decrypt_with_key(encrypted, key);
+        }
+        let key = EncryptionService::key_for(encrypted, &self.password)?;
+        let plaintext = EncryptionService::decrypt_with_key(encrypted, &key)?;
+        self.keys.push(key);
+        Ok(plaintext)
+    }
+}
+
+/// File in the data directory holding the secret passwords at rest are encrypted with
+const MACHINE_SECRET_FILE: &str = "machine_secret";
+
+/// Random secret used to encrypt passwords at rest, created on first use
+///
+/// This keeps passwords out of the credentials file in plaintext; it does not
+/// protect against someone with access to the user's account, who can read both.
+fn machine_secret(data_dir: &Path) -> Result<String, DatabaseError> {
+    let path = data_dir.join(MACHINE_SECRET_FILE);
+    if let Ok(secret) = fs::read_to_string(&path) {
+        if !secret.trim().is_empty() {
+            return Ok(secret.trim().to_string());
+        }
+    }
+
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let secret = general_purpose::STANDARD.encode(bytes);
+    write_atomically(&path, secret.as_bytes())?;
+    Ok(secret)
+}
+
+/// Key passwords were encrypted with before the machine secret, derived from the
+/// machine and user identity; only used to read files written back then
+fn legacy_machine_key() -> String {
+    let machine_id = ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .find_map(|p| fs::read_to_string(p).ok())
+        .map(|id| id.trim().to_string())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .unwrap_or_default();
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+
+    format!("inspektor:{}:{}", machine_id, user)
+}
+
+/// Passwords that are rejected as weak regardless of their composition
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456", "password", "12345678", "qwerty", "123456789", "12345", "1234", "111111",
+    "1234567", "dragon", "123123", "baseball", "abc123", "football", "monkey", "letmein",
+    "696969", "shadow", "master", "666666",
+];
+
+/// Strength of a database password, with a 0-100 score and improvement hints
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "level", rename_all = "snake_case")]
+pub enum PasswordStrength {
+    Weak { score: u8, suggestions: Vec<&'static str> },
+    Fair { score: u8, suggestions: Vec<&'static str> },
+    Strong { score: u8, suggestions: Vec<&'static str> },
+    VeryStrong { score: u8, suggestions: Vec<&'static str> },
+}
+
+/// Rate a password by length, character classes and membership in a common password list
+pub fn validate_password_strength(password: &str) -> PasswordStrength {
+    let mut score: u8 = 0;
+    let mut suggestions = Vec::new();
+    let length = password.chars().count();
+
+    if length >= 8 {
+        score += 15;
+    } else {
+        suggestions.push("Use at least 8 characters");
+    }
+    if length >= 12 {
+        score += 15;
+    } else if length >= 8 {
+        suggestions.push("Use 12 or more characters for a stronger password");
+    }
+    if length >= 16 {
+        score += 10;
+    }
+
+    if password.chars().any(|c| c.is_uppercase()) {
+        score += 15;
+    } else {
+        suggestions.push("Add uppercase letters");
+    }
+    if password.chars().any(|c| c.is_lowercase()) {
+        score += 15;
+    } else {
+        suggestions.push("Add lowercase letters");
+    }
+    if password.chars().any(|c| c.is_ascii_digit()) {
+        score += 15;
+    } else {
+        suggestions.push("Add digits");
+    }
+    if password.chars().any(|c| !c.is_alphanumeric()) {
+        score += 15;
+    } else {
+        suggestions.push("Add special characters");
+    }
+
+    let is_common = COMMON_PASSWORDS.contains(&password.to_lowercase().as_str());
+    if is_common {
+        score = 0;
+        suggestions.insert(0, "Avoid commonly used passwords");
+    }
+
+    if length < 8 || is_common || score < 40 {
+        PasswordStrength::Weak { score, suggestions }
+    } else if score < 70 {
+        PasswordStrength::Fair { score, suggestions }
+    } else if score < 90 {
+        PasswordStrength::Strong { score, suggestions }
+    } else {
+        PasswordStrength::VeryStrong { score, suggestions }
+    }
+}
+
+/// Reject credentials that are missing fields needed to connect
+fn validate_credentials(credentials: &DatabaseCredentials) -> Result<(), DatabaseError> {
+    credentials
+        .validate()
+        .map_err(|errors| DatabaseError::CredentialsError(errors.join(", ")))
+}
+
+/// Response of `save_credentials`
+#[derive(Debug, Clone, Serialize)]
+pub struct SaveCredentialsResponse {
+    pub id: String,
+    pub password_strength: Option<PasswordStrength>,
 }
 
 // Tauri commands for credential management
@@ -62,40 +635,493 @@ impl CredentialStore {
 pub async fn save_credentials(
     credentials: DatabaseCredentials,
     store: State<'_, CredentialStore>,
-) -> Result<String, String> {
-    store
-        .add(credentials.clone())
-        .map_err(|e| e.to_string())?;
-    Ok(credentials.id)
+) -> Result<SaveCredentialsResponse, DatabaseErrorContext> {
+    let id = credentials.id.clone();
+    validate_credentials(&credentials)
+        .map_err(|e| with_context(e, Some(id.as_str()), "validate_credentials"))?;
+    let password_strength = store
+        .add(credentials)
+        .map_err(|e| with_context(e, Some(id.as_str()), "add_credentials"))?;
+    Ok(SaveCredentialsResponse {
+        id,
+        password_strength,
+    })
 }
 
 #[tauri::command]
 pub async fn get_credentials(
     id: String,
     store: State<'_, CredentialStore>,
-) -> Result<DatabaseCredentials, String> {
-    store.get(&id).map_err(|e| e.to_string())
+) -> Result<DatabaseCredentials, DatabaseErrorContext> {
+    store
+        .get(&id)
+        .map_err(|e| with_context(e, Some(id.as_str()), "get_credentials"))
+}
+
+#[tauri::command]
+pub async fn duplicate_credential(
+    id: String,
+    new_name: String,
+    store: State<'_, CredentialStore>,
+) -> Result<String, DatabaseErrorContext> {
+    store
+        .duplicate(&id, &new_name)
+        .map_err(|e| with_context(e, Some(id.as_str()), "duplicate"))
 }
 
 #[tauri::command]
 pub async fn list_credentials(
+    tag: Option<String>,
+    group: Option<String>,
+    store: State<'_, CredentialStore>,
+) -> Result<Vec<DatabaseCredentials>, DatabaseErrorContext> {
+    store
+        .list_filtered(tag.as_deref(), group.as_deref())
+        .map_err(|e| with_context(e, None, "list_credentials"))
+}
+
+#[tauri::command]
+pub async fn search_credentials(
+    query: String,
+    db_type: Option<DatabaseType>,
+    store: State<'_, CredentialStore>,
+) -> Result<Vec<DatabaseCredentials>, DatabaseErrorContext> {
+    store
+        .search(&query, db_type.as_ref())
+        .map_err(|e| with_context(e, None, "search_credentials"))
+}
+
+/// Parse a connection URL into unsaved credentials, password included, for review
+#[tauri::command]
+pub async fn parse_connection_url(
+    url: String,
+) -> Result<DatabaseCredentials, DatabaseErrorContext> {
+    DatabaseCredentials::from_url(&url).map_err(|e| with_context(e, None, "from_url"))
+}
+
+#[tauri::command]
+pub async fn add_tag_to_credential(
+    id: String,
+    tag: String,
     store: State<'_, CredentialStore>,
-) -> Result<Vec<DatabaseCredentials>, String> {
-    store.list().map_err(|e| e.to_string())
+) -> Result<(), DatabaseErrorContext> {
+    store
+        .add_tag(&id, &tag)
+        .map_err(|e| with_context(e, Some(id.as_str()), "add_tag"))
+}
+
+#[tauri::command]
+pub async fn remove_tag_from_credential(
+    id: String,
+    tag: String,
+    store: State<'_, CredentialStore>,
+) -> Result<(), DatabaseErrorContext> {
+    store
+        .remove_tag(&id, &tag)
+        .map_err(|e| with_context(e, Some(id.as_str()), "remove_tag"))
 }
 
 #[tauri::command]
 pub async fn delete_credentials(
     id: String,
     store: State<'_, CredentialStore>,
-) -> Result<(), String> {
-    store.remove(&id).map_err(|e| e.to_string())
+    pools: State<'_, PoolRegistry>,
+) -> Result<(), DatabaseErrorContext> {
+    store
+        .remove(&id)
+        .map_err(|e| with_context(e, Some(id.as_str()), "remove_credentials"))?;
+    pools.evict(&id).await;
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn update_credentials(
     credentials: DatabaseCredentials,
     store: State<'_, CredentialStore>,
-) -> Result<(), String> {
-    store.update(credentials).map_err(|e| e.to_string())
+    pools: State<'_, PoolRegistry>,
+) -> Result<(), DatabaseErrorContext> {
+    let id = credentials.id.clone();
+    validate_credentials(&credentials)
+        .map_err(|e| with_context(e, Some(id.as_str()), "validate_credentials"))?;
+    store
+        .update(credentials)
+        .map_err(|e| with_context(e, Some(id.as_str()), "update_credentials"))?;
+    // The cached pool was opened with the old credentials
+    pools.evict(&id).await;
+    Ok(())
+}
+
+/// Rotate the password of stored credentials
+///
+/// With `verify_by_connecting` a connection is opened with the new password first,
+/// and the old password is kept if that fails.
+#[tauri::command]
+pub async fn update_credential_password(
+    id: String,
+    new_password: String,
+    verify_by_connecting: bool,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<(), DatabaseErrorContext> {
+    if verify_by_connecting {
+        let mut creds = store
+            .get(&id)
+            .map_err(|e| with_context(e, Some(id.as_str()), "update_credential_password"))?;
+        creds.password = Some(new_password.clone());
+        test_connection(&creds, Some(1))
+            .await
+            .map_err(|e| with_context(e, Some(id.as_str()), "test_connection"))?;
+    }
+
+    store
+        .update_password(&id, new_password)
+        .map_err(|e| with_context(e, Some(id.as_str()), "update_credential_password"))?;
+    // The cached pool was opened with the old password
+    pools.evict(&id).await;
+    Ok(())
+}
+
+/// Write the given credentials to `output_path` as a password-protected backup
+#[tauri::command]
+pub async fn export_credentials(
+    ids: Vec<String>,
+    output_path: String,
+    password: String,
+    store: State<'_, CredentialStore>,
+) -> Result<(), DatabaseErrorContext> {
+    let backup = store
+        .export_backup(&ids, &password)
+        .map_err(|e| with_context(e, None, "export_backup"))?;
+    let json = serde_json::to_string_pretty(&backup).map_err(|e| {
+        with_context(DatabaseError::StorageError(e.to_string()), None, "export_backup")
+    })?;
+    write_atomically(Path::new(&output_path), json.as_bytes())
+        .map_err(|e| with_context(e, None, "export_backup"))
+}
+
+/// Add the credentials of a backup written by `export_credentials`
+///
+/// Returns the ids of the credentials that were added.
+#[tauri::command]
+pub async fn import_credentials(
+    input_path: String,
+    password: String,
+    collision_strategy: CollisionStrategy,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<String>, DatabaseErrorContext> {
+    let contents = fs::read_to_string(&input_path).map_err(|e| {
+        with_context(
+            DatabaseError::StorageError(format!("Failed to read {}: {}", input_path, e)),
+            None,
+            "import_backup",
+        )
+    })?;
+    let backup: CredentialBackup = serde_json::from_str(&contents).map_err(|e| {
+        with_context(
+            DatabaseError::StorageError(format!("Invalid backup file: {}", e)),
+            None,
+            "import_backup",
+        )
+    })?;
+
+    let added = store
+        .import_backup(&backup, &password, collision_strategy)
+        .map_err(|e| with_context(e, None, "import_backup"))?;
+    // Overwritten credentials may have a pool opened with the old settings
+    for id in &added {
+        pools.evict(id).await;
+    }
+    Ok(added)
+}
+
+#[tauri::command]
+pub async fn validate_connection_name(
+    name: String,
+    store: State<'_, CredentialStore>,
+) -> Result<(), DatabaseErrorContext> {
+    store
+        .validate_name(&name)
+        .map_err(|e| with_context(e, None, "validate_connection_name"))
+}
+
+/// Require connection names to start with one of `prefixes`, e.g. `prod-`, `dev-`
+/// or `staging-`; an empty list restores the default rule
+#[tauri::command]
+pub async fn set_connection_name_prefixes(
+    prefixes: Vec<String>,
+    store: State<'_, CredentialStore>,
+) -> Result<(), DatabaseErrorContext> {
+    if prefixes.is_empty() {
+        store.set_name_validator(Box::new(default_name_validator));
+    } else {
+        store.set_name_validator(prefix_name_validator(prefixes));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_strength_levels() {
+        assert!(matches!(validate_password_strength("abc"), PasswordStrength::Weak { .. }));
+        assert!(matches!(validate_password_strength("Password"), PasswordStrength::Weak { .. }));
+        assert!(matches!(validate_password_strength("letmein"), PasswordStrength::Weak { .. }));
+        assert!(matches!(validate_password_strength("abcdefgh12"), PasswordStrength::Fair { .. }));
+        assert!(matches!(
+            validate_password_strength("Tr0ub4dor&3"),
+            PasswordStrength::Strong { .. }
+        ));
+        assert!(matches!(
+            validate_password_strength("correct-Horse-battery-9"),
+            PasswordStrength::VeryStrong { .. }
+        ));
+    }
+
+    #[test]
+    fn test_common_password_scores_zero() {
+        let PasswordStrength::Weak { score, suggestions } = validate_password_strength("password")
+        else {
+            panic!("a common password should be weak");
+        };
+        assert_eq!(score, 0);
+        assert!(suggestions.contains(&"Avoid commonly used passwords"));
+    }
+
+    /// A credential store in its own temporary directory, removed on drop
+    struct StoreFixture {
+        dir: PathBuf,
+        store: CredentialStore,
+    }
+
+    impl StoreFixture {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("inspektor-test-{}", uuid::Uuid::new_v4()));
+            let store = CredentialStore::new(dir.join("credentials.json"));
+            Self { dir, store }
+        }
+
+        fn path(&self) -> PathBuf {
+            self.dir.join("credentials.json")
+        }
+    }
+
+    impl Drop for StoreFixture {
+        fn drop(&mut self) {
+            fs::remove_dir_all(&self.dir).ok();
+        }
+    }
+
+    fn test_credentials(id: &str) -> DatabaseCredentials {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": "Local Postgres",
+            "db_type": "postgres",
+            "host": "localhost",
+            "port": 5432,
+            "database": "app",
+            "username": "postgres",
+            "password": "s3cret-Password",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_credentials_survive_reload() {
+        let db = StoreFixture::new();
+        let store = &db.store;
+        let path = db.path();
+        store.add(test_credentials("a")).unwrap();
+        store.add(test_credentials("b")).unwrap();
+        store.remove("b").unwrap();
+
+        // The password must not be written in plaintext
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("s3cret-Password"));
+
+        let reloaded = CredentialStore::load(path.clone()).unwrap();
+        let creds = reloaded.get("a").unwrap();
+        assert_eq!(creds.password.as_deref(), Some("s3cret-Password"));
+        assert!(reloaded.get("b").is_err());
+    }
+
+    #[test]
+    fn test_legacy_machine_key_is_migrated() {
+        let db = StoreFixture::new();
+        let path = db.path();
+        let mut plain = test_credentials("a");
+        let password = plain.password.take().unwrap();
+        let legacy = EncryptionService::encrypt(&password, &legacy_machine_key(), "a", None).unwrap();
+        let file = CredentialsFile {
+            version: CREDENTIALS_FILE_VERSION,
+            credentials: vec![StoredCredential {
+                credentials: plain,
+                encrypted_password: Some(legacy),
+                encrypted_ssh_passphrase: None,
+            }],
+        };
+        write_atomically(&path, serde_json::to_string(&file).unwrap().as_bytes()).unwrap();
+
+        let store = CredentialStore::load(path.clone()).unwrap();
+        assert_eq!(store.get("a").unwrap().password.as_deref(), Some("s3cret-Password"));
+
+        // The file was rewritten with the machine secret
+        let contents = fs::read_to_string(&path).unwrap();
+        let file: CredentialsFile = serde_json::from_str(&contents).unwrap();
+        let encrypted = file.credentials[0].encrypted_password.as_ref().unwrap();
+        let secret = machine_secret(&db.dir).unwrap();
+        assert_eq!(EncryptionService::decrypt(encrypted, &secret).unwrap(), "s3cret-Password");
+    }
+
+    #[test]
+    fn test_duplicate_credentials_get_new_id() {
+        let db = StoreFixture::new();
+        let store = &db.store;
+        store.add(test_credentials("a")).unwrap();
+
+        let new_id = store.duplicate("a", "Staging Postgres").unwrap();
+        assert_ne!(new_id, "a");
+
+        let copy = store.get(&new_id).unwrap();
+        assert_eq!(copy.name, "Staging Postgres");
+        assert_eq!(copy.host.as_deref(), Some("localhost"));
+        assert_eq!(copy.password.as_deref(), Some("s3cret-Password"));
+        assert_eq!(store.get("a").unwrap().name, "Local Postgres");
+        assert!(store.duplicate("missing", "Copy").is_err());
+    }
+
+    #[test]
+    fn test_update_password_keeps_other_fields() {
+        let db = StoreFixture::new();
+        let store = &db.store;
+        let path = db.path();
+        store.add(test_credentials("a")).unwrap();
+
+        store.update_password("a", "n3w-Password".to_string()).unwrap();
+        assert!(store.update_password("missing", "x".to_string()).is_err());
+
+        let reloaded = CredentialStore::load(path.clone()).unwrap();
+        let creds = reloaded.get("a").unwrap();
+        assert_eq!(creds.password.as_deref(), Some("n3w-Password"));
+        assert_eq!(creds.name, "Local Postgres");
+        assert_eq!(creds.username.as_deref(), Some("postgres"));
+    }
+
+    #[test]
+    fn test_tags_and_group_filters() {
+        let db = StoreFixture::new();
+        let store = &db.store;
+        let path = db.path();
+        let mut staging = test_credentials("a");
+        staging.group = Some("backend".to_string());
+        store.add(staging).unwrap();
+        let mut other = test_credentials("b");
+        other.name = "Analytics".to_string();
+        store.add(other).unwrap();
+
+        store.add_tag("a", " staging ").unwrap();
+        store.add_tag("a", "staging").unwrap();
+        store.add_tag("a", "eu").unwrap();
+        store.add_tag("b", "eu").unwrap();
+        store.remove_tag("a", "eu").unwrap();
+        assert!(store.add_tag("a", "  ").is_err());
+        assert!(store.add_tag("missing", "staging").is_err());
+
+        let reloaded = CredentialStore::load(path.clone()).unwrap();
+        assert_eq!(reloaded.get("a").unwrap().tags, vec!["staging"]);
+        assert_eq!(reloaded.list_filtered(Some("eu"), None).unwrap()[0].id, "b");
+        assert_eq!(reloaded.list_filtered(None, Some("backend")).unwrap()[0].id, "a");
+        assert!(reloaded
+            .list_filtered(Some("staging"), Some("frontend"))
+            .unwrap()
+            .is_empty());
+        assert_eq!(reloaded.list_filtered(None, None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_search_redacts_passwords() {
+        let db = StoreFixture::new();
+        let store = &db.store;
+        let mut creds = test_credentials("a");
+        creds.environment_variables =
+            Some(HashMap::from([("DB_PASSWORD".to_string(), "env-s3cret".to_string())]));
+        store.add(creds).unwrap();
+        let mut sqlite = test_credentials("b");
+        sqlite.name = "Analytics".to_string();
+        sqlite.db_type = DatabaseType::SQLite;
+        sqlite.host = None;
+        sqlite.database = "analytics".to_string();
+        store.add(sqlite).unwrap();
+
+        let results = store.search("LOCALHOST", None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "a");
+        assert!(results[0].password.is_none());
+
+        let json = serde_json::to_string(&store.search("", None).unwrap()).unwrap();
+        assert!(!json.contains("s3cret-Password"));
+        let json = serde_json::to_string(&store.list_filtered(None, None).unwrap()).unwrap();
+        assert!(!json.contains("s3cret-Password"));
+        assert!(!json.contains("env-s3cret"));
+        assert!(json.contains("DB_PASSWORD"));
+        assert!(store.get("a").unwrap().password.is_some());
+
+        let results = store.search("a", Some(&DatabaseType::SQLite)).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "b");
+        assert!(store.search("missing", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_backup_round_trip_with_collisions() {
+        let db = StoreFixture::new();
+        let source = &db.store;
+        source.add(test_credentials("a")).unwrap();
+        let backup = source.export_backup(&["a".to_string()], "backup-pass").unwrap();
+
+        let target = CredentialStore::new(db.dir.join("target.json"));
+        assert!(target
+            .import_backup(&backup, "wrong-pass", CollisionStrategy::Skip)
+            .is_err());
+        assert_eq!(
+            target.import_backup(&backup, "backup-pass", CollisionStrategy::Skip).unwrap(),
+            vec!["a".to_string()]
+        );
+        assert_eq!(
+            target.get("a").unwrap().password.as_deref(),
+            Some("s3cret-Password")
+        );
+
+        assert!(target
+            .import_backup(&backup, "backup-pass", CollisionStrategy::Skip)
+            .unwrap()
+            .is_empty());
+        let renamed = target
+            .import_backup(&backup, "backup-pass", CollisionStrategy::RenameImported)
+            .unwrap();
+        assert_ne!(renamed[0], "a");
+        assert_eq!(target.get(&renamed[0]).unwrap().name, "Local Postgres (imported)");
+        assert_eq!(target.list_filtered(None, None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_custom_name_validator() {
+        let db = StoreFixture::new();
+        let store = &db.store;
+
+        assert!(store.validate_name("").is_err());
+        assert!(store.validate_name(&"x".repeat(101)).is_err());
+        assert!(store.validate_name("Local Postgres").is_ok());
+
+        store.set_name_validator(prefix_name_validator(
+            ["prod-", "dev-", "staging-"].map(String::from).to_vec(),
+        ));
+
+        // test_credentials uses "Local Postgres", which the new rule rejects
+        assert!(store.add(test_credentials("a")).is_err());
+        assert!(store.validate_name("dev-postgres").is_ok());
+        assert!(store.validate_name(&format!("dev-{}", "x".repeat(100))).is_err());
+    }
 }
@@ -0,0 +1,253 @@
+use super::connection::PoolRegistry;
+use super::credentials::CredentialStore;
+use super::types::{with_context, DatabaseError, DatabaseErrorContext, DatabaseType};
+use super::utils::quote_identifier;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use tauri::State;
+
+/// Bound specification for a new partition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PartitionBound {
+    Range { from: String, to: String },
+    List { values: Vec<String> },
+    Hash { modulus: u32, remainder: u32 },
+}
+
+/// Build the DDL that creates a partition of `parent_table`
+pub fn build_partition_ddl(
+    db_type: &DatabaseType,
+    parent_table: &str,
+    partition_name: &str,
+    bound: &PartitionBound,
+) -> Result<String, DatabaseError> {
+    let parent = quote_identifier(parent_table, db_type);
+    let partition = quote_identifier(partition_name, db_type);
+
+    match db_type {
+        DatabaseType::Postgres => {
+            let values = match bound {
+                PartitionBound::Range { from, to } => format!(
+                    "FROM ({}) TO ({})",
+                    partition_literal(from),
+                    partition_literal(to)
+                ),
+                PartitionBound::List { values } => format!("IN ({})", partition_literals(values)),
+                PartitionBound::Hash { modulus, remainder } => {
+                    format!("WITH (MODULUS {}, REMAINDER {})", modulus, remainder)
+                }
+            };
+
+            Ok(format!(
+                "CREATE TABLE {} PARTITION OF {} FOR VALUES {}",
+                partition, parent, values
+            ))
+        }
+        DatabaseType::MySQL => {
+            // MySQL range partitions only have an upper bound; the lower bound is the
+            // previous partition's upper bound
+            let values = match bound {
+                PartitionBound::Range { to, .. } => {
+                    format!("VALUES LESS THAN ({})", partition_literal(to))
+                }
+                PartitionBound::List { values } => {
+                    format!("VALUES IN ({})", partition_literals(values))
+                }
+                PartitionBound::Hash { .. } => {
+                    return Err(DatabaseError::Other(
+                        "MySQL hash partitions cannot be added individually, use ALTER TABLE ... ADD PARTITION PARTITIONS n".to_string(),
+                    ));
+                }
+            };
+
+            Ok(format!(
+                "ALTER TABLE {} ADD PARTITION (PARTITION {} {})",
+                parent, partition, values
+            ))
+        }
+        DatabaseType::SQLite | DatabaseType::ClickHouse => Err(DatabaseError::UnsupportedType),
+    }
+}
+
+/// Render a partition bound value: numbers and MINVALUE/MAXVALUE are kept as-is,
+/// everything else becomes a string literal
+fn partition_literal(value: &str) -> String {
+    let trimmed = value.trim();
+    let is_keyword =
+        trimmed.eq_ignore_ascii_case("MINVALUE") || trimmed.eq_ignore_ascii_case("MAXVALUE");
+
+    if is_keyword || is_numeric_literal(trimmed) {
+        trimmed.to_uppercase()
+    } else {
+        format!("'{}'", trimmed.replace('\'', "''"))
+    }
+}
+
+/// Whether a value can be written as a SQL number as it is
+///
+/// `f64` also parses `inf`, `NaN` and `infinity`, which are not SQL numbers, so
+/// only digits, a sign, `.` and an exponent are accepted.
+fn is_numeric_literal(value: &str) -> bool {
+    value.chars().any(|c| c.is_ascii_digit())
+        && value
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | 'e' | 'E'))
+        && value.parse::<f64>().is_ok_and(f64::is_finite)
+}
+
+fn partition_literals(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|v| partition_literal(v))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Create a partition of an existing partitioned table
+pub async fn create_partition(
+    database_id: &str,
+    parent_table: &str,
+    partition_name: &str,
+    bound: &PartitionBound,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<(), DatabaseError> {
+    let creds = store.get(database_id)?;
+    let ddl = build_partition_ddl(&creds.db_type, parent_table, partition_name, bound)?;
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    sqlx::query(&ddl)
+        .execute(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, &ddl))?;
+    Ok(())
+}
+
+/// Detach a partition from its parent table (Postgres only)
+pub async fn detach_table_partition(
+    database_id: &str,
+    partition_name: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<(), DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Err(DatabaseError::UnsupportedType);
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+    let partition = quote_identifier(partition_name, &creds.db_type);
+
+    // Look up the parent table so the caller only needs to know the partition name
+    let parent_query = format!(
+        "SELECT inhparent::regclass::text AS parent_table FROM pg_inherits WHERE inhrelid = '{}'::regclass",
+        partition.replace('\'', "''")
+    );
+    let row = sqlx::query(&parent_query)
+        .fetch_optional(&*pool)
+        .await
+        .map_err(DatabaseError::from)?
+        .ok_or_else(|| {
+            DatabaseError::Other(format!("{} is not a partition", partition_name))
+        })?;
+    let parent_table: String = row
+        .try_get("parent_table")
+        .map_err(DatabaseError::from)?;
+
+    // regclass::text is already quoted where needed
+    let ddl = format!("ALTER TABLE {} DETACH PARTITION {}", parent_table, partition);
+    sqlx::query(&ddl)
+        .execute(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, &ddl))?;
+    Ok(())
+}
+
+// Tauri commands for partition management
+#[tauri::command]
+pub async fn create_table_partition(
+    database_id: String,
+    parent_table: String,
+    partition_name: String,
+    partition_type: PartitionBound,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<(), DatabaseErrorContext> {
+    create_partition(
+        &database_id,
+        &parent_table,
+        &partition_name,
+        &partition_type,
+        &store,
+        &pools,
+    )
+    .await
+    .map_err(|e| {
+        with_context(e, Some(database_id.as_str()), "create_partition").with_table(&parent_table)
+    })
+}
+
+#[tauri::command]
+pub async fn detach_partition(
+    database_id: String,
+    partition_name: String,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<(), DatabaseErrorContext> {
+    detach_table_partition(&database_id, &partition_name, &store, &pools)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "detach_partition").with_table(&partition_name)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgres_partition_ddl() {
+        let range = PartitionBound::Range {
+            from: "2024-01-01".to_string(),
+            to: "2025-01-01".to_string(),
+        };
+        assert_eq!(
+            build_partition_ddl(&DatabaseType::Postgres, "events", "events_2024", &range).unwrap(),
+            "CREATE TABLE \"events_2024\" PARTITION OF \"events\" FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')"
+        );
+
+        let hash = PartitionBound::Hash { modulus: 4, remainder: 1 };
+        assert_eq!(
+            build_partition_ddl(&DatabaseType::Postgres, "events", "events_h1", &hash).unwrap(),
+            "CREATE TABLE \"events_h1\" PARTITION OF \"events\" FOR VALUES WITH (MODULUS 4, REMAINDER 1)"
+        );
+    }
+
+    #[test]
+    fn test_mysql_partition_ddl() {
+        let list = PartitionBound::List {
+            values: vec!["1".to_string(), "2".to_string()],
+        };
+        assert_eq!(
+            build_partition_ddl(&DatabaseType::MySQL, "orders", "p_eu", &list).unwrap(),
+            "ALTER TABLE `orders` ADD PARTITION (PARTITION `p_eu` VALUES IN (1, 2))"
+        );
+        assert!(matches!(
+            build_partition_ddl(&DatabaseType::SQLite, "orders", "p_eu", &list),
+            Err(DatabaseError::UnsupportedType)
+        ));
+    }
+
+    #[test]
+    fn test_partition_literal() {
+        assert_eq!(partition_literal(" 42 "), "42");
+        assert_eq!(partition_literal("-1.5e3"), "-1.5E3");
+        assert_eq!(partition_literal("maxvalue"), "MAXVALUE");
+        for value in ["inf", "NaN", "infinity", "-Infinity", "1e400"] {
+            assert_eq!(partition_literal(value), format!("'{}'", value));
+        }
+        assert_eq!(partition_literal("it's"), "'it''s'");
+    }
+}
@@ -0,0 +1,193 @@
+use super::connection::{recheck_on_connection_error, PoolRegistry};
+use super::credentials::{write_atomically, CredentialStore};
+use super::history::QueryHistory;
+use super::masking::apply_masking;
+use super::metadata::{
+    get_all_table_schemas, get_indexes, get_relationships, get_tables, qualified_table_name,
+};
+use super::query::{coerce_values, convert_rows, fetch_all_with_declared_types};
+use super::types::{
+    with_context, DatabaseCredentials, DatabaseError, DatabaseErrorContext, DiagnosticReport,
+    TableSchema,
+};
+use chrono::Utc;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+
+/// Directory under the app data directory holding the written reports
+const DIAGNOSTICS_DIR: &str = "diagnostics";
+
+/// Number of recent history entries included in a report
+const REPORT_HISTORY_ENTRIES: usize = 10;
+
+/// Number of sample rows included per table
+const REPORT_SAMPLE_ROWS: usize = 5;
+
+/// Number of tables sampled concurrently
+const SAMPLE_CONCURRENCY: usize = 10;
+
+/// Placeholder written in place of secrets
+const REDACTED: &str = "[REDACTED]";
+
+/// Collect the metadata of a database into a single report
+///
+/// Tables, schemas, indexes and relationships are fetched concurrently. Sample rows
+/// are masked with the connection's masking rules like any other query result.
+pub async fn build_diagnostic_report(
+    database_id: &str,
+    include_sample_data: bool,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+    history: &QueryHistory,
+) -> Result<DiagnosticReport, DatabaseError> {
+    let creds = store.get(database_id)?;
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let (tables, schemas, indexes, relationships) = tokio::join!(
+        get_tables(database_id, store, pools),
+        get_all_table_schemas(&creds, &pool),
+        get_indexes(database_id, store, pools),
+        get_relationships(database_id, store, pools),
+    );
+    let schemas = schemas?;
+
+    let sample_data = if include_sample_data {
+        Some(sample_tables(&creds, &pool, &schemas).await?)
+    } else {
+        None
+    };
+
+    Ok(DiagnosticReport {
+        generated_at: Utc::now(),
+        connection: redact_credentials(&creds),
+        tables: tables?,
+        schemas,
+        indexes: indexes?,
+        relationships: relationships?,
+        recent_queries: history.list(Some(database_id), REPORT_HISTORY_ENTRIES),
+        sample_data,
+    })
+}
+
+/// First rows of every table, keyed by table name
+async fn sample_tables(
+    creds: &DatabaseCredentials,
+    pool: &sqlx::AnyPool,
+    schemas: &[TableSchema],
+) -> Result<HashMap<String, Vec<HashMap<String, Value>>>, DatabaseError> {
+    // Futures are built before streaming them so the command future stays Send
+    let samples: Vec<_> = schemas
+        .iter()
+        .map(|table| async move {
+            let sql = format!(
+                "SELECT * FROM {} LIMIT {}",
+                qualified_table_name(&table.table_name, table.schema.as_deref(), &creds.db_type),
+                REPORT_SAMPLE_ROWS
+            );
+            let mut conn = pool.acquire().await.map_err(DatabaseError::from)?;
+            let (rows, column_types) = fetch_all_with_declared_types(creds, &mut conn, &sql)
+                .await
+                .map_err(|e| DatabaseError::query_failed(e, &sql))?;
+
+            let (columns, mut result_rows) = convert_rows(&rows);
+            coerce_values(&columns, &column_types, &mut result_rows);
+            apply_masking(creds, &columns, &mut result_rows);
+            Ok::<_, DatabaseError>((table.table_name.clone(), result_rows))
+        })
+        .collect();
+    stream::iter(samples)
+        .buffer_unordered(SAMPLE_CONCURRENCY)
+        .try_collect()
+        .await
+}
+
+/// Copy of the credentials with secrets replaced by a placeholder
+fn redact_credentials(creds: &DatabaseCredentials) -> DatabaseCredentials {
+    let mut redacted = creds.clone();
+    let redact = |value: &mut Option<String>| {
+        if value.is_some() {
+            *value = Some(REDACTED.to_string());
+        }
+    };
+    redact(&mut redacted.password);
+    redact(&mut redacted.ssh_private_key_passphrase);
+    // Variables are often used for the password itself
+    if let Some(variables) = redacted.environment_variables.as_mut() {
+        for value in variables.values_mut() {
+            *value = REDACTED.to_string();
+        }
+    }
+    redacted
+}
+
+// Tauri command for diagnostic reports
+/// Write a pretty-printed diagnostic report to the app data directory and return its path
+#[tauri::command]
+pub async fn export_diagnostic_report(
+    database_id: String,
+    include_sample_data: bool,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+    history: State<'_, QueryHistory>,
+) -> Result<String, DatabaseErrorContext> {
+    let result =
+        build_diagnostic_report(&database_id, include_sample_data, &store, &pools, &history).await;
+    let report = recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "build_diagnostic_report"))?;
+
+    // The id only names the file, so any character that is not safe in a path is replaced
+    let file_id: String = database_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let path: PathBuf = store.data_dir().join(DIAGNOSTICS_DIR).join(format!(
+        "{}-{}.json",
+        file_id,
+        report.generated_at.format("%Y%m%d-%H%M%S")
+    ));
+
+    serde_json::to_vec_pretty(&report)
+        .map_err(|e| DatabaseError::StorageError(e.to_string()))
+        .and_then(|json| write_atomically(&path, &json))
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "export_diagnostic_report"))?;
+
+    Ok(path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_credentials() {
+        let creds: DatabaseCredentials = serde_json::from_value(serde_json::json!({
+            "id": "a",
+            "name": "Local Postgres",
+            "db_type": "postgres",
+            "host": "localhost",
+            "port": 5432,
+            "database": "app",
+            "username": "postgres",
+            "password": "s3cret",
+            "environment_variables": { "DB_PASSWORD": "s3cret" },
+        }))
+        .unwrap();
+
+        let redacted = redact_credentials(&creds);
+        assert_eq!(redacted.password.as_deref(), Some(REDACTED));
+        assert_eq!(redacted.ssh_private_key_passphrase, None);
+        assert_eq!(redacted.username.as_deref(), Some("postgres"));
+        assert!(!serde_json::to_string(&redacted).unwrap().contains("s3cret"));
+    }
+}
@@ -0,0 +1,181 @@
+use super::types::{
+    with_context, DatabaseError, DatabaseErrorContext, QueryResult, QueryResultDiff, RowDiff,
+};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Compare two results of the same query, matching rows on `key_column`
+///
+/// Rows are compared on the columns both results have; added and removed columns
+/// are reported separately. Every row must have a non-null key that is unique
+/// within its result. Added and modified rows keep the order of `after`, removed
+/// rows the order of `before`.
+pub fn diff_results(
+    before: &QueryResult,
+    after: &QueryResult,
+    key_column: &str,
+) -> Result<QueryResultDiff, DatabaseError> {
+    let before_rows = rows_by_key(before, key_column, "first")?;
+    let after_rows = rows_by_key(after, key_column, "second")?;
+
+    let shared_columns: Vec<&String> = after
+        .columns
+        .iter()
+        .filter(|col| before.columns.contains(col))
+        .collect();
+
+    let before_by_key: HashMap<&String, &HashMap<String, Value>> =
+        before_rows.iter().map(|(key, row)| (key, *row)).collect();
+
+    let mut added_rows = Vec::new();
+    let mut modified_rows = Vec::new();
+    for (key, row) in &after_rows {
+        let Some(previous) = before_by_key.get(key) else {
+            added_rows.push((*row).clone());
+            continue;
+        };
+        let changed_columns: Vec<String> = shared_columns
+            .iter()
+            .filter(|col| previous.get(col.as_str()) != row.get(col.as_str()))
+            .map(|col| col.to_string())
+            .collect();
+        if !changed_columns.is_empty() {
+            modified_rows.push(RowDiff {
+                before: (*previous).clone(),
+                after: (*row).clone(),
+                changed_columns,
+            });
+        }
+    }
+
+    let after_keys: HashSet<&String> = after_rows.iter().map(|(key, _)| key).collect();
+    let removed_rows = before_rows
+        .iter()
+        .filter(|(key, _)| !after_keys.contains(key))
+        .map(|(_, row)| (*row).clone())
+        .collect();
+
+    Ok(QueryResultDiff {
+        added_rows,
+        removed_rows,
+        modified_rows,
+        columns_added: after
+            .columns
+            .iter()
+            .filter(|col| !before.columns.contains(col))
+            .cloned()
+            .collect(),
+        columns_removed: before
+            .columns
+            .iter()
+            .filter(|col| !after.columns.contains(col))
+            .cloned()
+            .collect(),
+    })
+}
+
+/// A row and its key, as returned by `rows_by_key`
+type KeyedRow<'a> = (String, &'a HashMap<String, Value>);
+
+/// Pair each row with its key, rendered as JSON so it can be compared and hashed
+fn rows_by_key<'a>(
+    result: &'a QueryResult,
+    key_column: &str,
+    label: &str,
+) -> Result<Vec<KeyedRow<'a>>, DatabaseError> {
+    if !result.columns.iter().any(|col| col == key_column) {
+        return Err(DatabaseError::QueryError(format!(
+            "Key column {} is not in the {} result",
+            key_column, label
+        )));
+    }
+
+    let mut seen = HashSet::new();
+    let mut rows = Vec::with_capacity(result.rows.len());
+    for row in &result.rows {
+        let key = match row.get(key_column) {
+            Some(value) if !value.is_null() => value.to_string(),
+            _ => {
+                return Err(DatabaseError::QueryError(format!(
+                    "Key column {} is null in the {} result",
+                    key_column, label
+                )))
+            }
+        };
+        if !seen.insert(key.clone()) {
+            return Err(DatabaseError::QueryError(format!(
+                "Key column {} has duplicate value {} in the {} result",
+                key_column, key, label
+            )));
+        }
+        rows.push((key, row));
+    }
+
+    Ok(rows)
+}
+
+// Tauri command for comparing query results
+#[tauri::command]
+pub async fn diff_query_results(
+    result_a: QueryResult,
+    result_b: QueryResult,
+    key_column: String,
+) -> Result<QueryResultDiff, DatabaseErrorContext> {
+    diff_results(&result_a, &result_b, &key_column)
+        .map_err(|e| with_context(e, None, "diff_results"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(columns: &[&str], rows: Value) -> QueryResult {
+        let rows: Vec<HashMap<String, Value>> = serde_json::from_value(rows).unwrap();
+        serde_json::from_value(serde_json::json!({
+            "columns": columns,
+            "row_count": rows.len(),
+            "rows": rows,
+            "execution_time_ms": 0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_diff_results() {
+        let before = result(
+            &["id", "name", "legacy"],
+            serde_json::json!([
+                { "id": 1, "name": "alice", "legacy": true },
+                { "id": 2, "name": "bob", "legacy": false },
+                { "id": 3, "name": "carol", "legacy": false },
+            ]),
+        );
+        let after = result(
+            &["id", "name", "email"],
+            serde_json::json!([
+                { "id": 1, "name": "alice", "email": "a@example.com" },
+                { "id": 3, "name": "caroline", "email": null },
+                { "id": 4, "name": "dave", "email": null },
+            ]),
+        );
+
+        let diff = diff_results(&before, &after, "id").unwrap();
+        assert_eq!(diff.columns_added, vec!["email"]);
+        assert_eq!(diff.columns_removed, vec!["legacy"]);
+        assert_eq!(diff.added_rows.len(), 1);
+        assert_eq!(diff.added_rows[0]["id"], serde_json::json!(4));
+        assert_eq!(diff.removed_rows.len(), 1);
+        assert_eq!(diff.removed_rows[0]["id"], serde_json::json!(2));
+        // Only shared columns are compared, so row 1 is unchanged
+        assert_eq!(diff.modified_rows.len(), 1);
+        assert_eq!(diff.modified_rows[0].changed_columns, vec!["name"]);
+        assert_eq!(
+            diff.modified_rows[0].before["name"],
+            serde_json::json!("carol")
+        );
+
+        assert!(diff_results(&before, &after, "missing").is_err());
+        let duplicated = result(&["id"], serde_json::json!([{ "id": 1 }, { "id": 1 }]));
+        assert!(diff_results(&duplicated, &after, "id").is_err());
+    }
+}
@@ -0,0 +1,332 @@
+use super::connection::PoolRegistry;
+use super::credentials::CredentialStore;
+use super::metadata::{get_indexes, get_table_schema, qualified_table_name};
+use super::query::bind_value;
+use super::types::{
+    with_context, ColumnInfo, DatabaseError, DatabaseErrorContext, DatabaseType, IndexInfo,
+    NormalizedType,
+};
+use super::utils::quote_identifier;
+use serde_json::Value;
+use sqlx::Row;
+use std::collections::HashMap;
+use tauri::State;
+
+/// Generate an INSERT statement for a table
+///
+/// Generated columns (identity, serial, auto-increment and computed columns) are
+/// left out since the database fills them in. Without sample values the statement
+/// uses bind placeholders (`$1` for Postgres, `?` for MySQL and SQLite). Postgres
+/// tables are looked up in `public` unless `table_schema` is given.
+pub async fn generate_insert(
+    database_id: &str,
+    table_name: &str,
+    table_schema: Option<&str>,
+    with_sample_values: bool,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<String, DatabaseError> {
+    let creds = store.get(database_id)?;
+    let table_schema = match creds.db_type {
+        DatabaseType::Postgres => Some(table_schema.unwrap_or("public")),
+        _ => table_schema,
+    };
+
+    let table_list = format!("('{}')", table_name.replace('\'', "''"));
+    let schema = get_table_schema(database_id, &table_list, table_schema, store, pools)
+        .await?
+        .into_iter()
+        .find(|s| s.table_name == table_name)
+        .ok_or_else(|| DatabaseError::Other(format!("Table not found: {}", table_name)))?;
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+    let generated = get_generated_columns(&creds, &pool, table_schema, table_name).await?;
+    let target = qualified_table_name(table_name, table_schema, &creds.db_type);
+
+    let columns: Vec<&ColumnInfo> = schema
+        .columns
+        .iter()
+        .filter(|col| !generated.iter().any(|g| g == &col.name))
+        .collect();
+
+    if columns.is_empty() {
+        return Ok(format!("INSERT INTO {} DEFAULT VALUES", target));
+    }
+
+    let column_list: Vec<String> = columns
+        .iter()
+        .map(|col| quote_identifier(&col.name, &creds.db_type))
+        .collect();
+
+    let values: Vec<String> = columns
+        .iter()
+        .enumerate()
+        .map(|(idx, col)| {
+            if with_sample_values {
+                sample_value(col, &creds.db_type)
+            } else {
+                placeholder(idx + 1, &creds.db_type)
+            }
+        })
+        .collect();
+
+    Ok(format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        target,
+        column_list.join(", "),
+        values.join(", ")
+    ))
+}
+
+/// Get the names of columns whose values are generated by the database
+///
+/// `schema` narrows the Postgres and MySQL lookups down; MySQL defaults to the
+/// connection's database.
+async fn get_generated_columns(
+    creds: &super::types::DatabaseCredentials,
+    pool: &sqlx::AnyPool,
+    schema: Option<&str>,
+    table_name: &str,
+) -> Result<Vec<String>, DatabaseError> {
+    let escaped = table_name.replace('\'', "''");
+    let escaped_schema = schema.unwrap_or(&creds.database).replace('\'', "''");
+
+    let mut generated = Vec::new();
+
+    match creds.db_type {
+        DatabaseType::Postgres => {
+            let query = format!(
+                "SELECT column_name::text
+                FROM information_schema.columns
+                WHERE table_name = '{}' AND table_schema = '{}'
+                    AND (is_identity = 'YES'
+                        OR is_generated = 'ALWAYS'
+                        OR column_default LIKE 'nextval(%')",
+                escaped, escaped_schema
+            );
+            let rows = sqlx::query(&query)
+                .fetch_all(pool)
+                .await
+                .map_err(DatabaseError::from)?;
+
+            for row in rows {
+                generated.push(
+                    row.try_get("column_name")
+                        .map_err(DatabaseError::from)?,
+                );
+            }
+        }
+        DatabaseType::MySQL => {
+            let query = format!(
+                "SELECT CAST(column_name AS CHAR) AS column_name
+                FROM information_schema.columns
+                WHERE table_name = '{}' AND table_schema = '{}'
+                    AND (extra LIKE '%auto_increment%' OR extra LIKE '%GENERATED%')",
+                escaped, escaped_schema
+            );
+            let rows = sqlx::query(&query)
+                .fetch_all(pool)
+                .await
+                .map_err(DatabaseError::from)?;
+
+            for row in rows {
+                generated.push(
+                    row.try_get("column_name")
+                        .or_else(|_| row.try_get("COLUMN_NAME"))
+                        .map_err(DatabaseError::from)?,
+                );
+            }
+        }
+        DatabaseType::SQLite => {
+            // table_xinfo reports generated columns with hidden = 2 (virtual) or 3 (stored)
+            let query = format!(
+                "PRAGMA table_xinfo({})",
+                quote_identifier(table_name, &DatabaseType::SQLite)
+            );
+            let rows = sqlx::query(&query)
+                .fetch_all(pool)
+                .await
+                .map_err(DatabaseError::from)?;
+
+            let mut integer_pks = Vec::new();
+            let mut pk_count = 0;
+            for row in rows {
+                let name: String = row
+                    .try_get("name")
+                    .map_err(DatabaseError::from)?;
+                let data_type: String = row.try_get("type").unwrap_or_default();
+                let hidden: i32 = row.try_get("hidden").unwrap_or(0);
+                let pk: i32 = row.try_get("pk").unwrap_or(0);
+
+                if hidden == 2 || hidden == 3 {
+                    generated.push(name);
+                } else if pk > 0 {
+                    pk_count += 1;
+                    if data_type.eq_ignore_ascii_case("INTEGER") {
+                        integer_pks.push(name);
+                    }
+                }
+            }
+
+            // A single INTEGER PRIMARY KEY is an alias for the rowid and is assigned automatically
+            if pk_count == 1 {
+                generated.extend(integer_pks);
+            }
+        }
+        DatabaseType::ClickHouse => return Err(DatabaseError::UnsupportedType),
+    }
+
+    Ok(generated)
+}
+
+/// Check whether inserting `values` into a table would violate a unique constraint
+///
+/// Every unique index (including the primary key) whose columns are all given and
+/// non-null is checked with a `SELECT COUNT(*)`. Returns
+/// `DatabaseError::ConstraintViolation` with the index name on the first duplicate.
+/// Partial unique indexes are checked as if they covered the whole table.
+pub async fn check_unique_before_insert(
+    database_id: &str,
+    table_name: &str,
+    values: &HashMap<String, Value>,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<(), DatabaseError> {
+    let creds = store.get(database_id)?;
+
+    let unique_indexes: Vec<IndexInfo> = get_indexes(database_id, store, pools)
+        .await?
+        .into_iter()
+        .filter(|index| index.is_unique && index.table_name == table_name)
+        .filter(|index| {
+            !index.columns.is_empty()
+                && index
+                    .columns
+                    .iter()
+                    .all(|col| values.get(col).is_some_and(|v| !v.is_null()))
+        })
+        .collect();
+
+    if unique_indexes.is_empty() {
+        return Ok(());
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    for index in unique_indexes {
+        let conditions: Vec<String> = index
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(idx, col)| {
+                let column = quote_identifier(col, &creds.db_type);
+                // Postgres does not compare text parameters with other column types
+                match (&creds.db_type, &values[col]) {
+                    (DatabaseType::Postgres, Value::String(_)) => {
+                        format!("{}::text = {}", column, placeholder(idx + 1, &creds.db_type))
+                    }
+                    _ => format!("{} = {}", column, placeholder(idx + 1, &creds.db_type)),
+                }
+            })
+            .collect();
+        let sql = format!(
+            "SELECT COUNT(*) FROM {} WHERE {}",
+            quote_identifier(table_name, &creds.db_type),
+            conditions.join(" AND ")
+        );
+
+        let query = index
+            .columns
+            .iter()
+            .fold(sqlx::query(&sql), |query, col| bind_value(query, &values[col]));
+
+        let row = query
+            .fetch_one(&*pool)
+            .await
+            .map_err(|e| DatabaseError::query_failed(e, &sql))?;
+        let count: i64 = row
+            .try_get(0)
+            .map_err(DatabaseError::from)?;
+
+        if count > 0 {
+            return Err(DatabaseError::ConstraintViolation(index.name));
+        }
+    }
+
+    Ok(())
+}
+
+/// Bind placeholder for the nth (1-based) parameter
+fn placeholder(position: usize, db_type: &DatabaseType) -> String {
+    match db_type {
+        DatabaseType::Postgres => format!("${}", position),
+        DatabaseType::MySQL | DatabaseType::SQLite | DatabaseType::ClickHouse => "?".to_string(),
+    }
+}
+
+/// Dummy literal suitable for the column's type
+fn sample_value(column: &ColumnInfo, db_type: &DatabaseType) -> String {
+    let value = match NormalizedType::from_data_type(&column.data_type) {
+        NormalizedType::Integer => "1",
+        NormalizedType::Float => "1.0",
+        NormalizedType::Boolean => match db_type {
+            DatabaseType::SQLite => "1",
+            _ => "true",
+        },
+        NormalizedType::Text => "'sample'",
+        NormalizedType::Json => "'{}'",
+        NormalizedType::Binary => match db_type {
+            DatabaseType::Postgres => "'\\x00'::bytea",
+            _ => "X'00'",
+        },
+        NormalizedType::Timestamp => match db_type {
+            DatabaseType::SQLite => "CURRENT_TIMESTAMP",
+            _ => "NOW()",
+        },
+        NormalizedType::Date => "CURRENT_DATE",
+        NormalizedType::Uuid => match db_type {
+            DatabaseType::Postgres => "gen_random_uuid()",
+            DatabaseType::MySQL => "UUID()",
+            DatabaseType::SQLite => "lower(hex(randomblob(16)))",
+            DatabaseType::ClickHouse => "generateUUIDv4()",
+        },
+        NormalizedType::Unknown if column.is_nullable => "NULL",
+        NormalizedType::Unknown => "'sample'",
+    };
+
+    value.to_string()
+}
+
+// Tauri command for DML generation
+#[tauri::command]
+pub async fn generate_insert_statement(
+    database_id: String,
+    table_name: String,
+    schema: Option<String>,
+    with_sample_values: bool,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<String, DatabaseErrorContext> {
+    let schema = schema.as_deref();
+    generate_insert(&database_id, &table_name, schema, with_sample_values, &store, &pools)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "generate_insert").with_table(&table_name)
+        })
+}
+
+#[tauri::command]
+pub async fn check_unique_constraints(
+    database_id: String,
+    table_name: String,
+    values: HashMap<String, Value>,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<(), DatabaseErrorContext> {
+    check_unique_before_insert(&database_id, &table_name, &values, &store, &pools)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "check_unique_before_insert")
+                .with_table(&table_name)
+        })
+}
@@ -4,12 +4,16 @@ use aes_gcm::{
 };
 use argon2::{
     password_hash::{rand_core::RngCore, SaltString},
-    Argon2, PasswordHasher,
+    Algorithm, Argon2, Params, PasswordHasher, Version,
 };
 use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
 
-use super::types::DatabaseError;
+use super::credentials::{write_atomically, CredentialStore};
+use super::types::{with_context, DatabaseError, DatabaseErrorContext};
 
 /// Encrypted connection data that can be safely stored on the server
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +26,63 @@ pub struct EncryptedConnection {
     pub salt: String,
     /// Connection name (not encrypted)
     pub name: String,
+    /// Argon2 parameters the key was derived with
+    ///
+    /// Data written before the parameters were stored used the argon2 crate
+    /// defaults, which is what a missing field deserializes to.
+    #[serde(default = "Argon2Config::legacy")]
+    pub argon2: Argon2Config,
+}
+
+/// Cost parameters for Argon2id key derivation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Argon2Config {
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of passes over the memory
+    pub iterations: u32,
+    /// Degree of parallelism (lanes)
+    pub parallelism: u32,
+}
+
+impl Argon2Config {
+    /// Parameters of `Argon2::default()`, used before they were configurable
+    pub fn legacy() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl Default for Argon2Config {
+    /// 64 MiB of memory, 3 iterations and 4 lanes
+    fn default() -> Self {
+        Self {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 4,
+        }
+    }
+}
+
+/// Key derived from a password, reusable for any number of encryptions
+///
+/// Argon2 is deliberately slow, so callers that encrypt several values with the
+/// same password derive the key once and keep it.
+#[derive(Clone)]
+pub struct DerivedKey {
+    key: [u8; 32],
+    salt: String,
+    config: Argon2Config,
+}
+
+impl DerivedKey {
+    /// Whether `encrypted_conn` was encrypted with a key of this salt and parameters
+    pub fn matches(&self, encrypted_conn: &EncryptedConnection) -> bool {
+        self.salt == encrypted_conn.salt && self.config == encrypted_conn.argon2
+    }
 }
 
 /// Service for encrypting and decrypting database credentials
@@ -33,22 +94,61 @@ impl EncryptionService {
     /// # Arguments
     /// * `credentials_json` - JSON string of the credentials to encrypt
     /// * `password` - User's password for encryption
+    /// * `config` - Argon2 parameters, `Argon2Config::default()` when `None`
     ///
     /// # Returns
-    /// * `EncryptedConnection` - Encrypted data with salt and nonce
+    /// * `EncryptedConnection` - Encrypted data with salt, nonce and Argon2 parameters
     pub fn encrypt(
         credentials_json: &str,
         password: &str,
         connection_name: &str,
+        config: Option<&Argon2Config>,
     ) -> Result<EncryptedConnection, DatabaseError> {
+        let key = Self::new_key(password, config)?;
+        Self::encrypt_with_key(credentials_json, &key, connection_name)
+    }
+
+    /// Derive a key for `password` with a fresh random salt
+    pub fn new_key(
+        password: &str,
+        config: Option<&Argon2Config>,
+    ) -> Result<DerivedKey, DatabaseError> {
+        let config = config.cloned().unwrap_or_default();
+
         // Generate a random salt for key derivation
         let salt = SaltString::generate(&mut OsRng);
 
         // Derive encryption key from password using Argon2
-        let key = Self::derive_key(password, salt.as_str())?;
+        let key = Self::derive_key(password, salt.as_str(), Some(&config))?;
+
+        Ok(DerivedKey {
+            key,
+            salt: salt.as_str().to_string(),
+            config,
+        })
+    }
 
+    /// Derive the key `encrypted_conn` was encrypted with, if `password` is right
+    pub fn key_for(
+        encrypted_conn: &EncryptedConnection,
+        password: &str,
+    ) -> Result<DerivedKey, DatabaseError> {
+        let key = Self::derive_key(password, &encrypted_conn.salt, Some(&encrypted_conn.argon2))?;
+        Ok(DerivedKey {
+            key,
+            salt: encrypted_conn.salt.clone(),
+            config: encrypted_conn.argon2.clone(),
+        })
+    }
+
+    /// Encrypt with an already derived key and a fresh random nonce
+    pub fn encrypt_with_key(
+        credentials_json: &str,
+        key: &DerivedKey,
+        connection_name: &str,
+    ) -> Result<EncryptedConnection, DatabaseError> {
         // Generate a random nonce for AES-GCM
-        let cipher = Aes256Gcm::new(&key.into());
+        let cipher = Aes256Gcm::new(&key.key.into());
         let mut nonce_bytes = [0u8; 12];
         OsRng.fill_bytes(&mut nonce_bytes);
         let nonce = Nonce::from_slice(&nonce_bytes);
@@ -61,8 +161,9 @@ impl EncryptionService {
         Ok(EncryptedConnection {
             encrypted_data: general_purpose::STANDARD.encode(&ciphertext),
             nonce: general_purpose::STANDARD.encode(nonce),
-            salt: salt.as_str().to_string(),
+            salt: key.salt.clone(),
             name: connection_name.to_string(),
+            argon2: key.config.clone(),
         })
     }
 
@@ -78,8 +179,21 @@ impl EncryptionService {
         encrypted_conn: &EncryptedConnection,
         password: &str,
     ) -> Result<String, DatabaseError> {
-        // Derive the same key from password and salt
-        let key = Self::derive_key(password, &encrypted_conn.salt)?;
+        // Derive the same key from password, salt and the stored Argon2 parameters
+        let key = Self::key_for(encrypted_conn, password)?;
+        Self::decrypt_with_key(encrypted_conn, &key)
+    }
+
+    /// Decrypt with an already derived key, which has to `match` the data
+    pub fn decrypt_with_key(
+        encrypted_conn: &EncryptedConnection,
+        key: &DerivedKey,
+    ) -> Result<String, DatabaseError> {
+        if !key.matches(encrypted_conn) {
+            return Err(DatabaseError::EncryptionError(
+                "Key was derived with a different salt or parameters".to_string(),
+            ));
+        }
 
         // Decode base64 data
         let ciphertext = general_purpose::STANDARD
@@ -93,7 +207,7 @@ impl EncryptionService {
         let nonce = Nonce::from_slice(&nonce_bytes);
 
         // Decrypt
-        let cipher = Aes256Gcm::new(&key.into());
+        let cipher = Aes256Gcm::new(&key.key.into());
         let plaintext = cipher
             .decrypt(nonce, ciphertext.as_ref())
             .map_err(|e| DatabaseError::EncryptionError(format!("Decryption failed (wrong password?): {}", e)))?;
@@ -102,14 +216,39 @@ impl EncryptionService {
             .map_err(|e| DatabaseError::EncryptionError(format!("Invalid UTF-8: {}", e)))
     }
 
-    /// Derive a 256-bit encryption key from password and salt using Argon2
-    fn derive_key(password: &str, salt_str: &str) -> Result<[u8; 32], DatabaseError> {
+    /// Re-encrypt data with a new password
+    ///
+    /// The data is decrypted with `old_password` and encrypted again with a fresh
+    /// salt and nonce, so it fails with the same error as `decrypt` when the old
+    /// password is wrong. `config` defaults to `Argon2Config::default()` when `None`,
+    /// which also upgrades data written with older parameters.
+    pub fn rotate_key(
+        encrypted_conn: &EncryptedConnection,
+        old_password: &str,
+        new_password: &str,
+        config: Option<&Argon2Config>,
+    ) -> Result<EncryptedConnection, DatabaseError> {
+        let plaintext = Self::decrypt(encrypted_conn, old_password)?;
+        Self::encrypt(&plaintext, new_password, &encrypted_conn.name, config)
+    }
+
+    /// Derive a 256-bit encryption key from password and salt using Argon2id
+    ///
+    /// `config` defaults to `Argon2Config::default()` when `None`.
+    fn derive_key(
+        password: &str,
+        salt_str: &str,
+        config: Option<&Argon2Config>,
+    ) -> Result<[u8; 32], DatabaseError> {
         use argon2::password_hash::SaltString;
 
-        let argon2 = Argon2::default();
+        let config = config.cloned().unwrap_or_default();
+        let params = Params::new(config.memory_kib, config.iterations, config.parallelism, None)
+            .map_err(|e| DatabaseError::EncryptionError(format!("Invalid Argon2 parameters: {}", e)))?;
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
 
         // Create SaltString from the string representation
-        let salt = SaltString::new(salt_str)
+        let salt = SaltString::from_b64(salt_str)
             .map_err(|e| DatabaseError::EncryptionError(format!("Invalid salt: {}", e)))?;
 
         let password_hash = argon2
@@ -144,6 +283,60 @@ impl EncryptionService {
     }
 }
 
+/// Directory in the app data dir holding password-protected connections, one file per id
+const ENCRYPTED_CONNECTIONS_DIR: &str = "encrypted_connections";
+
+fn encrypted_connection_path(data_dir: &Path, id: &str) -> Result<PathBuf, DatabaseError> {
+    // The id becomes a file name, so it must not be able to leave the directory
+    let valid = !id.is_empty()
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(DatabaseError::StorageError(format!(
+            "Invalid encrypted connection id: {}",
+            id
+        )));
+    }
+    Ok(data_dir.join(ENCRYPTED_CONNECTIONS_DIR).join(format!("{}.json", id)))
+}
+
+/// Read the password-protected connection stored under `id`
+pub fn load_encrypted(data_dir: &Path, id: &str) -> Result<EncryptedConnection, DatabaseError> {
+    let path = encrypted_connection_path(data_dir, id)?;
+    let contents = fs::read_to_string(&path).map_err(|e| {
+        DatabaseError::StorageError(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    serde_json::from_str(&contents)
+        .map_err(|e| DatabaseError::StorageError(format!("Invalid encrypted connection: {}", e)))
+}
+
+/// Write a password-protected connection under `id`, replacing the file atomically
+pub fn save_encrypted(
+    data_dir: &Path,
+    id: &str,
+    encrypted_conn: &EncryptedConnection,
+) -> Result<(), DatabaseError> {
+    let path = encrypted_connection_path(data_dir, id)?;
+    let json = serde_json::to_string_pretty(encrypted_conn)
+        .map_err(|e| DatabaseError::StorageError(e.to_string()))?;
+    write_atomically(&path, json.as_bytes())
+}
+
+/// Re-encrypt the connection stored under `id` with a new password
+///
+/// The file is only replaced once the old password has decrypted it, so a wrong
+/// password leaves it untouched.
+pub fn rotate_stored_key(
+    data_dir: &Path,
+    id: &str,
+    old_password: &str,
+    new_password: &str,
+    config: Option<&Argon2Config>,
+) -> Result<(), DatabaseError> {
+    let encrypted = load_encrypted(data_dir, id)?;
+    let rotated = EncryptionService::rotate_key(&encrypted, old_password, new_password, config)?;
+    save_encrypted(data_dir, id, &rotated)
+}
+
 // Tauri commands for encryption/decryption
 
 #[tauri::command]
@@ -151,24 +344,83 @@ pub async fn encrypt_connection(
     credentials_json: String,
     password: String,
     connection_name: String,
-) -> Result<EncryptedConnection, String> {
-    EncryptionService::encrypt(&credentials_json, &password, &connection_name)
-        .map_err(|e| e.to_string())
+    argon2_config: Option<Argon2Config>,
+) -> Result<EncryptedConnection, DatabaseErrorContext> {
+    EncryptionService::encrypt(
+        &credentials_json,
+        &password,
+        &connection_name,
+        argon2_config.as_ref(),
+    )
+        .map_err(|e| with_context(e, None, "encrypt"))
 }
 
 #[tauri::command]
 pub async fn decrypt_connection(
     encrypted_conn: EncryptedConnection,
     password: String,
-) -> Result<String, String> {
-    EncryptionService::decrypt(&encrypted_conn, &password).map_err(|e| e.to_string())
+) -> Result<String, DatabaseErrorContext> {
+    EncryptionService::decrypt(&encrypted_conn, &password)
+        .map_err(|e| with_context(e, None, "decrypt"))
+}
+
+#[tauri::command]
+pub async fn rotate_connection_key(
+    encrypted_conn: EncryptedConnection,
+    old_password: String,
+    new_password: String,
+    argon2_config: Option<Argon2Config>,
+) -> Result<EncryptedConnection, DatabaseErrorContext> {
+    EncryptionService::rotate_key(
+        &encrypted_conn,
+        &old_password,
+        &new_password,
+        argon2_config.as_ref(),
+    )
+    .map_err(|e| with_context(e, None, "rotate_key"))
+}
+
+#[tauri::command]
+pub async fn save_encrypted_connection(
+    id: String,
+    encrypted_conn: EncryptedConnection,
+    store: State<'_, CredentialStore>,
+) -> Result<(), DatabaseErrorContext> {
+    save_encrypted(store.data_dir(), &id, &encrypted_conn)
+        .map_err(|e| with_context(e, None, "save_encrypted"))
+}
+
+#[tauri::command]
+pub async fn load_encrypted_connection(
+    id: String,
+    store: State<'_, CredentialStore>,
+) -> Result<EncryptedConnection, DatabaseErrorContext> {
+    load_encrypted(store.data_dir(), &id).map_err(|e| with_context(e, None, "load_encrypted"))
+}
+
+#[tauri::command]
+pub async fn rotate_credential_password(
+    id: String,
+    old_password: String,
+    new_password: String,
+    argon2_config: Option<Argon2Config>,
+    store: State<'_, CredentialStore>,
+) -> Result<(), DatabaseErrorContext> {
+    rotate_stored_key(
+        store.data_dir(),
+        &id,
+        &old_password,
+        &new_password,
+        argon2_config.as_ref(),
+    )
+    .map_err(|e| with_context(e, None, "rotate_stored_key"))
 }
 
 #[tauri::command]
 pub async fn verify_connection_password(
     encrypted_conn: EncryptedConnection,
     password: String,
-) -> Result<bool, String> {
+) -> Result<bool, DatabaseErrorContext> {
     Ok(EncryptionService::verify_password(&encrypted_conn, &password))
 }
 
@@ -183,7 +435,7 @@ mod tests {
         let name = "Test Connection";
 
         // Encrypt
-        let encrypted = EncryptionService::encrypt(credentials, password, name).unwrap();
+        let encrypted = EncryptionService::encrypt(credentials, password, name, None).unwrap();
 
         // Decrypt
         let decrypted = EncryptionService::decrypt(&encrypted, password).unwrap();
@@ -199,7 +451,7 @@ mod tests {
         let name = "Test Connection";
 
         // Encrypt
-        let encrypted = EncryptionService::encrypt(credentials, password, name).unwrap();
+        let encrypted = EncryptionService::encrypt(credentials, password, name, None).unwrap();
 
         // Try to decrypt with wrong password
         let result = EncryptionService::decrypt(&encrypted, wrong_password);
@@ -213,9 +465,101 @@ mod tests {
         let password = "my_secure_password";
         let name = "Test Connection";
 
-        let encrypted = EncryptionService::encrypt(credentials, password, name).unwrap();
+        let encrypted = EncryptionService::encrypt(credentials, password, name, None).unwrap();
 
         assert!(EncryptionService::verify_password(&encrypted, password));
         assert!(!EncryptionService::verify_password(&encrypted, "wrong"));
     }
+
+    #[test]
+    fn test_rotate_key() {
+        let credentials = r#"{"host":"localhost","port":5432,"database":"test"}"#;
+        let encrypted =
+            EncryptionService::encrypt(credentials, "old_password", "Test Connection", None)
+                .unwrap();
+
+        let rotated =
+            EncryptionService::rotate_key(&encrypted, "old_password", "new_password", None)
+                .unwrap();
+        assert_ne!(rotated.salt, encrypted.salt);
+        assert_ne!(rotated.nonce, encrypted.nonce);
+        assert_eq!(rotated.name, "Test Connection");
+        assert_eq!(EncryptionService::decrypt(&rotated, "new_password").unwrap(), credentials);
+        assert!(EncryptionService::decrypt(&rotated, "old_password").is_err());
+    }
+
+    #[test]
+    fn test_rotate_key_rejects_wrong_old_password() {
+        let credentials = r#"{"host":"localhost","port":5432,"database":"test"}"#;
+        let encrypted =
+            EncryptionService::encrypt(credentials, "old_password", "Test Connection", None)
+                .unwrap();
+
+        let result = EncryptionService::rotate_key(&encrypted, "wrong", "new_password", None);
+        assert!(matches!(result, Err(DatabaseError::EncryptionError(_))));
+    }
+
+    #[test]
+    fn test_rotate_stored_key() {
+        let dir = std::env::temp_dir().join(format!("inspektor-test-{}", uuid::Uuid::new_v4()));
+        let credentials = r#"{"host":"localhost","port":5432,"database":"test"}"#;
+        let encrypted =
+            EncryptionService::encrypt(credentials, "old_password", "Test Connection", None)
+                .unwrap();
+        save_encrypted(&dir, "conn-1", &encrypted).unwrap();
+
+        let result = rotate_stored_key(&dir, "conn-1", "wrong", "new_password", None);
+        assert!(matches!(result, Err(DatabaseError::EncryptionError(_))));
+        let unchanged = load_encrypted(&dir, "conn-1").unwrap();
+        assert_eq!(EncryptionService::decrypt(&unchanged, "old_password").unwrap(), credentials);
+
+        rotate_stored_key(&dir, "conn-1", "old_password", "new_password", None).unwrap();
+        let rotated = load_encrypted(&dir, "conn-1").unwrap();
+        assert_eq!(EncryptionService::decrypt(&rotated, "new_password").unwrap(), credentials);
+        assert!(!dir.join(ENCRYPTED_CONNECTIONS_DIR).join("conn-1.tmp").exists());
+
+        assert!(matches!(
+            load_encrypted(&dir, "../credentials"),
+            Err(DatabaseError::StorageError(_))
+        ));
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_custom_argon2_config() {
+        let credentials = r#"{"host":"localhost","port":5432,"database":"test"}"#;
+        let password = "my_secure_password";
+        let config = Argon2Config {
+            memory_kib: 8 * 1024,
+            iterations: 1,
+            parallelism: 1,
+        };
+
+        let encrypted =
+            EncryptionService::encrypt(credentials, password, "Test Connection", Some(&config))
+                .unwrap();
+        assert_eq!(encrypted.argon2, config);
+
+        // decrypt reads the parameters back from the encrypted data
+        let decrypted = EncryptionService::decrypt(&encrypted, password).unwrap();
+        assert_eq!(credentials, decrypted);
+
+        // The same password and salt give a different key with different parameters
+        let default_key =
+            EncryptionService::derive_key(password, &encrypted.salt, None).unwrap();
+        let custom_key =
+            EncryptionService::derive_key(password, &encrypted.salt, Some(&config)).unwrap();
+        assert_ne!(default_key, custom_key);
+
+        let mut tampered = encrypted.clone();
+        tampered.argon2 = Argon2Config::default();
+        assert!(EncryptionService::decrypt(&tampered, password).is_err());
+    }
+
+    #[test]
+    fn test_missing_argon2_config_uses_legacy_parameters() {
+        let json = r#"{"encrypted_data":"","nonce":"","salt":"","name":"old"}"#;
+        let encrypted: EncryptedConnection = serde_json::from_str(json).unwrap();
+        assert_eq!(encrypted.argon2, Argon2Config::legacy());
+    }
 }
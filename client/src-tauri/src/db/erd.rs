@@ -0,0 +1,131 @@
+use super::credentials::{write_atomically, CredentialStore};
+use super::types::{with_context, DatabaseError, DatabaseErrorContext, ErdLayout};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// Directory under the app data directory holding one layout file per database
+const ERD_LAYOUT_DIR: &str = "erd_layouts";
+
+/// Path of the layout file for a database
+///
+/// Layout files only hold diagram positions and are safe to delete.
+fn layout_path(data_dir: &Path, database_id: &str) -> Result<PathBuf, DatabaseError> {
+    let is_safe = !database_id.is_empty()
+        && database_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !is_safe {
+        return Err(DatabaseError::StorageError(format!(
+            "Invalid database id for ERD layout: {}",
+            database_id
+        )));
+    }
+
+    Ok(data_dir
+        .join(ERD_LAYOUT_DIR)
+        .join(format!("{}.json", database_id)))
+}
+
+/// Save the ER diagram layout for a database
+pub fn save_layout(
+    data_dir: &Path,
+    database_id: &str,
+    layout: &ErdLayout,
+) -> Result<(), DatabaseError> {
+    let path = layout_path(data_dir, database_id)?;
+    let json = serde_json::to_vec_pretty(layout)
+        .map_err(|e| DatabaseError::StorageError(e.to_string()))?;
+    write_atomically(&path, &json)
+}
+
+/// Load the saved ER diagram layout for a database, if any
+pub fn load_layout(data_dir: &Path, database_id: &str) -> Result<Option<ErdLayout>, DatabaseError> {
+    let path = layout_path(data_dir, database_id)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let contents = fs::read_to_string(&path).map_err(|e| {
+        DatabaseError::StorageError(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    let layout = serde_json::from_str(&contents)
+        .map_err(|e| DatabaseError::StorageError(format!("Invalid ERD layout file: {}", e)))?;
+
+    Ok(Some(layout))
+}
+
+/// Delete the saved ER diagram layout for a database
+pub fn reset_layout(data_dir: &Path, database_id: &str) -> Result<(), DatabaseError> {
+    let path = layout_path(data_dir, database_id)?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| {
+            DatabaseError::StorageError(format!("Failed to delete {}: {}", path.display(), e))
+        })?;
+    }
+    Ok(())
+}
+
+// Tauri commands for ER diagram layouts
+#[tauri::command]
+pub async fn save_erd_layout(
+    database_id: String,
+    layout: ErdLayout,
+    store: State<'_, CredentialStore>,
+) -> Result<(), DatabaseErrorContext> {
+    store
+        .get(&database_id)
+        .and_then(|_| save_layout(store.data_dir(), &database_id, &layout))
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "save_layout"))
+}
+
+#[tauri::command]
+pub async fn load_erd_layout(
+    database_id: String,
+    store: State<'_, CredentialStore>,
+) -> Result<Option<ErdLayout>, DatabaseErrorContext> {
+    load_layout(store.data_dir(), &database_id)
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "load_layout"))
+}
+
+#[tauri::command]
+pub async fn reset_erd_layout(
+    database_id: String,
+    store: State<'_, CredentialStore>,
+) -> Result<(), DatabaseErrorContext> {
+    reset_layout(store.data_dir(), &database_id)
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "reset_layout"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::types::TablePosition;
+    use super::*;
+
+    #[test]
+    fn test_layout_roundtrip() {
+        let data_dir = std::env::temp_dir().join(format!("inspektor-erd-{}", uuid::Uuid::new_v4()));
+
+        let mut layout = ErdLayout::new();
+        layout.insert(
+            "users".to_string(),
+            TablePosition {
+                x: 10.0,
+                y: 20.0,
+                width: 200.0,
+                height: 120.0,
+            },
+        );
+
+        assert!(load_layout(&data_dir, "db-1").unwrap().is_none());
+        save_layout(&data_dir, "db-1", &layout).unwrap();
+        let loaded = load_layout(&data_dir, "db-1").unwrap().unwrap();
+        assert_eq!(loaded["users"].width, 200.0);
+
+        reset_layout(&data_dir, "db-1").unwrap();
+        assert!(load_layout(&data_dir, "db-1").unwrap().is_none());
+        assert!(layout_path(&data_dir, "../credentials").is_err());
+
+        let _ = fs::remove_dir_all(&data_dir);
+    }
+}
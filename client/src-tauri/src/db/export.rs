@@ -0,0 +1,792 @@
+use super::connection::{connection_target, recheck_on_connection_error, PoolRegistry};
+use super::credentials::CredentialStore;
+use super::masking::apply_masking;
+use super::metadata::qualified_table_name;
+use super::query::{
+    coerce_values, column_types, convert_row, executable_sql, QueryCanceller,
+    DEFAULT_QUERY_TIMEOUT_SECS,
+};
+use super::types::{
+    with_context, CopyFormat, DatabaseCredentials, DatabaseError, DatabaseErrorContext,
+    DatabaseType, ExportResult, NormalizedType,
+};
+use futures::TryStreamExt;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::{json, Value};
+use sqlx::any::AnyRow;
+use sqlx::postgres::PgConnection;
+use sqlx::{Column, Connection, Row};
+use std::collections::HashMap;
+use std::fs::File;
+use std::future::Future;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tauri::{AppHandle, State};
+
+/// Run a single-statement query and hand each converted row to `on_row`
+///
+/// Rows are fetched as a stream, so only one row is held in memory at a time. The
+/// same type coercions and masking rules as `execute_query` are applied. Exports
+/// can run for much longer than a query, so `DEFAULT_QUERY_TIMEOUT_SECS` limits the
+/// wait for each row rather than the whole export. Returns the number of rows
+/// passed to `on_row`.
+async fn stream_query_rows<F>(
+    database_id: &str,
+    sql: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+    mut on_row: F,
+) -> Result<u64, DatabaseError>
+where
+    F: FnMut(&[String], &HashMap<String, Value>) -> Result<(), DatabaseError>,
+{
+    let creds = store.get(database_id)?;
+    let executable = executable_sql(sql, &creds.db_type, true)?;
+    if executable.statements.len() > 1 {
+        return Err(DatabaseError::QueryError(
+            "Export requires a single statement".to_string(),
+        ));
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+    let (mut conn, canceller) = QueryCanceller::acquire(&creds.db_type, &pool).await?;
+
+    let mut rows = sqlx::query(&executable.sql).fetch(&mut *conn);
+    let mut columns: Option<(Vec<String>, Vec<NormalizedType>)> = None;
+    let mut written = 0;
+
+    while let Some(row) = canceller
+        .timeout(&pool, DEFAULT_QUERY_TIMEOUT_SECS, rows.try_next())
+        .await?
+        .map_err(DatabaseError::from)?
+    {
+        let (names, types) = columns.get_or_insert_with(|| describe_columns(&row));
+
+        let mut row_map = [convert_row(&row)];
+        coerce_values(names, types, &mut row_map);
+        apply_masking(&creds, names, &mut row_map);
+
+        on_row(names, &row_map[0])?;
+        written += 1;
+    }
+
+    Ok(written)
+}
+
+fn describe_columns(row: &AnyRow) -> (Vec<String>, Vec<NormalizedType>) {
+    let names = row
+        .columns()
+        .iter()
+        .map(|col| col.name().to_string())
+        .collect();
+    (names, column_types(std::slice::from_ref(row)))
+}
+
+/// Export the result of a query to a CSV file
+///
+/// Columns are written in result order with a header row taken from the first
+/// row, so an empty result produces an empty file. NULLs are written as empty
+/// fields. The file is replaced with `write_replacing`. Returns the number of rows
+/// written.
+pub async fn export_query_csv(
+    database_id: &str,
+    sql: &str,
+    output_path: &Path,
+    delimiter: Option<char>,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<u64, DatabaseError> {
+    write_replacing(output_path, |path| async move {
+        write_query_csv(database_id, sql, &path, delimiter, store, pools).await
+    })
+    .await
+}
+
+/// Write the result of a query to `output_path` for `export_query_csv`
+async fn write_query_csv(
+    database_id: &str,
+    sql: &str,
+    output_path: &Path,
+    delimiter: Option<char>,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<u64, DatabaseError> {
+    let delimiter = match delimiter {
+        Some(c) if c.is_ascii() => c as u8,
+        Some(c) => {
+            return Err(DatabaseError::Other(format!(
+                "CSV delimiter must be an ASCII character, got '{}'",
+                c
+            )))
+        }
+        None => b',',
+    };
+
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_path(output_path)
+        .map_err(|e| {
+            DatabaseError::StorageError(format!(
+                "Failed to create {}: {}",
+                output_path.display(),
+                e
+            ))
+        })?;
+    let write_error = |e: csv::Error| {
+        DatabaseError::StorageError(format!("Failed to write {}: {}", output_path.display(), e))
+    };
+
+    let mut header_written = false;
+    let written = stream_query_rows(database_id, sql, store, pools, |columns, row| {
+        if !header_written {
+            writer.write_record(columns).map_err(write_error)?;
+            header_written = true;
+        }
+        writer
+            .write_record(columns.iter().map(|col| csv_field(row.get(col))))
+            .map_err(write_error)
+    })
+    .await?;
+
+    writer.flush().map_err(|e| {
+        DatabaseError::StorageError(format!("Failed to write {}: {}", output_path.display(), e))
+    })?;
+    Ok(written)
+}
+
+/// Render a value as a CSV field; quoting is left to the CSV writer
+fn csv_field(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Width reserved for `row_count` in the JSON header, which is only known at the end
+const ROW_COUNT_WIDTH: usize = 20;
+
+/// Export the result of a query to a JSON file
+///
+/// The file holds a `header` object (`executed_at`, `database_id`, `sql`,
+/// `row_count`) followed by the `rows` array, with row keys in result column
+/// order. Rows are written as they are streamed from the database. The row count
+/// is only known afterwards, so it is written into space reserved in the header,
+/// padded with leading whitespace. The file is replaced with `write_replacing`.
+/// Returns the number of bytes written.
+pub async fn export_query_json(
+    database_id: &str,
+    sql: &str,
+    output_path: &Path,
+    pretty: bool,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<u64, DatabaseError> {
+    write_replacing(output_path, |path| async move {
+        write_query_json(database_id, sql, &path, pretty, store, pools).await
+    })
+    .await
+}
+
+/// Write the result of a query to `output_path` for `export_query_json`
+async fn write_query_json(
+    database_id: &str,
+    sql: &str,
+    output_path: &Path,
+    pretty: bool,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<u64, DatabaseError> {
+    let write_error = |e: std::io::Error| {
+        DatabaseError::StorageError(format!("Failed to write {}: {}", output_path.display(), e))
+    };
+
+    let file = File::create(output_path).map_err(|e| {
+        DatabaseError::StorageError(format!("Failed to create {}: {}", output_path.display(), e))
+    })?;
+    let mut writer = BufWriter::new(file);
+
+    let header = json!({
+        "executed_at": chrono::Utc::now().to_rfc3339(),
+        "database_id": database_id,
+        "sql": sql,
+    });
+    let header = if pretty {
+        serde_json::to_string_pretty(&header)
+    } else {
+        serde_json::to_string(&header)
+    }
+    .map_err(|e| DatabaseError::Other(e.to_string()))?;
+    // Reopen the header object so the row count placeholder can be appended
+    let header = header
+        .trim_end()
+        .strip_suffix('}')
+        .unwrap_or(&header)
+        .trim_end();
+
+    if pretty {
+        write!(
+            writer,
+            "{{\n  \"header\": {},\n    \"row_count\": ",
+            header.replace('\n', "\n  ")
+        )
+    } else {
+        write!(writer, "{{\"header\":{},\"row_count\":", header)
+    }
+    .map_err(write_error)?;
+    let row_count_offset = writer.stream_position().map_err(write_error)?;
+    write!(writer, "{:>width$}", 0, width = ROW_COUNT_WIDTH).map_err(write_error)?;
+    let rows_open = if pretty {
+        "\n  },\n  \"rows\": ["
+    } else {
+        "},\"rows\":["
+    };
+    writer
+        .write_all(rows_open.as_bytes())
+        .map_err(write_error)?;
+
+    let mut first = true;
+    let row_count = stream_query_rows(database_id, sql, store, pools, |columns, row| {
+        if !first {
+            write!(writer, ",").map_err(write_error)?;
+        }
+        first = false;
+
+        let row = OrderedRow { columns, row };
+        if pretty {
+            // Indent the row below "rows"; newlines inside strings are escaped, so
+            // splitting on lines is safe
+            let rendered = serde_json::to_string_pretty(&row)
+                .map_err(|e| DatabaseError::Other(e.to_string()))?;
+            for line in rendered.lines() {
+                write!(writer, "\n    {}", line).map_err(write_error)?;
+            }
+            Ok(())
+        } else {
+            serde_json::to_writer(&mut writer, &row)
+                .map_err(|e| DatabaseError::Other(e.to_string()))
+        }
+    })
+    .await?;
+
+    let closing = match (pretty, row_count) {
+        (false, _) => "]}",
+        (true, 0) => "]\n}\n",
+        (true, _) => "\n  ]\n}\n",
+    };
+    writer.write_all(closing.as_bytes()).map_err(write_error)?;
+
+    writer
+        .seek(SeekFrom::Start(row_count_offset))
+        .map_err(write_error)?;
+    write!(writer, "{:>width$}", row_count, width = ROW_COUNT_WIDTH).map_err(write_error)?;
+
+    let file = writer
+        .into_inner()
+        .map_err(|e| write_error(e.into_error()))?;
+    let bytes_written = file.metadata().map_err(write_error)?.len();
+    Ok(bytes_written)
+}
+
+/// Serializes a row map with its keys in result column order
+struct OrderedRow<'a> {
+    columns: &'a [String],
+    row: &'a HashMap<String, Value>,
+}
+
+impl Serialize for OrderedRow<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for column in self.columns {
+            map.serialize_entry(column, self.row.get(column).unwrap_or(&Value::Null))?;
+        }
+        map.end()
+    }
+}
+
+/// Export a whole table to a file
+///
+/// Postgres tables are written with `COPY ... TO STDOUT` on a dedicated connection,
+/// passing the server's output straight to the file instead of decoding each row.
+/// MySQL and SQLite, and Postgres databases with masking rules (which COPY would
+/// bypass), go through `export_query_csv` and only support `CopyFormat::Csv`.
+/// The file is replaced with `write_replacing`.
+pub async fn export_table_copy(
+    database_id: &str,
+    table_name: &str,
+    schema: Option<&str>,
+    output_path: &Path,
+    format: CopyFormat,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<ExportResult, DatabaseError> {
+    let creds = store.get(database_id)?;
+    let start = Instant::now();
+    let table = qualified_table_name(table_name, schema, &creds.db_type);
+    let masked = creds
+        .masking_rules
+        .as_ref()
+        .is_some_and(|rules| !rules.is_empty());
+    match (&creds.db_type, format) {
+        (DatabaseType::Postgres, _) if !masked => {}
+        (_, CopyFormat::Csv) => {}
+        (DatabaseType::Postgres, _) => {
+            return Err(DatabaseError::Other(
+                "Masking rules apply to this database, export it as CSV instead".to_string(),
+            ))
+        }
+        _ => return Err(DatabaseError::UnsupportedType),
+    }
+
+    let rows_exported = write_replacing(output_path, |path| async move {
+        if matches!(creds.db_type, DatabaseType::Postgres) && !masked {
+            copy_out_postgres(&creds, &table, &path, format).await
+        } else {
+            let sql = format!("SELECT * FROM {}", table);
+            write_query_csv(database_id, &sql, &path, None, store, pools).await
+        }
+    })
+    .await?;
+
+    let bytes_written = std::fs::metadata(output_path)
+        .map_err(|e| {
+            DatabaseError::StorageError(format!("Failed to read {}: {}", output_path.display(), e))
+        })?
+        .len();
+
+    Ok(ExportResult {
+        rows_exported,
+        bytes_written,
+        duration_ms: start.elapsed().as_millis() as u64,
+    })
+}
+
+/// Path of the file an export is written to before it is renamed to `output_path`
+fn temporary_export_path(output_path: &Path) -> PathBuf {
+    let mut file_name = output_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    output_path.with_file_name(file_name)
+}
+
+/// Run `write` on a temporary file next to `output_path`, then rename it into place
+///
+/// The temporary file is removed when `write` fails, so a failed export never
+/// replaces `output_path` or leaves a truncated file behind.
+async fn write_replacing<T, F, Fut>(output_path: &Path, write: F) -> Result<T, DatabaseError>
+where
+    F: FnOnce(PathBuf) -> Fut,
+    Fut: Future<Output = Result<T, DatabaseError>>,
+{
+    let tmp_path = temporary_export_path(output_path);
+    let written = match write(tmp_path.clone()).await {
+        Ok(written) => written,
+        Err(e) => {
+            std::fs::remove_file(&tmp_path).ok();
+            return Err(e);
+        }
+    };
+    std::fs::rename(&tmp_path, output_path).map_err(|e| {
+        std::fs::remove_file(&tmp_path).ok();
+        DatabaseError::StorageError(format!("Failed to replace {}: {}", output_path.display(), e))
+    })?;
+    Ok(written)
+}
+
+/// Stream `COPY table TO STDOUT` into a file, returning the number of rows copied
+async fn copy_out_postgres(
+    creds: &DatabaseCredentials,
+    table: &str,
+    output_path: &Path,
+    format: CopyFormat,
+) -> Result<u64, DatabaseError> {
+    let options = match format {
+        CopyFormat::Csv => "FORMAT csv, HEADER",
+        CopyFormat::Text => "FORMAT text",
+        CopyFormat::Binary => "FORMAT binary",
+    };
+    let statement = format!("COPY {} TO STDOUT WITH ({})", table, options);
+
+    // COPY needs a Postgres connection rather than one from the Any pool; the
+    // tunnel, if any, stays open until the copy is done
+    let (conn_str, _tunnel) = connection_target(creds).await?;
+    let mut conn = PgConnection::connect(&conn_str)
+        .await
+        .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
+
+    let write_error = |e: std::io::Error| {
+        DatabaseError::StorageError(format!("Failed to write {}: {}", output_path.display(), e))
+    };
+    let file = File::create(output_path).map_err(|e| {
+        DatabaseError::StorageError(format!("Failed to create {}: {}", output_path.display(), e))
+    })?;
+    let mut writer = BufWriter::new(file);
+    let mut counter = CopyRowCounter::new(format);
+
+    let mut stream = conn.copy_out_raw(&statement).await.map_err(|e| DatabaseError::query_failed(e, &statement))?;
+    while let Some(chunk) = stream
+        .try_next()
+        .await
+        .map_err(DatabaseError::from)?
+    {
+        counter.feed(&chunk);
+        writer.write_all(&chunk).map_err(write_error)?;
+    }
+    drop(stream);
+
+    writer.flush().map_err(write_error)?;
+    conn.close().await.ok();
+    Ok(counter.rows())
+}
+
+/// Counts the rows of COPY output as it is written
+///
+/// Text rows end with a newline (newlines in values are escaped), CSV rows too
+/// except inside quoted fields, and binary rows are found by walking the tuple
+/// headers.
+enum CopyRowCounter {
+    Lines {
+        csv: bool,
+        in_quotes: bool,
+        lines: u64,
+    },
+    Binary {
+        state: BinaryCopyState,
+        pending: Vec<u8>, // Partial fixed-size field split across chunks
+        skip: usize,      // Bytes of header extension or field data still to skip
+        rows: u64,
+    },
+}
+
+#[derive(Clone, Copy)]
+enum BinaryCopyState {
+    Signature,
+    ExtensionLength,
+    FieldCount,
+    FieldLength(u16), // Fields left in the current tuple
+    Done,
+}
+
+/// Signature and flags at the start of binary COPY output
+const BINARY_COPY_HEADER_LEN: usize = 11 + 4;
+
+impl CopyRowCounter {
+    fn new(format: CopyFormat) -> Self {
+        match format {
+            CopyFormat::Csv | CopyFormat::Text => CopyRowCounter::Lines {
+                csv: format == CopyFormat::Csv,
+                in_quotes: false,
+                lines: 0,
+            },
+            CopyFormat::Binary => CopyRowCounter::Binary {
+                state: BinaryCopyState::Signature,
+                pending: Vec::new(),
+                skip: 0,
+                rows: 0,
+            },
+        }
+    }
+
+    fn feed(&mut self, mut data: &[u8]) {
+        match self {
+            CopyRowCounter::Lines {
+                csv,
+                in_quotes,
+                lines,
+            } => {
+                for &byte in data {
+                    match byte {
+                        // An escaped quote ("") toggles twice, leaving the state unchanged
+                        b'"' if *csv => *in_quotes = !*in_quotes,
+                        b'\n' if !*in_quotes => *lines += 1,
+                        _ => {}
+                    }
+                }
+            }
+            CopyRowCounter::Binary {
+                state,
+                pending,
+                skip,
+                rows,
+            } => loop {
+                let skipped = (*skip).min(data.len());
+                data = &data[skipped..];
+                *skip -= skipped;
+                if *skip > 0 {
+                    return;
+                }
+
+                let needed = match state {
+                    BinaryCopyState::Signature => BINARY_COPY_HEADER_LEN,
+                    BinaryCopyState::ExtensionLength | BinaryCopyState::FieldLength(_) => 4,
+                    BinaryCopyState::FieldCount => 2,
+                    BinaryCopyState::Done => return,
+                };
+                let take = (needed - pending.len()).min(data.len());
+                pending.extend_from_slice(&data[..take]);
+                data = &data[take..];
+                if pending.len() < needed {
+                    return;
+                }
+
+                *state = match *state {
+                    BinaryCopyState::Signature => BinaryCopyState::ExtensionLength,
+                    BinaryCopyState::ExtensionLength => {
+                        *skip = u32::from_be_bytes([pending[0], pending[1], pending[2], pending[3]])
+                            as usize;
+                        BinaryCopyState::FieldCount
+                    }
+                    BinaryCopyState::FieldCount => {
+                        // The trailer is a field count of -1
+                        match i16::from_be_bytes([pending[0], pending[1]]) {
+                            count if count < 0 => BinaryCopyState::Done,
+                            0 => {
+                                *rows += 1;
+                                BinaryCopyState::FieldCount
+                            }
+                            count => {
+                                *rows += 1;
+                                BinaryCopyState::FieldLength(count as u16)
+                            }
+                        }
+                    }
+                    BinaryCopyState::FieldLength(left) => {
+                        // NULLs have a length of -1 and no data
+                        let length =
+                            i32::from_be_bytes([pending[0], pending[1], pending[2], pending[3]]);
+                        *skip = length.max(0) as usize;
+                        if left > 1 {
+                            BinaryCopyState::FieldLength(left - 1)
+                        } else {
+                            BinaryCopyState::FieldCount
+                        }
+                    }
+                    BinaryCopyState::Done => BinaryCopyState::Done,
+                };
+                pending.clear();
+            },
+        }
+    }
+
+    fn rows(&self) -> u64 {
+        match self {
+            // The first CSV line is the header
+            CopyRowCounter::Lines {
+                csv: true, lines, ..
+            } => lines.saturating_sub(1),
+            CopyRowCounter::Lines { lines, .. } => *lines,
+            CopyRowCounter::Binary { rows, .. } => *rows,
+        }
+    }
+}
+
+// Tauri commands for exporting query results
+#[tauri::command]
+pub async fn export_query_result_csv(
+    database_id: String,
+    sql: String,
+    output_path: String,
+    delimiter: Option<char>,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<u64, DatabaseErrorContext> {
+    let result = export_query_csv(
+        &database_id,
+        &sql,
+        Path::new(&output_path),
+        delimiter,
+        &store,
+        &pools,
+    )
+    .await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "export_query_csv"))
+}
+
+#[tauri::command]
+pub async fn export_query_result_json(
+    database_id: String,
+    sql: String,
+    output_path: String,
+    pretty: bool,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<u64, DatabaseErrorContext> {
+    let result = export_query_json(
+        &database_id,
+        &sql,
+        Path::new(&output_path),
+        pretty,
+        &store,
+        &pools,
+    )
+    .await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "export_query_json"))
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_table(
+    database_id: String,
+    table_name: String,
+    schema: Option<String>,
+    output_path: String,
+    format: CopyFormat,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<ExportResult, DatabaseErrorContext> {
+    let result = export_table_copy(
+        &database_id,
+        &table_name,
+        schema.as_deref(),
+        Path::new(&output_path),
+        format,
+        &store,
+        &pools,
+    )
+    .await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "export_table_copy").with_table(&table_name)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_fixtures::SqliteFixture;
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field(None), "");
+        assert_eq!(csv_field(Some(&Value::Null)), "");
+        assert_eq!(csv_field(Some(&Value::from(42))), "42");
+        assert_eq!(csv_field(Some(&Value::from("a,\"b\""))), "a,\"b\"");
+        assert_eq!(csv_field(Some(&json!({"k": 1}))), "{\"k\":1}");
+    }
+
+    #[test]
+    fn test_ordered_row_keeps_column_order() {
+        let columns = vec!["zeta".to_string(), "alpha".to_string()];
+        let row = HashMap::from([
+            ("alpha".to_string(), Value::from(1)),
+            ("zeta".to_string(), Value::from("z")),
+        ]);
+
+        let rendered = serde_json::to_string(&OrderedRow {
+            columns: &columns,
+            row: &row,
+        })
+        .unwrap();
+        assert_eq!(rendered, "{\"zeta\":\"z\",\"alpha\":1}");
+    }
+
+    #[test]
+    fn test_temporary_export_path() {
+        assert_eq!(
+            temporary_export_path(Path::new("/tmp/out/users.csv")),
+            Path::new("/tmp/out/users.csv.tmp")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_failed_csv_export_keeps_the_previous_file() {
+        let db = SqliteFixture::new();
+        let output = db.dir.join("users.csv");
+        std::fs::write(&output, "previous").unwrap();
+
+        let failed = export_query_csv(
+            "sqlite-1",
+            "SELECT * FROM missing",
+            &output,
+            None,
+            &db.store,
+            &db.pools,
+        )
+        .await;
+        assert!(failed.is_err());
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "previous");
+        assert!(!temporary_export_path(&output).exists());
+
+        let written = export_query_csv(
+            "sqlite-1",
+            "SELECT 1 AS n",
+            &output,
+            None,
+            &db.store,
+            &db.pools,
+        )
+        .await
+        .unwrap();
+        assert_eq!(written, 1);
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "n\n1\n");
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_failed_json_export_keeps_the_previous_file() {
+        let db = SqliteFixture::new();
+        let output = db.dir.join("users.json");
+        std::fs::write(&output, "previous").unwrap();
+
+        let failed = export_query_json(
+            "sqlite-1",
+            "SELECT * FROM missing",
+            &output,
+            false,
+            &db.store,
+            &db.pools,
+        )
+        .await;
+        assert!(failed.is_err());
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "previous");
+        assert!(!temporary_export_path(&output).exists());
+
+        let bytes = export_query_json(
+            "sqlite-1",
+            "SELECT 1 AS n",
+            &output,
+            false,
+            &db.store,
+            &db.pools,
+        )
+        .await
+        .unwrap();
+        let written = std::fs::read_to_string(&output).unwrap();
+        assert_eq!(bytes, written.len() as u64);
+        let parsed: Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(parsed["rows"], json!([{ "n": 1 }]));
+        assert_eq!(parsed["header"]["row_count"], 1);
+    }
+
+    #[test]
+    fn test_copy_row_counter() {
+        let mut csv = CopyRowCounter::new(CopyFormat::Csv);
+        csv.feed(b"id,note\n1,\"multi\nline \"\"quoted\"\"\"\n");
+        csv.feed(b"2,plain\n");
+        assert_eq!(csv.rows(), 2);
+
+        let mut text = CopyRowCounter::new(CopyFormat::Text);
+        text.feed(b"1\ta\\nb\n2\t\\N\n");
+        assert_eq!(text.rows(), 2);
+
+        // Two tuples of (int4, text): (1, 'ab') and (2, NULL), then the trailer
+        let mut binary_copy = b"PGCOPY\n\xff\r\n\0".to_vec();
+        binary_copy.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0]);
+        binary_copy.extend_from_slice(&[0, 2, 0, 0, 0, 4, 0, 0, 0, 1, 0, 0, 0, 2, b'a', b'b']);
+        binary_copy.extend_from_slice(&[0, 2, 0, 0, 0, 4, 0, 0, 0, 2, 0xff, 0xff, 0xff, 0xff]);
+        binary_copy.extend_from_slice(&[0xff, 0xff]);
+        let mut binary = CopyRowCounter::new(CopyFormat::Binary);
+        // Feed byte by byte so every field is split across chunks
+        for byte in &binary_copy {
+            binary.feed(std::slice::from_ref(byte));
+        }
+        assert_eq!(binary.rows(), 2);
+    }
+}
@@ -0,0 +1,140 @@
+use super::credentials::write_atomically;
+use super::types::{with_context, DatabaseError, DatabaseErrorContext, HistoryEntry};
+use chrono::Utc;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::State;
+
+/// Number of entries kept in the history, oldest entries are evicted first
+pub const MAX_HISTORY_ENTRIES: usize = 1000;
+
+/// History of successfully executed queries, persisted as a JSON file in the app
+/// data directory
+pub struct QueryHistory {
+    entries: Mutex<VecDeque<HistoryEntry>>,
+    path: PathBuf,
+}
+
+impl QueryHistory {
+    /// Load the history from `path`; a missing or unreadable file yields an empty history
+    pub fn load(path: PathBuf) -> Self {
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<VecDeque<HistoryEntry>>(&contents).ok())
+            .unwrap_or_default();
+
+        Self {
+            entries: Mutex::new(entries),
+            path,
+        }
+    }
+
+    /// Record an executed query and persist the history
+    pub fn record(
+        &self,
+        database_id: &str,
+        sql: &str,
+        execution_time_ms: u64,
+        row_count: usize,
+    ) -> Result<(), DatabaseError> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.push_back(HistoryEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            database_id: database_id.to_string(),
+            sql: sql.to_string(),
+            executed_at: Utc::now(),
+            execution_time_ms,
+            row_count,
+        });
+        while entries.len() > MAX_HISTORY_ENTRIES {
+            entries.pop_front();
+        }
+
+        self.persist(&entries)
+    }
+
+    /// Most recent entries first, optionally restricted to one database
+    pub fn list(&self, database_id: Option<&str>, limit: usize) -> Vec<HistoryEntry> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .rev()
+            .filter(|entry| database_id.is_none_or(|id| entry.database_id == id))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Remove the entries of one database, or all entries when `database_id` is `None`
+    pub fn clear(&self, database_id: Option<&str>) -> Result<(), DatabaseError> {
+        let mut entries = self.entries.lock().unwrap();
+        match database_id {
+            Some(id) => entries.retain(|entry| entry.database_id != id),
+            None => entries.clear(),
+        }
+
+        self.persist(&entries)
+    }
+
+    fn persist(&self, entries: &VecDeque<HistoryEntry>) -> Result<(), DatabaseError> {
+        let json =
+            serde_json::to_vec(entries).map_err(|e| DatabaseError::StorageError(e.to_string()))?;
+        write_atomically(&self.path, &json)
+    }
+}
+
+// Tauri commands for query history
+#[tauri::command]
+pub async fn get_query_history(
+    database_id: Option<String>,
+    limit: usize,
+    history: State<'_, QueryHistory>,
+) -> Result<Vec<HistoryEntry>, DatabaseErrorContext> {
+    Ok(history.list(database_id.as_deref(), limit))
+}
+
+#[tauri::command]
+pub async fn clear_query_history(
+    database_id: Option<String>,
+    history: State<'_, QueryHistory>,
+) -> Result<(), DatabaseErrorContext> {
+    history
+        .clear(database_id.as_deref())
+        .map_err(|e| with_context(e, database_id.as_deref(), "clear_history"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_is_bounded_and_persisted() {
+        let path =
+            std::env::temp_dir().join(format!("inspektor-history-{}.json", uuid::Uuid::new_v4()));
+        let history = QueryHistory::load(path.clone());
+
+        for i in 0..MAX_HISTORY_ENTRIES + 5 {
+            let database_id = if i % 2 == 0 { "db-a" } else { "db-b" };
+            history
+                .record(database_id, &format!("SELECT {}", i), 1, 1)
+                .unwrap();
+        }
+
+        let reloaded = QueryHistory::load(path.clone());
+        let all = reloaded.list(None, usize::MAX);
+        assert_eq!(all.len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(all[0].sql, format!("SELECT {}", MAX_HISTORY_ENTRIES + 4));
+
+        let db_a = reloaded.list(Some("db-a"), 3);
+        assert_eq!(db_a.len(), 3);
+        assert!(db_a.iter().all(|entry| entry.database_id == "db-a"));
+
+        reloaded.clear(Some("db-a")).unwrap();
+        assert!(reloaded.list(Some("db-a"), 10).is_empty());
+        assert!(!reloaded.list(Some("db-b"), 10).is_empty());
+
+        let _ = fs::remove_file(&path);
+    }
+}
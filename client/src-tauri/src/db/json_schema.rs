@@ -0,0 +1,184 @@
+use super::connection::PoolRegistry;
+use super::credentials::CredentialStore;
+use super::metadata::get_table_schema;
+use super::types::{
+    with_context, ColumnInfo, DatabaseError, DatabaseErrorContext, NormalizedType, TableSchema,
+};
+use serde_json::{json, Map, Value};
+use tauri::State;
+
+/// Export a table schema as a JSON Schema (draft-07) document
+pub async fn export_table_json_schema(
+    database_id: &str,
+    table_name: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<String, DatabaseError> {
+    let table_list = format!("('{}')", table_name.replace('\'', "''"));
+    let schemas = get_table_schema(database_id, &table_list, None, store, pools).await?;
+
+    let table = schemas
+        .into_iter()
+        .find(|s| s.table_name == table_name)
+        .ok_or_else(|| DatabaseError::Other(format!("Table not found: {}", table_name)))?;
+
+    serde_json::to_string_pretty(&build_json_schema(&table))
+        .map_err(|e| DatabaseError::Other(format!("Failed to serialize JSON Schema: {}", e)))
+}
+
+/// Build the JSON Schema `object` definition for a table
+pub fn build_json_schema(table: &TableSchema) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+
+    for column in &table.columns {
+        properties.insert(column.name.clone(), column_schema(column));
+
+        // Columns the database fills in on its own are not required from the client
+        if !column.is_nullable && column.default_value.is_none() {
+            required.push(Value::String(column.name.clone()));
+        }
+    }
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": table.table_name,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// Build the property definition for a single column
+fn column_schema(column: &ColumnInfo) -> Value {
+    let mut schema = Map::new();
+
+    match NormalizedType::from_data_type(&column.data_type) {
+        NormalizedType::Integer => {
+            let (minimum, maximum) = integer_range(&column.data_type);
+            schema.insert("type".to_string(), json!("integer"));
+            schema.insert("minimum".to_string(), json!(minimum));
+            schema.insert("maximum".to_string(), json!(maximum));
+        }
+        NormalizedType::Float => {
+            schema.insert("type".to_string(), json!("number"));
+        }
+        NormalizedType::Boolean => {
+            schema.insert("type".to_string(), json!("boolean"));
+        }
+        NormalizedType::Text => {
+            schema.insert("type".to_string(), json!("string"));
+            if let Some(max_length) = column.character_max_length {
+                schema.insert("maxLength".to_string(), json!(max_length));
+            }
+        }
+        NormalizedType::Binary => {
+            schema.insert("type".to_string(), json!("string"));
+            schema.insert("contentEncoding".to_string(), json!("base64"));
+        }
+        NormalizedType::Timestamp => {
+            schema.insert("type".to_string(), json!("string"));
+            schema.insert("format".to_string(), json!("date-time"));
+        }
+        NormalizedType::Date => {
+            schema.insert("type".to_string(), json!("string"));
+            schema.insert("format".to_string(), json!("date"));
+        }
+        NormalizedType::Uuid => {
+            schema.insert("type".to_string(), json!("string"));
+            schema.insert("format".to_string(), json!("uuid"));
+        }
+        // JSON columns and unknown types accept any value
+        NormalizedType::Json | NormalizedType::Unknown => {}
+    }
+
+    let mut schema = if column.is_nullable && schema.contains_key("type") {
+        json!({ "anyOf": [Value::Object(schema), { "type": "null" }] })
+    } else {
+        Value::Object(schema)
+    };
+
+    if let Some(obj) = schema.as_object_mut() {
+        obj.insert("description".to_string(), json!(column.data_type));
+    }
+    schema
+}
+
+/// Value range of an integer column based on its declared storage size
+fn integer_range(data_type: &str) -> (i64, i64) {
+    let lower = data_type.to_lowercase();
+    let unsigned = lower.contains("unsigned");
+    let base = lower.split(['(', ' ']).next().unwrap_or("");
+
+    match (base, unsigned) {
+        ("tinyint", false) => (i8::MIN as i64, i8::MAX as i64),
+        ("tinyint", true) => (0, u8::MAX as i64),
+        ("smallint" | "int2" | "smallserial", false) => (i16::MIN as i64, i16::MAX as i64),
+        ("smallint", true) => (0, u16::MAX as i64),
+        ("mediumint", false) => (-8_388_608, 8_388_607),
+        ("mediumint", true) => (0, 16_777_215),
+        ("int" | "int4" | "serial", false) => (i32::MIN as i64, i32::MAX as i64),
+        ("int", true) => (0, u32::MAX as i64),
+        // Postgres bigint and SQLite INTEGER (which may be reported as "integer") are 64-bit
+        (_, true) => (0, i64::MAX),
+        _ => (i64::MIN, i64::MAX),
+    }
+}
+
+// Tauri command for JSON Schema export
+#[tauri::command]
+pub async fn export_json_schema(
+    database_id: String,
+    table_name: String,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<String, DatabaseErrorContext> {
+    export_table_json_schema(&database_id, &table_name, &store, &pools)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "export_json_schema").with_table(&table_name)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, data_type: &str, is_nullable: bool) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            is_nullable,
+            is_primary_key: false,
+            default_value: None,
+            character_max_length: None,
+            is_unique: false,
+            unique_constraint_name: None,
+            is_inherited: false,
+        }
+    }
+
+    #[test]
+    fn test_json_schema_required_and_nullable() {
+        let mut id = column("id", "int4", false);
+        id.default_value = Some("nextval('users_id_seq')".to_string());
+        let mut email = column("email", "character varying", false);
+        email.character_max_length = Some(255);
+        let bio = column("bio", "text", true);
+
+        let table = TableSchema {
+            table_name: "users".to_string(),
+            schema: Some("public".to_string()),
+            columns: vec![id, email, bio],
+        };
+
+        let schema = build_json_schema(&table);
+
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["required"], json!(["email"]));
+        assert_eq!(schema["properties"]["id"]["type"], "integer");
+        assert_eq!(schema["properties"]["id"]["maximum"], json!(i32::MAX));
+        assert_eq!(schema["properties"]["email"]["maxLength"], 255);
+        assert_eq!(schema["properties"]["bio"]["anyOf"][1]["type"], "null");
+    }
+}
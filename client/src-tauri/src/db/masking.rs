@@ -0,0 +1,205 @@
+use super::types::{DatabaseCredentials, MaskingStrategy};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Mask values in query results according to the credential's masking rules
+///
+/// The first matching rule wins for each column. Rules listing the connection's
+/// username in `except_roles` are skipped. Returns the masked columns together with
+/// the strategy applied to them.
+pub fn apply_masking(
+    creds: &DatabaseCredentials,
+    columns: &[String],
+    rows: &mut [HashMap<String, Value>],
+) -> Vec<(String, String)> {
+    let rules = match &creds.masking_rules {
+        Some(rules) if !rules.is_empty() => rules,
+        _ => return Vec::new(),
+    };
+    let role = creds.username.as_deref().unwrap_or("");
+
+    let mut applied = Vec::new();
+    for column in columns {
+        let rule = rules.iter().find(|rule| {
+            glob_match(&rule.column_pattern, column) && !rule.except_roles.iter().any(|r| r == role)
+        });
+        let Some(rule) = rule else {
+            continue;
+        };
+
+        for row in rows.iter_mut() {
+            if let Some(value) = row.get_mut(column) {
+                *value = mask_value(value, &rule.strategy);
+            }
+        }
+        applied.push((column.clone(), rule.strategy.name().to_string()));
+    }
+
+    applied
+}
+
+/// Mask a single value; NULLs stay NULL so the result still shows missing data
+fn mask_value(value: &Value, strategy: &MaskingStrategy) -> Value {
+    if value.is_null() {
+        return Value::Null;
+    }
+
+    let text = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+
+    match strategy {
+        MaskingStrategy::Redact => Value::String("***".to_string()),
+        MaskingStrategy::Partial {
+            visible_prefix,
+            visible_suffix,
+        } => Value::String(mask_partial(&text, *visible_prefix, *visible_suffix)),
+        MaskingStrategy::Hash => {
+            let digest = Sha256::digest(text.as_bytes());
+            Value::String(digest.iter().map(|b| format!("{:02x}", b)).collect())
+        }
+        MaskingStrategy::Nullify => Value::Null,
+    }
+}
+
+/// Keep the first and last characters visible and replace the rest with `*`
+///
+/// For email addresses the domain is always kept and the visible characters apply
+/// to the local part, so `alice@example.com` becomes `ali**@example.com`.
+fn mask_partial(text: &str, visible_prefix: usize, visible_suffix: usize) -> String {
+    if let Some((local, domain)) = text.split_once('@') {
+        return format!(
+            "{}@{}",
+            mask_partial(local, visible_prefix, visible_suffix),
+            domain
+        );
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    if visible_prefix + visible_suffix >= chars.len() {
+        return text.to_string();
+    }
+
+    let suffix_start = chars.len() - visible_suffix;
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            if i < visible_prefix || i >= suffix_start {
+                *c
+            } else {
+                '*'
+            }
+        })
+        .collect()
+}
+
+/// Case-insensitive glob match supporting `*` (any run of characters) and `?`
+/// (exactly one character)
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = backtrack {
+            // Let the last `*` absorb one more character and retry
+            p = star_p + 1;
+            n = star_n + 1;
+            backtrack = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::types::MaskingRule;
+    use serde_json::json;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*password*", "user_password_hash"));
+        assert!(glob_match("email", "EMAIL"));
+        assert!(glob_match("phone_?", "phone_1"));
+        assert!(!glob_match("email", "email_verified"));
+        assert!(!glob_match("*password*", "username"));
+    }
+
+    #[test]
+    fn test_apply_masking() {
+        let mut creds: DatabaseCredentials = serde_json::from_value(json!({
+            "id": "db-1",
+            "name": "prod",
+            "db_type": "postgres",
+            "host": "localhost",
+            "port": 5432,
+            "database": "app",
+            "username": "analyst",
+            "password": null,
+            "file_path": null,
+        }))
+        .unwrap();
+        creds.masking_rules = Some(vec![
+            MaskingRule {
+                column_pattern: "*password*".to_string(),
+                strategy: MaskingStrategy::Redact,
+                except_roles: Vec::new(),
+            },
+            MaskingRule {
+                column_pattern: "email".to_string(),
+                strategy: MaskingStrategy::Partial {
+                    visible_prefix: 3,
+                    visible_suffix: 0,
+                },
+                except_roles: vec!["admin".to_string()],
+            },
+        ]);
+
+        let columns = vec![
+            "id".to_string(),
+            "email".to_string(),
+            "password_hash".to_string(),
+        ];
+        let mut rows = vec![HashMap::from([
+            ("id".to_string(), json!(1)),
+            ("email".to_string(), json!("alice@example.com")),
+            ("password_hash".to_string(), json!("$argon2id$...")),
+        ])];
+
+        let applied = apply_masking(&creds, &columns, &mut rows);
+
+        assert_eq!(
+            applied,
+            vec![
+                ("email".to_string(), "partial".to_string()),
+                ("password_hash".to_string(), "redact".to_string()),
+            ]
+        );
+        assert_eq!(rows[0]["id"], json!(1));
+        assert_eq!(rows[0]["email"], json!("ali**@example.com"));
+        assert_eq!(rows[0]["password_hash"], json!("***"));
+
+        creds.username = Some("admin".to_string());
+        let mut rows = vec![HashMap::from([(
+            "email".to_string(),
+            json!("alice@example.com"),
+        )])];
+        apply_masking(&creds, &columns, &mut rows);
+        assert_eq!(rows[0]["email"], json!("alice@example.com"));
+    }
+}
@@ -1,32 +1,66 @@
-use super::connection::create_pool;
+use super::clickhouse;
+use super::connection::{recheck_on_connection_error, PoolRegistry};
 use super::credentials::CredentialStore;
-use super::types::{ColumnInfo, DatabaseError, DatabaseType, Relationship, TableInfo, TableSchema};
-use sqlx::{Column, Row, TypeInfo};
-use tauri::State;
+use super::masking::apply_masking;
+use super::query::convert_row;
+use super::types::{
+    with_context, AnalyzeProgress, BlockedQuery, CheckConstraintInfo, ColumnChange, ColumnInfo,
+    ColumnSearchHit, ColumnSearchResult, ColumnStatistics, DatabaseError, DatabaseErrorContext,
+    DatabaseRelationships, DatabaseSizeInfo, DatabaseType, DependencyInfo, EnumTypeInfo, IndexInfo, JournalMode,
+    NormalizedType, PragmaRecommendation, Relationship, RelationshipSource, ReplicaInfo,
+    RoutineInfo, RoutineType, SchemaDiff, SchemaFetchCompleteEvent, SchemaInfo, SchemaSearchResult,
+    SchemaTableLoadedEvent, SequenceInfo, SqlitePragmaReport, SqlitePragmas, TableDDL,
+    TableDeadTupleInfo, TableDiff, TableInfo, TableLockWaitStats, TableReference,
+    TableRowCountResult, TableSchema, TableSizeInfo, TableStatistics, TableType, TriggerInfo,
+    VacuumProgress,
+};
+use super::utils::quote_identifier;
+use futures::future::join_all;
+use futures::stream::{self, StreamExt};
+use sqlx::{Column, Row};
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::Semaphore;
 
 pub async fn get_tables(
     database_id: &str,
     store: &CredentialStore,
+    pools: &PoolRegistry,
 ) -> Result<Vec<TableInfo>, DatabaseError> {
     let creds = store.get(database_id)?;
-    let pool = create_pool(&creds).await?;
+    if matches!(creds.db_type, DatabaseType::ClickHouse) {
+        return clickhouse::get_tables(&creds).await;
+    }
+    let pool = pools.get_or_create(database_id, &creds).await?;
 
     let query = match creds.db_type {
         DatabaseType::Postgres => {
-            "SELECT table_name::text, table_schema::text FROM information_schema.tables WHERE table_schema NOT IN ('pg_catalog', 'information_schema') ORDER BY table_name".to_string()
+            // Materialized views are not part of information_schema.tables. Partitions
+            // are listed in pg_inherits too, so they report their partitioned table
+            // as parent
+            "SELECT table_name::text, table_schema::text, table_type::text,
+                (SELECT p.relname::text FROM pg_inherits i
+                    JOIN pg_class p ON p.oid = i.inhparent
+                    WHERE i.inhrelid = format('%I.%I', table_schema, table_name)::regclass
+                    ORDER BY i.inhseqno LIMIT 1) AS parent_table
+            FROM information_schema.tables WHERE table_schema NOT IN ('pg_catalog', 'information_schema')
+            UNION ALL
+            SELECT matviewname::text AS table_name, schemaname::text AS table_schema, 'MATERIALIZED VIEW' AS table_type, NULL::text AS parent_table FROM pg_matviews WHERE schemaname NOT IN ('pg_catalog', 'information_schema')
+            ORDER BY table_name".to_string()
         }
         DatabaseType::MySQL => {
-            format!("SELECT table_name, table_schema FROM information_schema.tables WHERE table_schema = '{}' ORDER BY table_name", creds.database)
+            format!("SELECT table_name, table_schema, table_type FROM information_schema.tables WHERE table_schema = '{}' ORDER BY table_name", creds.database.replace('\'', "''"))
         }
         DatabaseType::SQLite => {
-            "SELECT name as table_name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name".to_string()
+            "SELECT name as table_name, type as table_type FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name".to_string()
         }
+        DatabaseType::ClickHouse => unreachable!(),
     };
 
     let rows = sqlx::query(&query)
-        .fetch_all(&pool)
+        .fetch_all(&*pool)
         .await
-        .map_err(|e| DatabaseError::QueryError(format!("{}\n\nSQL Query:\n{}", e, query)))?;
+        .map_err(|e| DatabaseError::query_failed(e, &query))?;
 
     let mut tables = Vec::new();
     for row in rows {
@@ -34,7 +68,7 @@ pub async fn get_tables(
         let table_name: String = row
             .try_get("table_name")
             .or_else(|_| row.try_get("TABLE_NAME"))
-            .map_err(|e| DatabaseError::QueryError(format!("{}\n\nSQL Query:\n{}", e, query)))?;
+            .map_err(|e| DatabaseError::query_failed(e, &query))?;
 
         let schema: Option<String> = if matches!(creds.db_type, DatabaseType::SQLite) {
             None
@@ -44,14 +78,25 @@ pub async fn get_tables(
                 .ok()
         };
 
+        let table_type: String = row
+            .try_get("table_type")
+            .or_else(|_| row.try_get("TABLE_TYPE"))
+            .unwrap_or_default();
+
+        // Only the Postgres query selects a parent table
+        let parent_table: Option<String> = row
+            .try_get::<Option<String>, _>("parent_table")
+            .ok()
+            .flatten();
+
         tables.push(TableInfo {
             name: table_name,
             schema,
             row_count: None, // We could optionally count rows here
+            table_type: TableType::from_table_type(&table_type),
+            parent_table,
         });
     }
-
-    pool.close().await;
     Ok(tables)
 }
 
@@ -60,9 +105,13 @@ pub async fn get_table_schema(
     table_names: &str,
     schema: Option<&str>,
     store: &CredentialStore,
+    pools: &PoolRegistry,
 ) -> Result<Vec<TableSchema>, DatabaseError> {
     let creds = store.get(database_id)?;
-    let pool = create_pool(&creds).await?;
+    if matches!(creds.db_type, DatabaseType::ClickHouse) {
+        return clickhouse::get_table_schema(&creds, table_names).await;
+    }
+    let pool = pools.get_or_create(database_id, &creds).await?;
 
     let mut schemas = Vec::new();
 
@@ -77,34 +126,45 @@ pub async fn get_table_schema(
             .collect();
 
         for table_name in tables {
-            let query = format!("PRAGMA table_info('{}')", table_name);
+            let query = format!(
+                "PRAGMA table_info({})",
+                quote_identifier(table_name, &DatabaseType::SQLite)
+            );
             let rows = sqlx::query(&query)
-                .fetch_all(&pool)
+                .fetch_all(&*pool)
                 .await
-                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+                .map_err(DatabaseError::from)?;
+            let unique_columns = get_sqlite_unique_columns(&pool, table_name).await?;
 
             let mut columns = Vec::new();
             for row in rows {
                 let col_name: String = row
                     .try_get("name")
-                    .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+                    .map_err(DatabaseError::from)?;
                 let data_type: String = row
                     .try_get("type")
-                    .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+                    .map_err(DatabaseError::from)?;
                 let not_null: i32 = row
                     .try_get("notnull")
-                    .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+                    .map_err(DatabaseError::from)?;
                 let pk: i32 = row
                     .try_get("pk")
-                    .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+                    .map_err(DatabaseError::from)?;
                 let default_val: Option<String> = row.try_get("dflt_value").ok();
 
+                let character_max_length = parse_type_length(&data_type);
+                let unique_constraint_name = unique_columns.get(&col_name).cloned();
+
                 columns.push(ColumnInfo {
                     name: col_name,
                     data_type,
                     is_nullable: not_null == 0,
                     is_primary_key: pk > 0,
                     default_value: default_val,
+                    character_max_length,
+                    is_unique: unique_constraint_name.is_some(),
+                    unique_constraint_name,
+                    is_inherited: false,
                 });
             }
 
@@ -118,6 +178,7 @@ pub async fn get_table_schema(
         // Postgres and MySQL can query multiple tables at once
         let query = match creds.db_type {
             DatabaseType::Postgres => {
+                let schema_name = schema.unwrap_or("public").replace('\'', "''");
                 format!(
                     "SELECT
                         c.table_name::text,
@@ -125,7 +186,10 @@ pub async fn get_table_schema(
                         c.data_type::text,
                         c.is_nullable::text,
                         c.column_default::text,
-                        CASE WHEN pk.column_name IS NOT NULL THEN true ELSE false END as is_primary_key
+                        c.character_maximum_length::bigint AS character_max_length,
+                        CASE WHEN pk.column_name IS NOT NULL THEN true ELSE false END as is_primary_key,
+                        uq.constraint_name AS unique_constraint_name,
+                        {} AS is_inherited
                     FROM information_schema.columns c
                     LEFT JOIN (
                         SELECT ku.table_name::text, ku.column_name::text
@@ -137,12 +201,16 @@ pub async fn get_table_schema(
                             AND tc.table_name IN {}
                             AND tc.table_schema = '{}'
                     ) pk ON c.table_name = pk.table_name AND c.column_name = pk.column_name
+                    LEFT JOIN ({}) uq
+                        ON c.table_name = uq.table_name AND c.column_name = uq.column_name
                     WHERE c.table_name IN {} AND c.table_schema = '{}'
                     ORDER BY c.table_name, c.ordinal_position",
+                    POSTGRES_IS_INHERITED,
                     table_names,
-                    schema.unwrap_or("public"),
+                    schema_name,
+                    postgres_unique_columns_query(table_names, &schema_name),
                     table_names,
-                    schema.unwrap_or("public")
+                    schema_name
                 )
             }
             DatabaseType::MySQL => {
@@ -153,21 +221,25 @@ pub async fn get_table_schema(
                         CAST(data_type AS CHAR) AS `data_type`,
                         CAST(is_nullable AS CHAR) AS `is_nullable`,
                         CAST(column_default AS CHAR) as `column_default`,
-                        CASE WHEN column_key = 'PRI' THEN 1 ELSE 0 END as `is_primary_key`
-                    FROM information_schema.columns
+                        CAST(character_maximum_length AS SIGNED) as `character_max_length`,
+                        CASE WHEN column_key = 'PRI' THEN 1 ELSE 0 END as `is_primary_key`,
+                        CASE WHEN column_key = 'UNI' THEN 1 ELSE 0 END as `is_unique`,
+                        {} as `unique_constraint_name`
+                    FROM information_schema.columns c
                     WHERE table_name IN {} AND table_schema = '{}'
                     ORDER BY table_name, ordinal_position",
+                    MYSQL_UNIQUE_INDEX_NAME,
                     table_names,
-                    creds.database
+                    creds.database.replace('\'', "''")
                 )
             }
-            DatabaseType::SQLite => unreachable!(),
+            DatabaseType::SQLite | DatabaseType::ClickHouse => unreachable!(),
         };
 
         let rows = sqlx::query(&query)
-            .fetch_all(&pool)
+            .fetch_all(&*pool)
             .await
-            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+            .map_err(DatabaseError::from)?;
 
         // Build a helper function to get column value by trying different case variations
         let get_column_value = |row: &sqlx::any::AnyRow, base_name: &str| -> Result<String, DatabaseError> {
@@ -203,7 +275,6 @@ pub async fn get_table_schema(
         };
 
         // Group columns by table name
-        use std::collections::HashMap;
         let mut tables_map: HashMap<String, Vec<ColumnInfo>> = HashMap::new();
 
         for row in rows {
@@ -227,17 +298,44 @@ pub async fn get_table_schema(
                 pk_val
             };
 
+            let character_max_length: Option<i64> = row
+                .try_get::<Option<i64>, _>("character_max_length")
+                .or_else(|_| row.try_get::<Option<i64>, _>("CHARACTER_MAX_LENGTH"))
+                .ok()
+                .flatten();
+
+            let unique_constraint_name: Option<String> = row
+                .try_get::<Option<String>, _>("unique_constraint_name")
+                .or_else(|_| row.try_get::<Option<String>, _>("UNIQUE_CONSTRAINT_NAME"))
+                .ok()
+                .flatten();
+            let is_unique = if matches!(creds.db_type, DatabaseType::MySQL) {
+                get_mysql_int(&row, "is_unique")
+                    .or_else(|| get_mysql_int(&row, "IS_UNIQUE"))
+                    .unwrap_or(0)
+                    > 0
+            } else {
+                unique_constraint_name.is_some()
+            };
+
+            // MySQL has no table inheritance and does not select the column
+            let is_inherited: bool = row.try_get("is_inherited").unwrap_or(false);
+
             let column_info = ColumnInfo {
                 name: col_name,
                 data_type,
                 is_nullable,
                 is_primary_key: is_pk,
                 default_value: default_val,
+                character_max_length,
+                is_unique,
+                unique_constraint_name,
+                is_inherited,
             };
 
             tables_map
                 .entry(table_name)
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(column_info);
         }
 
@@ -250,17 +348,116 @@ pub async fn get_table_schema(
             });
         }
     }
-
-    pool.close().await;
     Ok(schemas)
 }
 
+/// Select list expression naming the first single-column unique index of column `c`
+/// in `information_schema.columns c`
+const MYSQL_UNIQUE_INDEX_NAME: &str = "(SELECT CAST(MIN(s.index_name) AS CHAR)
+        FROM information_schema.statistics s
+        WHERE s.table_schema = c.table_schema
+            AND s.table_name = c.table_name
+            AND s.column_name = c.column_name
+            AND s.non_unique = 0
+            AND s.index_name <> 'PRIMARY'
+            AND NOT EXISTS (
+                SELECT 1 FROM information_schema.statistics s2
+                WHERE s2.table_schema = s.table_schema
+                    AND s2.table_name = s.table_name
+                    AND s2.index_name = s.index_name
+                    AND s2.seq_in_index > 1
+            ))";
+
+/// Select list expression telling whether column `c` of `information_schema.columns c`
+/// also exists on a table that `c`'s table inherits from
+const POSTGRES_IS_INHERITED: &str = "EXISTS (
+        SELECT 1 FROM pg_inherits i
+        JOIN pg_attribute pa ON pa.attrelid = i.inhparent
+        WHERE i.inhrelid = format('%I.%I', c.table_schema, c.table_name)::regclass
+            AND pa.attname = c.column_name
+            AND pa.attnum > 0
+            AND NOT pa.attisdropped
+    )";
+
+/// Subquery listing the columns covered by a single-column UNIQUE constraint, with
+/// the constraint name, for the given `('a', 'b')` table list
+fn postgres_unique_columns_query(table_names: &str, schema: &str) -> String {
+    format!(
+        "SELECT ku.table_name::text AS table_name, ku.column_name::text AS column_name,
+            MIN(tc.constraint_name::text) AS constraint_name
+        FROM information_schema.table_constraints tc
+        JOIN information_schema.key_column_usage ku
+            ON tc.constraint_name = ku.constraint_name
+            AND tc.table_schema = ku.table_schema
+        WHERE tc.constraint_type = 'UNIQUE'
+            AND tc.table_name IN {}
+            AND tc.table_schema = '{}'
+            AND NOT EXISTS (
+                SELECT 1 FROM information_schema.key_column_usage other
+                WHERE other.constraint_name = ku.constraint_name
+                    AND other.table_schema = ku.table_schema
+                    AND other.column_name <> ku.column_name
+            )
+        GROUP BY ku.table_name, ku.column_name",
+        table_names, schema
+    )
+}
+
+/// Map SQLite columns covered by a single-column unique index to the index name
+///
+/// Indexes backing the primary key are left out, as are partial indexes since they
+/// only enforce uniqueness on some rows.
+async fn get_sqlite_unique_columns(
+    pool: &sqlx::AnyPool,
+    table_name: &str,
+) -> Result<HashMap<String, String>, DatabaseError> {
+    let query = format!(
+        "PRAGMA index_list({})",
+        quote_identifier(table_name, &DatabaseType::SQLite)
+    );
+    let indexes = sqlx::query(&query)
+        .fetch_all(pool)
+        .await
+        .map_err(DatabaseError::from)?;
+
+    let mut unique_columns = HashMap::new();
+    for index in indexes {
+        let unique: i32 = index.try_get("unique").unwrap_or(0);
+        let origin: String = index.try_get("origin").unwrap_or_default();
+        let partial: i32 = index.try_get("partial").unwrap_or(0);
+        if unique != 1 || origin == "pk" || partial == 1 {
+            continue;
+        }
+        let index_name: String = index
+            .try_get("name")
+            .map_err(DatabaseError::from)?;
+
+        let info_query = format!(
+            "PRAGMA index_info({})",
+            quote_identifier(&index_name, &DatabaseType::SQLite)
+        );
+        let columns = sqlx::query(&info_query)
+            .fetch_all(pool)
+            .await
+            .map_err(DatabaseError::from)?;
+        // Expression indexes report a NULL column name
+        if let [column] = columns.as_slice() {
+            if let Ok(Some(column_name)) = column.try_get::<Option<String>, _>("name") {
+                unique_columns.entry(column_name).or_insert(index_name);
+            }
+        }
+    }
+
+    Ok(unique_columns)
+}
+
 pub async fn get_relationships(
     database_id: &str,
     store: &CredentialStore,
+    pools: &PoolRegistry,
 ) -> Result<Vec<Relationship>, DatabaseError> {
     let creds = store.get(database_id)?;
-    let pool = create_pool(&creds).await?;
+    let pool = pools.get_or_create(database_id, &creds).await?;
 
     let mut relationships = Vec::new();
 
@@ -269,13 +466,179 @@ pub async fn get_relationships(
     relationships.extend(explicit_relationships);
 
     // Step 2: Infer relationships based on naming conventions and schema analysis
-    let inferred_relationships = infer_relationships(&creds, &pool).await?;
+    let schemas = get_all_table_schemas(&creds, &pool).await?;
+    let inferred_relationships = infer_relationships(&schemas);
     relationships.extend(inferred_relationships);
+    remove_duplicate_inferred(&mut relationships);
 
-    pool.close().await;
+    // Step 3: Link the tables joined by junction tables, using both kinds of keys
+    let many_to_many = detect_junction_tables(&schemas, &relationships);
+    relationships.extend(many_to_many);
     Ok(relationships)
 }
 
+/// Drop inferred relationships that repeat a declared foreign key
+fn remove_duplicate_inferred(relationships: &mut Vec<Relationship>) {
+    let key = |rel: &Relationship| {
+        (
+            rel.table_name.clone(),
+            rel.column_name.clone(),
+            rel.foreign_table.clone(),
+            rel.foreign_column.clone(),
+        )
+    };
+    let explicit: HashSet<_> = relationships
+        .iter()
+        .filter(|rel| rel.source == RelationshipSource::Explicit)
+        .map(key)
+        .collect();
+
+    relationships
+        .retain(|rel| rel.source != RelationshipSource::Inferred || !explicit.contains(&key(rel)));
+}
+
+/// Find the tables, views and functions that depend on a table
+///
+/// Dependencies through relationships (explicit or inferred) are reported for all
+/// databases. On Postgres, views and functions referencing the table are found
+/// through `pg_depend`. The table is looked up in `schema`, `public` by default on
+/// Postgres, so a table of the same name in another schema is not matched.
+/// Results are sorted by the dependent's name.
+pub async fn get_table_dependents(
+    database_id: &str,
+    table_name: &str,
+    schema: Option<&str>,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<DependencyInfo>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    let schema = match creds.db_type {
+        DatabaseType::Postgres => Some(schema.unwrap_or("public")),
+        _ => schema,
+    };
+
+    let mut dependents: Vec<DependencyInfo> = get_relationships(database_id, store, pools)
+        .await?
+        .into_iter()
+        .filter(|rel| references_table(rel, table_name, schema))
+        .map(|rel| DependencyInfo {
+            dependent_table: rel.table_name,
+            via_column: rel.column_name,
+            via_constraint: rel.constraint_name,
+            dependency_type: rel.source.dependency_type().to_string(),
+        })
+        .collect();
+
+    if matches!(creds.db_type, DatabaseType::Postgres) {
+        let pool = pools.get_or_create(database_id, &creds).await?;
+        let regclass = qualified_table_name(table_name, schema, &creds.db_type).replace('\'', "''");
+
+        // Views depend on the table through their rewrite rule, SQL-standard function
+        // bodies depend on it directly
+        let query = format!(
+            "SELECT v.relname::text AS dependent, 'view' AS dependency_type,
+                COALESCE(string_agg(DISTINCT a.attname::text, ', '), '') AS via_column
+            FROM pg_depend d
+            JOIN pg_rewrite r ON r.oid = d.objid AND d.classid = 'pg_rewrite'::regclass
+            JOIN pg_class v ON v.oid = r.ev_class
+            LEFT JOIN pg_attribute a ON a.attrelid = d.refobjid AND a.attnum = d.refobjsubid
+            WHERE d.refobjid = to_regclass('{0}') AND v.oid <> d.refobjid
+            GROUP BY v.relname
+            UNION ALL
+            SELECT p.proname::text AS dependent, 'function' AS dependency_type,
+                COALESCE(string_agg(DISTINCT a.attname::text, ', '), '') AS via_column
+            FROM pg_depend d
+            JOIN pg_proc p ON p.oid = d.objid AND d.classid = 'pg_proc'::regclass
+            LEFT JOIN pg_attribute a ON a.attrelid = d.refobjid AND a.attnum = d.refobjsubid
+            WHERE d.refobjid = to_regclass('{0}')
+            GROUP BY p.proname",
+            regclass
+        );
+
+        let rows = sqlx::query(&query)
+            .fetch_all(&*pool)
+            .await
+            .map_err(|e| DatabaseError::query_failed(e, &query))?;
+
+        for row in rows {
+            dependents.push(DependencyInfo {
+                dependent_table: row
+                    .try_get("dependent")
+                    .map_err(DatabaseError::from)?,
+                via_column: row.try_get("via_column").unwrap_or_default(),
+                via_constraint: None,
+                dependency_type: row.try_get("dependency_type").unwrap_or_default(),
+            });
+        }
+    }
+
+    dependents.sort_by(|a, b| a.dependent_table.cmp(&b.dependent_table));
+    Ok(dependents)
+}
+
+/// Whether a relationship points at `table_name` in `schema`
+///
+/// A relationship without a foreign schema (MySQL, SQLite, or an inferred
+/// relationship whose target schema is unknown) matches any schema.
+fn references_table(rel: &Relationship, table_name: &str, schema: Option<&str>) -> bool {
+    rel.foreign_table == table_name
+        && match (schema, rel.foreign_schema.as_deref()) {
+            (Some(schema), Some(foreign_schema)) => schema == foreign_schema,
+            _ => true,
+        }
+}
+
+/// Find everything referencing `target_table`, as shown before dropping or altering it
+///
+/// Built on `get_table_dependents`, which looks the table up in `schema`.
+pub async fn find_table_references(
+    database_id: &str,
+    target_table: &str,
+    schema: Option<&str>,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<TableReference>, DatabaseError> {
+    let dependents = get_table_dependents(database_id, target_table, schema, store, pools).await?;
+
+    Ok(dependents
+        .into_iter()
+        .map(|dep| TableReference {
+            from_table: dep.dependent_table,
+            from_column: dep.via_column,
+            constraint_name: dep.via_constraint,
+            relationship_type: dep.dependency_type,
+        })
+        .collect())
+}
+
+/// Maximum number of databases whose relationships are fetched at the same time
+const MAX_CONCURRENT_RELATIONSHIP_FETCHES: usize = 4;
+
+/// Get relationships for several databases concurrently
+///
+/// Every database gets its own result, so one that cannot be read does not hide
+/// the relationships of the others.
+pub async fn get_relationships_for_databases(
+    database_ids: &[String],
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> HashMap<String, Result<Vec<Relationship>, DatabaseError>> {
+    let semaphore = Semaphore::new(MAX_CONCURRENT_RELATIONSHIP_FETCHES);
+
+    let fetches = database_ids.iter().map(|database_id| {
+        let semaphore = &semaphore;
+        async move {
+            let relationships = match semaphore.acquire().await {
+                Ok(_permit) => get_relationships(database_id, store, pools).await,
+                Err(e) => Err(DatabaseError::Other(e.to_string())),
+            };
+            (database_id.clone(), relationships)
+        }
+    });
+
+    join_all(fetches).await.into_iter().collect()
+}
+
 /// Get explicit foreign key constraints from the database
 async fn get_explicit_relationships(
     creds: &super::types::DatabaseCredentials,
@@ -283,14 +646,18 @@ async fn get_explicit_relationships(
 ) -> Result<Vec<Relationship>, DatabaseError> {
     // Handle SQLite separately since it uses PRAGMA
     if matches!(creds.db_type, DatabaseType::SQLite) {
-        return get_sqlite_foreign_keys(pool).await;
+        let mut relationships = get_sqlite_foreign_keys(pool).await?;
+        mark_self_referential(&mut relationships);
+        return Ok(relationships);
     }
 
     let query = match creds.db_type {
         DatabaseType::Postgres => {
             "SELECT
+                tc.table_schema::text AS table_schema,
                 tc.table_name::text,
                 kcu.column_name::text,
+                ccu.table_schema::text AS foreign_schema,
                 ccu.table_name::text AS foreign_table,
                 ccu.column_name::text AS foreign_column,
                 tc.constraint_name::text
@@ -314,16 +681,16 @@ async fn get_explicit_relationships(
                 FROM information_schema.key_column_usage
                 WHERE referenced_table_name IS NOT NULL
                     AND table_schema = '{}'",
-                creds.database
+                creds.database.replace('\'', "''")
             )
         }
-        DatabaseType::SQLite => unreachable!(),
+        DatabaseType::SQLite | DatabaseType::ClickHouse => unreachable!(),
     };
 
     let rows = sqlx::query(&query)
         .fetch_all(pool)
         .await
-        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        .map_err(DatabaseError::from)?;
 
     let mut relationships = Vec::new();
     for row in rows {
@@ -332,29 +699,36 @@ async fn get_explicit_relationships(
             table_name: row
                 .try_get("table_name")
                 .or_else(|_| row.try_get("TABLE_NAME"))
-                .map_err(|e| DatabaseError::QueryError(e.to_string()))?,
+                .map_err(DatabaseError::from)?,
             column_name: row
                 .try_get("column_name")
                 .or_else(|_| row.try_get("COLUMN_NAME"))
-                .map_err(|e| DatabaseError::QueryError(e.to_string()))?,
+                .map_err(DatabaseError::from)?,
             foreign_table: row
                 .try_get("foreign_table")
                 .or_else(|_| row.try_get("FOREIGN_TABLE"))
-                .map_err(|e| DatabaseError::QueryError(e.to_string()))?,
+                .map_err(DatabaseError::from)?,
             foreign_column: row
                 .try_get("foreign_column")
                 .or_else(|_| row.try_get("FOREIGN_COLUMN"))
-                .map_err(|e| DatabaseError::QueryError(e.to_string()))?,
+                .map_err(DatabaseError::from)?,
             constraint_name: Some(
                 row.try_get("constraint_name")
                     .or_else(|_| row.try_get("CONSTRAINT_NAME"))
-                    .map_err(|e| DatabaseError::QueryError(e.to_string()))?,
+                    .map_err(DatabaseError::from)?,
             ),
-            relationship_type: "foreign_key".to_string(),
+            source: RelationshipSource::Explicit,
             confidence: None,
+            junction_table: None,
+            is_self_referential: false,
+            relationship_cardinality: None,
+            // MySQL does not select the schema columns
+            schema: row.try_get("table_schema").ok(),
+            foreign_schema: row.try_get("foreign_schema").ok(),
         });
     }
 
+    mark_self_referential(&mut relationships);
     Ok(relationships)
 }
 
@@ -365,32 +739,35 @@ async fn get_sqlite_foreign_keys(pool: &sqlx::AnyPool) -> Result<Vec<Relationshi
     let table_rows = sqlx::query(table_query)
         .fetch_all(pool)
         .await
-        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        .map_err(DatabaseError::from)?;
 
     let mut relationships = Vec::new();
 
     for table_row in table_rows {
         let table_name: String = table_row
             .try_get("name")
-            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+            .map_err(DatabaseError::from)?;
 
         // Use PRAGMA to get foreign keys for this table
-        let pragma_query = format!("PRAGMA foreign_key_list('{}')", table_name);
+        let pragma_query = format!(
+            "PRAGMA foreign_key_list({})",
+            quote_identifier(&table_name, &DatabaseType::SQLite)
+        );
         let fk_rows = sqlx::query(&pragma_query)
             .fetch_all(pool)
             .await
-            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+            .map_err(DatabaseError::from)?;
 
         for fk_row in fk_rows {
             let foreign_table: String = fk_row
                 .try_get("table")
-                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+                .map_err(DatabaseError::from)?;
             let column_name: String = fk_row
                 .try_get("from")
-                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+                .map_err(DatabaseError::from)?;
             let foreign_column: String = fk_row
                 .try_get("to")
-                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+                .map_err(DatabaseError::from)?;
 
             relationships.push(Relationship {
                 table_name: table_name.clone(),
@@ -398,8 +775,13 @@ async fn get_sqlite_foreign_keys(pool: &sqlx::AnyPool) -> Result<Vec<Relationshi
                 foreign_table,
                 foreign_column,
                 constraint_name: None, // SQLite PRAGMA doesn't return constraint names
-                relationship_type: "foreign_key".to_string(),
+                source: RelationshipSource::Explicit,
                 confidence: None,
+                junction_table: None,
+                is_self_referential: false,
+                relationship_cardinality: None,
+                schema: None,
+                foreign_schema: None,
             });
         }
     }
@@ -408,32 +790,29 @@ async fn get_sqlite_foreign_keys(pool: &sqlx::AnyPool) -> Result<Vec<Relationshi
 }
 
 /// Infer relationships based on naming conventions and schema patterns
-async fn infer_relationships(
-    creds: &super::types::DatabaseCredentials,
-    pool: &sqlx::AnyPool,
-) -> Result<Vec<Relationship>, DatabaseError> {
-    use std::collections::HashMap;
-
-    // Get all tables and their columns with types
-    let schemas = get_all_table_schemas(creds, pool).await?;
-
+fn infer_relationships(schemas: &[TableSchema]) -> Vec<Relationship> {
     // Build a map of potential primary keys: table_name -> [(column_name, data_type)]
     let mut primary_keys: HashMap<String, Vec<(String, String)>> = HashMap::new();
     let mut all_columns: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    // Tables are matched by name alone, the first schema with the name is used
+    let mut table_schemas: HashMap<&str, Option<String>> = HashMap::new();
 
-    for schema in &schemas {
+    for schema in schemas {
         let table_name = &schema.table_name;
+        table_schemas
+            .entry(table_name.as_str())
+            .or_insert_with(|| schema.schema.clone());
 
         for col in &schema.columns {
             all_columns
                 .entry(table_name.clone())
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push((col.name.clone(), col.data_type.clone()));
 
             if col.is_primary_key {
                 primary_keys
                     .entry(table_name.clone())
-                    .or_insert_with(Vec::new)
+                    .or_default()
                     .push((col.name.clone(), col.data_type.clone()));
             }
         }
@@ -442,10 +821,12 @@ async fn infer_relationships(
     let mut inferred = Vec::new();
 
     // Pattern matching for common foreign key naming conventions
-    for schema in &schemas {
+    for schema in schemas {
+        // Columns of a composite key, as in junction tables, can still reference other tables
+        let pk_count = schema.columns.iter().filter(|col| col.is_primary_key).count();
         for col in &schema.columns {
-            // Skip if it's a primary key (don't want self-references)
-            if col.is_primary_key {
+            // Skip if it's the table's own primary key (don't want self-references)
+            if col.is_primary_key && pk_count == 1 {
                 continue;
             }
 
@@ -474,8 +855,13 @@ async fn infer_relationships(
                                     foreign_table: target_table.clone(),
                                     foreign_column: pk_col.clone(),
                                     constraint_name: None,
-                                    relationship_type: "inferred".to_string(),
+                                    source: RelationshipSource::Inferred,
                                     confidence: Some("high".to_string()),
+                                    junction_table: None,
+                                    is_self_referential: false,
+                                    relationship_cardinality: None,
+                                    schema: schema.schema.clone(),
+                                    foreign_schema: table_schemas.get(target_table.as_str()).cloned().flatten(),
                                 });
                                 break;
                             }
@@ -504,8 +890,13 @@ async fn infer_relationships(
                                     foreign_table: target_table.clone(),
                                     foreign_column: pk_col.clone(),
                                     constraint_name: None,
-                                    relationship_type: "inferred".to_string(),
+                                    source: RelationshipSource::Inferred,
                                     confidence: Some("medium".to_string()),
+                                    junction_table: None,
+                                    is_self_referential: false,
+                                    relationship_cardinality: None,
+                                    schema: schema.schema.clone(),
+                                    foreign_schema: table_schemas.get(target_table.as_str()).cloned().flatten(),
                                 });
                                 break;
                             }
@@ -514,6 +905,32 @@ async fn infer_relationships(
                 }
             }
 
+            // Pattern 4: parent_id referencing the table's own key, as in adjacency lists
+            if col_name == "parent_id" {
+                let own_key = primary_keys
+                    .get(&schema.table_name)
+                    .filter(|pk_columns| pk_columns.len() == 1)
+                    .and_then(|pk_columns| pk_columns.first());
+                if let Some((pk_col, pk_type)) = own_key {
+                    if are_types_compatible(col_type, pk_type) {
+                        inferred.push(Relationship {
+                            table_name: schema.table_name.clone(),
+                            column_name: col.name.clone(),
+                            foreign_table: schema.table_name.clone(),
+                            foreign_column: pk_col.clone(),
+                            constraint_name: None,
+                            source: RelationshipSource::Inferred,
+                            confidence: Some("medium".to_string()),
+                            junction_table: None,
+                            is_self_referential: false,
+                            relationship_cardinality: None,
+                            schema: schema.schema.clone(),
+                            foreign_schema: schema.schema.clone(),
+                        });
+                    }
+                }
+            }
+
             // Pattern 3: exact table name match (e.g., column "user" referencing table "users.id")
             for (target_table, pk_columns) in &primary_keys {
                 let table_singular = target_table.trim_end_matches('s');
@@ -527,8 +944,13 @@ async fn infer_relationships(
                                 foreign_table: target_table.clone(),
                                 foreign_column: pk_col.clone(),
                                 constraint_name: None,
-                                relationship_type: "inferred".to_string(),
+                                source: RelationshipSource::Inferred,
                                 confidence: Some("low".to_string()),
+                                junction_table: None,
+                                is_self_referential: false,
+                                relationship_cardinality: None,
+                                schema: schema.schema.clone(),
+                                foreign_schema: table_schemas.get(target_table.as_str()).cloned().flatten(),
                             });
                             break;
                         }
@@ -538,264 +960,4081 @@ async fn infer_relationships(
         }
     }
 
-    Ok(inferred)
+    mark_self_referential(&mut inferred);
+    inferred
+}
+
+/// Flag relationships from a table to itself, such as `categories.parent_id`
+fn mark_self_referential(relationships: &mut [Relationship]) {
+    for rel in relationships
+        .iter_mut()
+        .filter(|rel| rel.table_name == rel.foreign_table && rel.schema == rel.foreign_schema)
+    {
+        rel.is_self_referential = true;
+        rel.relationship_cardinality = Some("self".to_string());
+    }
 }
 
-/// Get schemas for all tables in the database
-async fn get_all_table_schemas(
+/// Get schemas for all tables in the database, in table name order
+pub(crate) async fn get_all_table_schemas(
     creds: &super::types::DatabaseCredentials,
     pool: &sqlx::AnyPool,
 ) -> Result<Vec<TableSchema>, DatabaseError> {
-    // Get all table names first
-    let tables_query = match creds.db_type {
-        DatabaseType::Postgres => {
-            "SELECT table_name::text FROM information_schema.tables WHERE table_schema NOT IN ('pg_catalog', 'information_schema')".to_string()
-        }
-        DatabaseType::MySQL => {
-            format!("SELECT table_name FROM information_schema.tables WHERE table_schema = '{}'", creds.database)
-        }
-        DatabaseType::SQLite => {
-            "SELECT name as table_name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'".to_string()
-        }
-    };
+    let table_names = get_all_table_names(creds, pool).await?;
 
-    let table_rows = sqlx::query(&tables_query)
-        .fetch_all(pool)
+    // The futures are built before streaming them, a closure in the stream would
+    // keep its lifetime generic and the command future would not be Send
+    let fetches: Vec<_> = table_names
+        .iter()
+        .map(|(schema, table_name)| {
+            get_single_table_schema(creds, pool, table_name, schema.as_deref())
+        })
+        .collect();
+    stream::iter(fetches)
+        .buffered(SCHEMA_STREAM_CONCURRENCY)
+        .collect::<Vec<_>>()
         .await
-        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        .into_iter()
+        .collect()
+}
 
-    let mut table_names: Vec<String> = Vec::new();
-    for row in table_rows {
-        // MySQL returns uppercase column names, try both cases
-        let table_name: String = row
-            .try_get("table_name")
-            .or_else(|_| row.try_get("TABLE_NAME"))
-            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
-        table_names.push(table_name);
-    }
+/// Find every table with a column of the given name (case-insensitive)
+pub async fn find_column_usages(
+    database_id: &str,
+    column_name: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<ColumnSearchResult>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    let pool = pools.get_or_create(database_id, &creds).await?;
 
-    // Now get schema for each table
-    let mut schemas = Vec::new();
+    let schemas = get_all_table_schemas(&creds, &pool).await?;
+    Ok(search_columns(&schemas, column_name))
+}
 
-    for table_name in table_names {
-        let schema = match creds.db_type {
-            DatabaseType::SQLite => {
-                // Use PRAGMA for SQLite
-                let query = format!("PRAGMA table_info('{}')", table_name);
-                let rows = sqlx::query(&query)
-                    .fetch_all(pool)
-                    .await
-                    .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
-
-                let mut columns = Vec::new();
-                for row in rows {
-                    columns.push(ColumnInfo {
-                        name: row
-                            .try_get("name")
-                            .map_err(|e| DatabaseError::QueryError(e.to_string()))?,
-                        data_type: row
-                            .try_get("type")
-                            .map_err(|e| DatabaseError::QueryError(e.to_string()))?,
-                        is_nullable: row.try_get::<i32, _>("notnull").unwrap_or(0) == 0,
-                        is_primary_key: row.try_get::<i32, _>("pk").unwrap_or(0) > 0,
-                        default_value: row.try_get("dflt_value").ok(),
-                    });
-                }
+fn search_columns(schemas: &[TableSchema], column_name: &str) -> Vec<ColumnSearchResult> {
+    schemas
+        .iter()
+        .flat_map(|table| {
+            table
+                .columns
+                .iter()
+                .filter(|col| col.name.eq_ignore_ascii_case(column_name))
+                .map(|col| ColumnSearchResult {
+                    table_name: table.table_name.clone(),
+                    schema: table.schema.clone(),
+                    column: col.clone(),
+                })
+        })
+        .collect()
+}
 
-                TableSchema {
-                    table_name: table_name.clone(),
-                    schema: None,
-                    columns,
-                }
-            }
-            DatabaseType::Postgres => {
-                // Use information_schema for Postgres
-                let query = format!(
-                    "SELECT
-                        c.column_name::text,
-                        c.data_type::text,
-                        c.is_nullable::text,
-                        c.column_default::text,
-                        CASE WHEN pk.column_name IS NOT NULL THEN true ELSE false END as is_primary_key
-                    FROM information_schema.columns c
-                    LEFT JOIN (
-                        SELECT ku.column_name::text
-                        FROM information_schema.table_constraints tc
-                        JOIN information_schema.key_column_usage ku
-                            ON tc.constraint_name = ku.constraint_name
-                            AND tc.table_schema = ku.table_schema
-                        WHERE tc.constraint_type = 'PRIMARY KEY'
-                            AND tc.table_name = '{}'
-                    ) pk ON c.column_name = pk.column_name
-                    WHERE c.table_name = '{}'
-                    ORDER BY c.ordinal_position",
-                    table_name, table_name
-                );
+/// Search table and column names for `query` (case-insensitive)
+///
+/// Exact matches come first, then names starting with the text, then names containing
+/// it; ties keep table name order. Searching columns loads the schema of every table,
+/// so it is skipped unless `search_columns` is set.
+pub async fn search_schema(
+    database_id: &str,
+    query: &str,
+    search_tables: bool,
+    search_columns: bool,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<SchemaSearchResult, DatabaseError> {
+    let creds = store.get(database_id)?;
+    let query = query.trim();
 
-                let rows = sqlx::query(&query)
-                    .fetch_all(pool)
-                    .await
-                    .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
-
-                let mut columns = Vec::new();
-                for row in rows {
-                    let is_nullable: String = row
-                        .try_get("is_nullable")
-                        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
-
-                    columns.push(ColumnInfo {
-                        name: row
-                            .try_get("column_name")
-                            .map_err(|e| DatabaseError::QueryError(e.to_string()))?,
-                        data_type: row
-                            .try_get("data_type")
-                            .map_err(|e| DatabaseError::QueryError(e.to_string()))?,
-                        is_nullable: is_nullable.to_uppercase() == "YES",
-                        is_primary_key: row.try_get("is_primary_key").unwrap_or(false),
-                        default_value: row.try_get("column_default").ok(),
-                    });
-                }
+    let mut result = SchemaSearchResult::default();
+    if query.is_empty() {
+        return Ok(result);
+    }
 
-                TableSchema {
+    if search_tables {
+        let tables = get_tables(database_id, store, pools).await?;
+        result.matching_tables = rank_by_match(tables, query, |table| table.name.as_str());
+    }
+    if search_columns {
+        let pool = pools.get_or_create(database_id, &creds).await?;
+        let schemas = get_all_table_schemas(&creds, &pool).await?;
+        let columns: Vec<ColumnSearchHit> = schemas
+            .into_iter()
+            .flat_map(|table| {
+                let TableSchema { table_name, schema, columns } = table;
+                columns.into_iter().map(move |column| ColumnSearchHit {
                     table_name: table_name.clone(),
-                    schema: Some("public".to_string()),
-                    columns,
-                }
-            }
-            DatabaseType::MySQL => {
-                // Use information_schema for MySQL
-                let query = format!(
-                    "SELECT
-                        CAST(column_name AS CHAR) AS column_name,
-                        CAST(data_type AS CHAR) AS data_type,
-                        CAST(is_nullable AS CHAR) AS is_nullable,
-                        column_default,
-                        CASE WHEN column_key = 'PRI' THEN 1 ELSE 0 END as is_primary_key
-                    FROM information_schema.columns
-                    WHERE table_name = '{}' AND table_schema = '{}'
-                    ORDER BY ordinal_position",
-                    table_name,
-                    creds.database
-                );
+                    schema: schema.clone(),
+                    column,
+                })
+            })
+            .collect();
+        result.matching_columns = rank_by_match(columns, query, |hit| hit.column.name.as_str());
+    }
 
-                let rows = sqlx::query(&query)
-                    .fetch_all(pool)
-                    .await
-                    .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
-
-                let mut columns = Vec::new();
-                for row in rows {
-                    // MySQL returns uppercase column names, try both cases
-                    let is_nullable: String = row
-                        .try_get("is_nullable")
-                        .or_else(|_| row.try_get("IS_NULLABLE"))
-                        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
-                    let is_pk: i32 = row.try_get("is_primary_key")
-                        .or_else(|_| row.try_get("IS_PRIMARY_KEY"))
-                        .unwrap_or(0);
-
-                    columns.push(ColumnInfo {
-                        name: row
-                            .try_get("column_name")
-                            .or_else(|_| row.try_get("COLUMN_NAME"))
-                            .map_err(|e| DatabaseError::QueryError(e.to_string()))?,
-                        data_type: row
-                            .try_get("data_type")
-                            .or_else(|_| row.try_get("DATA_TYPE"))
-                            .map_err(|e| DatabaseError::QueryError(e.to_string()))?,
-                        is_nullable: is_nullable.to_uppercase() == "YES",
-                        is_primary_key: is_pk > 0,
-                        default_value: row.try_get("column_default")
-                            .or_else(|_| row.try_get("COLUMN_DEFAULT"))
-                            .ok(),
-                    });
-                }
+    Ok(result)
+}
 
-                TableSchema {
-                    table_name: table_name.clone(),
-                    schema: None,
-                    columns,
-                }
-            }
-        };
+/// Keep the items whose name contains `query` (case-insensitive), exact matches
+/// first, then prefix matches, then the remaining substring matches
+fn rank_by_match<T>(items: Vec<T>, query: &str, name: impl Fn(&T) -> &str) -> Vec<T> {
+    let query = query.to_lowercase();
+    let mut ranked: Vec<(u8, T)> = items
+        .into_iter()
+        .filter_map(|item| {
+            let lower = name(&item).to_lowercase();
+            let rank = if lower == query {
+                0
+            } else if lower.starts_with(&query) {
+                1
+            } else if lower.contains(&query) {
+                2
+            } else {
+                return None;
+            };
+            Some((rank, item))
+        })
+        .collect();
+
+    // sort_by_key is stable, so items of the same rank keep their order
+    ranked.sort_by_key(|(rank, _)| *rank);
+    ranked.into_iter().map(|(_, item)| item).collect()
+}
 
-        schemas.push(schema);
+/// Compare the schemas of two databases of the same type
+pub async fn diff_schemas(
+    source_id: &str,
+    target_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<SchemaDiff, DatabaseError> {
+    let source_creds = store.get(source_id)?;
+    let target_creds = store.get(target_id)?;
+    if source_creds.db_type != target_creds.db_type {
+        return Err(DatabaseError::Other(
+            "Schemas can only be compared between databases of the same type".to_string(),
+        ));
     }
 
-    Ok(schemas)
-}
+    let source_pool = pools.get_or_create(source_id, &source_creds).await?;
+    let target_pool = pools.get_or_create(target_id, &target_creds).await?;
 
-/// Check if two data types are compatible for foreign key relationships
-fn are_types_compatible(type1: &str, type2: &str) -> bool {
-    let t1 = normalize_type(type1);
-    let t2 = normalize_type(type2);
+    let (source, target, source_indexes, target_indexes) = tokio::join!(
+        get_all_table_schemas(&source_creds, &source_pool),
+        get_all_table_schemas(&target_creds, &target_pool),
+        get_indexes(source_id, store, pools),
+        get_indexes(target_id, store, pools),
+    );
 
-    // Exact match
-    if t1 == t2 {
-        return true;
-    }
+    let mut diff = compute_schema_diff(&source?, &target?);
+    let (source_indexes, target_indexes) = (source_indexes?, target_indexes?);
+    let same_index = |a: &IndexInfo, b: &IndexInfo| a.table_name == b.table_name && a.name == b.name;
 
-    // Integer types are compatible with each other
-    let int_types = ["int", "integer", "bigint", "smallint", "tinyint", "serial", "bigserial"];
-    if int_types.contains(&t1.as_str()) && int_types.contains(&t2.as_str()) {
-        return true;
-    }
+    diff.added_indexes = target_indexes
+        .iter()
+        .filter(|index| !source_indexes.iter().any(|other| same_index(index, other)))
+        .cloned()
+        .collect();
+    diff.removed_indexes = source_indexes
+        .iter()
+        .filter(|index| !target_indexes.iter().any(|other| same_index(index, other)))
+        .cloned()
+        .collect();
 
-    // String types are compatible
-    let string_types = ["varchar", "char", "text", "string"];
-    if string_types.contains(&t1.as_str()) && string_types.contains(&t2.as_str()) {
-        return true;
-    }
+    Ok(diff)
+}
 
-    // UUID types
-    if t1 == "uuid" && t2 == "uuid" {
-        return true;
+/// Match tables and columns by name and collect what differs between them
+///
+/// Index differences are filled in by `diff_schemas`, which fetches the indexes.
+pub fn compute_schema_diff(source: &[TableSchema], target: &[TableSchema]) -> SchemaDiff {
+    let mut diff = SchemaDiff {
+        added_tables: target
+            .iter()
+            .filter(|t| find_table(source, &t.table_name).is_none())
+            .cloned()
+            .collect(),
+        removed_tables: source
+            .iter()
+            .filter(|t| find_table(target, &t.table_name).is_none())
+            .cloned()
+            .collect(),
+        modified_tables: Vec::new(),
+        ..Default::default()
+    };
+
+    for source_table in source {
+        let Some(target_table) = find_table(target, &source_table.table_name) else {
+            continue;
+        };
+
+        let table_diff = TableDiff {
+            table_name: source_table.table_name.clone(),
+            added_columns: target_table
+                .columns
+                .iter()
+                .filter(|c| find_column(source_table, &c.name).is_none())
+                .cloned()
+                .collect(),
+            removed_columns: source_table
+                .columns
+                .iter()
+                .filter(|c| find_column(target_table, &c.name).is_none())
+                .cloned()
+                .collect(),
+            changed_columns: source_table
+                .columns
+                .iter()
+                .filter_map(|source_column| {
+                    let target_column = find_column(target_table, &source_column.name)?;
+                    (target_column != source_column).then(|| ColumnChange {
+                        column_name: source_column.name.clone(),
+                        source: source_column.clone(),
+                        target: target_column.clone(),
+                    })
+                })
+                .collect(),
+        };
+
+        if !table_diff.added_columns.is_empty()
+            || !table_diff.removed_columns.is_empty()
+            || !table_diff.changed_columns.is_empty()
+        {
+            diff.modified_tables.push(table_diff);
+        }
     }
 
-    false
+    diff
 }
 
-/// Normalize type names for comparison
-fn normalize_type(data_type: &str) -> String {
-    let lower = data_type.to_lowercase();
+fn find_table<'a>(tables: &'a [TableSchema], name: &str) -> Option<&'a TableSchema> {
+    tables.iter().find(|t| t.table_name == name)
+}
 
-    // Remove size specifications like VARCHAR(255) -> varchar
-    if let Some(idx) = lower.find('(') {
-        lower[..idx].to_string()
-    } else {
-        lower
-    }
+fn find_column<'a>(table: &'a TableSchema, name: &str) -> Option<&'a ColumnInfo> {
+    table.columns.iter().find(|c| c.name == name)
 }
 
-// Tauri commands for metadata
-#[tauri::command]
-pub async fn get_database_tables(
-    database_id: String,
-    store: State<'_, CredentialStore>,
-) -> Result<Vec<TableInfo>, String> {
-    get_tables(&database_id, &store)
-        .await
-        .map_err(|e| e.to_string())
+/// Get the indexes of all tables in a database
+pub async fn get_indexes(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<IndexInfo>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let indexes = match creds.db_type {
+        DatabaseType::Postgres => {
+            // Index columns are listed in indkey order; expression parts have attnum 0
+            // and drop out of the join
+            let query = "SELECT
+                    pi.indexname::text AS index_name,
+                    pi.tablename::text AS table_name,
+                    array_to_string(ARRAY(
+                        SELECT a.attname
+                        FROM unnest(ix.indkey) WITH ORDINALITY AS k(attnum, ord)
+                        JOIN pg_attribute a ON a.attrelid = ix.indrelid AND a.attnum = k.attnum
+                        ORDER BY k.ord
+                    ), ',')::text AS columns,
+                    ix.indisunique AS is_unique,
+                    ix.indisprimary AS is_primary,
+                    am.amname::text AS index_type
+                FROM pg_indexes pi
+                JOIN pg_namespace n ON n.nspname = pi.schemaname
+                JOIN pg_class i ON i.relname = pi.indexname AND i.relnamespace = n.oid
+                JOIN pg_index ix ON ix.indexrelid = i.oid
+                JOIN pg_am am ON am.oid = i.relam
+                WHERE pi.schemaname NOT IN ('pg_catalog', 'information_schema')
+                ORDER BY pi.tablename, pi.indexname";
+
+            let rows = sqlx::query(query)
+                .fetch_all(&*pool)
+                .await
+                .map_err(DatabaseError::from)?;
+
+            let mut indexes = Vec::new();
+            for row in rows {
+                let columns: String = row.try_get("columns").unwrap_or_default();
+                indexes.push(IndexInfo {
+                    name: row
+                        .try_get("index_name")
+                        .map_err(DatabaseError::from)?,
+                    table_name: row
+                        .try_get("table_name")
+                        .map_err(DatabaseError::from)?,
+                    columns: split_index_columns(&columns),
+                    is_unique: row.try_get("is_unique").unwrap_or(false),
+                    is_primary: row.try_get("is_primary").unwrap_or(false),
+                    index_type: row.try_get("index_type").unwrap_or_default(),
+                });
+            }
+            indexes
+        }
+        DatabaseType::MySQL => {
+            let query = format!(
+                "SELECT
+                    CAST(INDEX_NAME AS CHAR) AS index_name,
+                    CAST(TABLE_NAME AS CHAR) AS table_name,
+                    CAST(GROUP_CONCAT(COLUMN_NAME ORDER BY SEQ_IN_INDEX SEPARATOR ',') AS CHAR) AS columns,
+                    CAST(MIN(NON_UNIQUE) AS SIGNED) AS non_unique,
+                    CAST(INDEX_TYPE AS CHAR) AS index_type
+                FROM INFORMATION_SCHEMA.STATISTICS
+                WHERE TABLE_SCHEMA = '{}'
+                GROUP BY TABLE_NAME, INDEX_NAME, INDEX_TYPE
+                ORDER BY TABLE_NAME, INDEX_NAME",
+                creds.database.replace('\'', "''")
+            );
+
+            let rows = sqlx::query(&query)
+                .fetch_all(&*pool)
+                .await
+                .map_err(DatabaseError::from)?;
+
+            let mut indexes = Vec::new();
+            for row in rows {
+                let name: String = row
+                    .try_get("index_name")
+                    .or_else(|_| row.try_get("INDEX_NAME"))
+                    .map_err(DatabaseError::from)?;
+                let columns: String = row
+                    .try_get("columns")
+                    .or_else(|_| row.try_get("COLUMNS"))
+                    .unwrap_or_default();
+                let non_unique = get_mysql_int(&row, "non_unique")
+                    .or_else(|| get_mysql_int(&row, "NON_UNIQUE"))
+                    .unwrap_or(1);
+
+                indexes.push(IndexInfo {
+                    is_primary: name == "PRIMARY",
+                    name,
+                    table_name: row
+                        .try_get("table_name")
+                        .or_else(|_| row.try_get("TABLE_NAME"))
+                        .map_err(DatabaseError::from)?,
+                    columns: split_index_columns(&columns),
+                    is_unique: non_unique == 0,
+                    index_type: row
+                        .try_get("index_type")
+                        .or_else(|_| row.try_get("INDEX_TYPE"))
+                        .unwrap_or_default(),
+                });
+            }
+            indexes
+        }
+        DatabaseType::SQLite => {
+            let mut indexes = Vec::new();
+            for (_, table_name) in get_all_table_names(&creds, &pool).await? {
+                let list_query = format!(
+                    "PRAGMA index_list({})",
+                    quote_identifier(&table_name, &DatabaseType::SQLite)
+                );
+                let index_rows = sqlx::query(&list_query)
+                    .fetch_all(&*pool)
+                    .await
+                    .map_err(DatabaseError::from)?;
+
+                for index_row in index_rows {
+                    let name: String = index_row
+                        .try_get("name")
+                        .map_err(DatabaseError::from)?;
+                    let unique: i32 = index_row.try_get("unique").unwrap_or(0);
+                    // origin is "pk" for primary keys, "u" for UNIQUE constraints and
+                    // "c" for CREATE INDEX
+                    let origin: String = index_row.try_get("origin").unwrap_or_default();
+
+                    let info_query = format!(
+                        "PRAGMA index_info({})",
+                        quote_identifier(&name, &DatabaseType::SQLite)
+                    );
+                    let column_rows = sqlx::query(&info_query)
+                        .fetch_all(&*pool)
+                        .await
+                        .map_err(DatabaseError::from)?;
+                    let columns = column_rows
+                        .iter()
+                        .filter_map(|row| row.try_get::<Option<String>, _>("name").ok().flatten())
+                        .collect();
+
+                    indexes.push(IndexInfo {
+                        name,
+                        table_name: table_name.clone(),
+                        columns,
+                        is_unique: unique != 0,
+                        is_primary: origin == "pk",
+                        index_type: "btree".to_string(),
+                    });
+                }
+            }
+            indexes
+        }
+        DatabaseType::ClickHouse => return Err(DatabaseError::UnsupportedType),
+    };
+    Ok(indexes)
 }
 
-#[tauri::command]
-pub async fn get_database_table_schema(
-    database_id: String,
-    table_names: String,
-    schema: Option<String>,
-    store: State<'_, CredentialStore>,
-) -> Result<Vec<TableSchema>, String> {
-    get_table_schema(&database_id, &table_names, schema.as_deref(), &store)
-        .await
-        .map_err(|e| e.to_string())
+fn split_index_columns(columns: &str) -> Vec<String> {
+    columns
+        .split(',')
+        .filter(|col| !col.is_empty())
+        .map(|col| col.to_string())
+        .collect()
+}
+
+/// Get the schema and name of all tables in the database
+///
+/// The schema is only set on Postgres, where tables of every schema but the system
+/// ones are listed.
+async fn get_all_table_names(
+    creds: &super::types::DatabaseCredentials,
+    pool: &sqlx::AnyPool,
+) -> Result<Vec<(Option<String>, String)>, DatabaseError> {
+    let tables_query = match creds.db_type {
+        DatabaseType::Postgres => {
+            "SELECT table_schema::text, table_name::text FROM information_schema.tables WHERE table_schema NOT IN ('pg_catalog', 'information_schema')".to_string()
+        }
+        DatabaseType::MySQL => {
+            format!(
+                "SELECT table_name FROM information_schema.tables WHERE table_schema = '{}'",
+                creds.database.replace('\'', "''")
+            )
+        }
+        DatabaseType::SQLite => {
+            "SELECT name as table_name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'".to_string()
+        }
+        DatabaseType::ClickHouse => return Err(DatabaseError::UnsupportedType),
+    };
+
+    let table_rows = sqlx::query(&tables_query)
+        .fetch_all(pool)
+        .await
+        .map_err(DatabaseError::from)?;
+
+    let mut table_names = Vec::new();
+    for row in table_rows {
+        // MySQL returns uppercase column names, try both cases
+        let table_name: String = row
+            .try_get("table_name")
+            .or_else(|_| row.try_get("TABLE_NAME"))
+            .map_err(DatabaseError::from)?;
+        let schema = match creds.db_type {
+            DatabaseType::Postgres => Some(
+                row.try_get("table_schema")
+                    .map_err(DatabaseError::from)?,
+            ),
+            _ => None,
+        };
+        table_names.push((schema, table_name));
+    }
+
+    Ok(table_names)
+}
+
+/// Get the schema of a single table
+///
+/// `schema` selects the Postgres schema of the table and defaults to `public`; the
+/// other databases ignore it.
+async fn get_single_table_schema(
+    creds: &super::types::DatabaseCredentials,
+    pool: &sqlx::AnyPool,
+    table_name: &str,
+    schema: Option<&str>,
+) -> Result<TableSchema, DatabaseError> {
+    let schema_name = schema.unwrap_or("public");
+    let schema = match creds.db_type {
+        DatabaseType::SQLite => {
+            // Use PRAGMA for SQLite
+            let query = format!(
+                "PRAGMA table_info({})",
+                quote_identifier(table_name, &DatabaseType::SQLite)
+            );
+            let rows = sqlx::query(&query)
+                .fetch_all(pool)
+                .await
+                .map_err(DatabaseError::from)?;
+            let unique_columns = get_sqlite_unique_columns(pool, table_name).await?;
+
+            let mut columns = Vec::new();
+            for row in rows {
+                let name: String = row
+                    .try_get("name")
+                    .map_err(DatabaseError::from)?;
+                let data_type: String = row
+                    .try_get("type")
+                    .map_err(DatabaseError::from)?;
+                let unique_constraint_name = unique_columns.get(&name).cloned();
+
+                columns.push(ColumnInfo {
+                    name,
+                    character_max_length: parse_type_length(&data_type),
+                    data_type,
+                    is_nullable: row.try_get::<i32, _>("notnull").unwrap_or(0) == 0,
+                    is_primary_key: row.try_get::<i32, _>("pk").unwrap_or(0) > 0,
+                    default_value: row.try_get("dflt_value").ok(),
+                    is_unique: unique_constraint_name.is_some(),
+                    unique_constraint_name,
+                    is_inherited: false,
+                });
+            }
+
+            TableSchema {
+                table_name: table_name.to_string(),
+                schema: None,
+                columns,
+            }
+        }
+        DatabaseType::Postgres => {
+            // Use information_schema for Postgres
+            let escaped = table_name.replace('\'', "''");
+            let escaped_schema = schema_name.replace('\'', "''");
+            let query = format!(
+                "SELECT
+                    c.column_name::text,
+                    c.data_type::text,
+                    c.is_nullable::text,
+                    c.column_default::text,
+                    c.character_maximum_length::bigint AS character_max_length,
+                    CASE WHEN pk.column_name IS NOT NULL THEN true ELSE false END as is_primary_key,
+                    uq.constraint_name AS unique_constraint_name,
+                    {} AS is_inherited
+                FROM information_schema.columns c
+                LEFT JOIN (
+                    SELECT ku.column_name::text
+                    FROM information_schema.table_constraints tc
+                    JOIN information_schema.key_column_usage ku
+                        ON tc.constraint_name = ku.constraint_name
+                        AND tc.table_schema = ku.table_schema
+                    WHERE tc.constraint_type = 'PRIMARY KEY'
+                        AND tc.table_name = '{}'
+                        AND tc.table_schema = '{}'
+                ) pk ON c.column_name = pk.column_name
+                LEFT JOIN ({}) uq ON c.column_name = uq.column_name
+                WHERE c.table_name = '{}' AND c.table_schema = '{}'
+                ORDER BY c.ordinal_position",
+                POSTGRES_IS_INHERITED,
+                escaped,
+                escaped_schema,
+                postgres_unique_columns_query(&format!("('{}')", escaped), &escaped_schema),
+                escaped,
+                escaped_schema
+            );
+
+            let rows = sqlx::query(&query)
+                .fetch_all(pool)
+                .await
+                .map_err(DatabaseError::from)?;
+
+            let mut columns = Vec::new();
+            for row in rows {
+                let is_nullable: String = row
+                    .try_get("is_nullable")
+                    .map_err(DatabaseError::from)?;
+                let unique_constraint_name: Option<String> = row
+                    .try_get::<Option<String>, _>("unique_constraint_name")
+                    .ok()
+                    .flatten();
+
+                columns.push(ColumnInfo {
+                    name: row
+                        .try_get("column_name")
+                        .map_err(DatabaseError::from)?,
+                    data_type: row
+                        .try_get("data_type")
+                        .map_err(DatabaseError::from)?,
+                    is_nullable: is_nullable.to_uppercase() == "YES",
+                    is_primary_key: row.try_get("is_primary_key").unwrap_or(false),
+                    default_value: row.try_get("column_default").ok(),
+                    character_max_length: row
+                        .try_get::<Option<i64>, _>("character_max_length")
+                        .ok()
+                        .flatten(),
+                    is_unique: unique_constraint_name.is_some(),
+                    unique_constraint_name,
+                    is_inherited: row.try_get("is_inherited").unwrap_or(false),
+                });
+            }
+
+            TableSchema {
+                table_name: table_name.to_string(),
+                schema: Some(schema_name.to_string()),
+                columns,
+            }
+        }
+        DatabaseType::MySQL => {
+            // Use information_schema for MySQL
+            let query = format!(
+                "SELECT
+                    CAST(column_name AS CHAR) AS column_name,
+                    CAST(data_type AS CHAR) AS data_type,
+                    CAST(is_nullable AS CHAR) AS is_nullable,
+                    column_default,
+                    CAST(character_maximum_length AS SIGNED) AS character_max_length,
+                    CASE WHEN column_key = 'PRI' THEN 1 ELSE 0 END as is_primary_key,
+                    CASE WHEN column_key = 'UNI' THEN 1 ELSE 0 END as is_unique,
+                    {} as unique_constraint_name
+                FROM information_schema.columns c
+                WHERE table_name = '{}' AND table_schema = '{}'
+                ORDER BY ordinal_position",
+                MYSQL_UNIQUE_INDEX_NAME,
+                table_name.replace('\'', "''"),
+                creds.database.replace('\'', "''")
+            );
+
+            let rows = sqlx::query(&query)
+                .fetch_all(pool)
+                .await
+                .map_err(DatabaseError::from)?;
+
+            let mut columns = Vec::new();
+            for row in rows {
+                // MySQL returns uppercase column names, try both cases
+                let is_nullable: String = row
+                    .try_get("is_nullable")
+                    .or_else(|_| row.try_get("IS_NULLABLE"))
+                    .map_err(DatabaseError::from)?;
+                let is_pk: i32 = row.try_get("is_primary_key")
+                    .or_else(|_| row.try_get("IS_PRIMARY_KEY"))
+                    .unwrap_or(0);
+
+                columns.push(ColumnInfo {
+                    name: row
+                        .try_get("column_name")
+                        .or_else(|_| row.try_get("COLUMN_NAME"))
+                        .map_err(DatabaseError::from)?,
+                    data_type: row
+                        .try_get("data_type")
+                        .or_else(|_| row.try_get("DATA_TYPE"))
+                        .map_err(DatabaseError::from)?,
+                    is_nullable: is_nullable.to_uppercase() == "YES",
+                    is_primary_key: is_pk > 0,
+                    default_value: row.try_get("column_default")
+                        .or_else(|_| row.try_get("COLUMN_DEFAULT"))
+                        .ok(),
+                    character_max_length: row
+                        .try_get::<Option<i64>, _>("character_max_length")
+                        .or_else(|_| row.try_get::<Option<i64>, _>("CHARACTER_MAX_LENGTH"))
+                        .ok()
+                        .flatten(),
+                    is_unique: get_mysql_int(&row, "is_unique")
+                        .or_else(|| get_mysql_int(&row, "IS_UNIQUE"))
+                        .unwrap_or(0)
+                        > 0,
+                    unique_constraint_name: row
+                        .try_get::<Option<String>, _>("unique_constraint_name")
+                        .or_else(|_| row.try_get::<Option<String>, _>("UNIQUE_CONSTRAINT_NAME"))
+                        .ok()
+                        .flatten(),
+                    is_inherited: false,
+                });
+            }
+
+            TableSchema {
+                table_name: table_name.to_string(),
+                schema: None,
+                columns,
+            }
+        }
+        DatabaseType::ClickHouse => return Err(DatabaseError::UnsupportedType),
+    };
+
+    Ok(schema)
+}
+
+/// Find junction tables and link the tables they join with `many_to_many` entries
+///
+/// A table is a junction when its primary key is made of two columns that both
+/// reference other tables, or when it has exactly two referencing columns and at
+/// most one column besides them and its primary key (e.g. a `created_at`). Each
+/// junction yields one pair of entries, one in each direction, from the referenced
+/// column on one side to the referenced column on the other. Tables and their
+/// references are matched on schema and name.
+fn detect_junction_tables(
+    schemas: &[TableSchema],
+    relationships: &[Relationship],
+) -> Vec<Relationship> {
+    let mut many_to_many = Vec::new();
+
+    for schema in schemas {
+        // One outgoing reference per column, declared foreign keys first
+        let mut outgoing: Vec<&Relationship> = Vec::new();
+        for rel in relationships
+            .iter()
+            .filter(|r| r.source == RelationshipSource::Explicit)
+            .chain(relationships.iter().filter(|r| r.source != RelationshipSource::Explicit))
+        {
+            if rel.table_name == schema.table_name
+                && rel.schema == schema.schema
+                && !rel.is_self_referential
+                && rel.source != RelationshipSource::ManyToMany
+                && !outgoing.iter().any(|o| o.column_name == rel.column_name)
+            {
+                outgoing.push(rel);
+            }
+        }
+        let reference = |column: &str| outgoing.iter().find(|o| o.column_name == column).copied();
+
+        let primary_key: Vec<&str> = schema
+            .columns
+            .iter()
+            .filter(|col| col.is_primary_key)
+            .map(|col| col.name.as_str())
+            .collect();
+        let key_pair = match primary_key.as_slice() {
+            [a, b] => reference(a).zip(reference(b)),
+            _ => None,
+        };
+        let pair = key_pair.or_else(|| match outgoing.as_slice() {
+            [a, b] => {
+                let other_columns = schema
+                    .columns
+                    .iter()
+                    .filter(|col| !col.is_primary_key && reference(&col.name).is_none())
+                    .count();
+                (other_columns <= 1).then_some((*a, *b))
+            }
+            _ => None,
+        });
+        let Some((a, b)) = pair else {
+            continue;
+        };
+
+        let named_after_targets =
+            is_compound_name(&schema.table_name, &a.foreign_table, &b.foreign_table);
+        let confidence = if key_pair.is_some() || named_after_targets {
+            "high"
+        } else {
+            "medium"
+        };
+        for (from, to) in [(a, b), (b, a)] {
+            many_to_many.push(Relationship {
+                table_name: from.foreign_table.clone(),
+                column_name: from.foreign_column.clone(),
+                foreign_table: to.foreign_table.clone(),
+                foreign_column: to.foreign_column.clone(),
+                constraint_name: None,
+                source: RelationshipSource::ManyToMany,
+                confidence: Some(confidence.to_string()),
+                junction_table: Some(schema.table_name.clone()),
+                is_self_referential: false,
+                relationship_cardinality: None,
+                schema: from.foreign_schema.clone(),
+                foreign_schema: to.foreign_schema.clone(),
+            });
+        }
+    }
+
+    many_to_many
+}
+
+/// Whether `name` joins the names of tables `a` and `b`, singular or plural, in
+/// either order (`user_roles`, `users_roles` and `roleuser` all match `users`/`roles`)
+fn is_compound_name(name: &str, a: &str, b: &str) -> bool {
+    if a.eq_ignore_ascii_case(b) {
+        return false;
+    }
+    let name = name.to_lowercase().replace('_', "");
+    let forms = |table: &str| {
+        let table = table.to_lowercase().replace('_', "");
+        let mut forms = vec![table.clone()];
+        for suffix in ["es", "s"] {
+            if let Some(singular) = table.strip_suffix(suffix) {
+                forms.push(singular.to_string());
+            }
+        }
+        forms
+    };
+
+    let (forms_a, forms_b) = (forms(a), forms(b));
+    forms_a.iter().any(|fa| {
+        forms_b
+            .iter()
+            .any(|fb| name == format!("{}{}", fa, fb) || name == format!("{}{}", fb, fa))
+    })
+}
+
+/// Check if two data types are compatible for foreign key relationships
+fn are_types_compatible(type1: &str, type2: &str) -> bool {
+    let t1 = normalize_type(type1);
+    let t2 = normalize_type(type2);
+
+    // Exact match
+    if t1 == t2 {
+        return true;
+    }
+
+    // Integer types are compatible with each other
+    let int_types = ["int", "integer", "bigint", "smallint", "tinyint", "serial", "bigserial"];
+    if int_types.contains(&t1.as_str()) && int_types.contains(&t2.as_str()) {
+        return true;
+    }
+
+    // String types are compatible
+    let string_types = ["varchar", "char", "text", "string"];
+    if string_types.contains(&t1.as_str()) && string_types.contains(&t2.as_str()) {
+        return true;
+    }
+
+    // UUID types
+    if t1 == "uuid" && t2 == "uuid" {
+        return true;
+    }
+
+    false
+}
+
+/// Extract the declared length from a type like VARCHAR(255) (used for SQLite,
+/// which has no information_schema)
+fn parse_type_length(data_type: &str) -> Option<i64> {
+    let start = data_type.find('(')?;
+    let end = data_type[start..].find(')')? + start;
+    data_type[start + 1..end]
+        .split(',')
+        .next()
+        .and_then(|len| len.trim().parse().ok())
+}
+
+/// Normalize type names for comparison
+fn normalize_type(data_type: &str) -> String {
+    let lower = data_type.to_lowercase();
+
+    // Remove size specifications like VARCHAR(255) -> varchar
+    if let Some(idx) = lower.find('(') {
+        lower[..idx].to_string()
+    } else {
+        lower
+    }
+}
+
+/// Get progress of running VACUUM operations (Postgres only)
+///
+/// VACUUM FULL is reported by Postgres in `pg_stat_progress_cluster` rather than
+/// `pg_stat_progress_vacuum`, so both views are queried. There is no vacuumed block
+/// counter for VACUUM FULL, it is reported as 0.
+pub async fn get_vacuum_progress(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<VacuumProgress>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Ok(Vec::new());
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let query = "SELECT
+            p.relid::regclass::text AS table_name,
+            p.phase::text AS phase,
+            p.heap_blks_total::bigint AS heap_blks_total,
+            p.heap_blks_scanned::bigint AS heap_blks_scanned,
+            p.heap_blks_vacuumed::bigint AS heap_blks_vacuumed
+        FROM pg_stat_progress_vacuum p
+        UNION ALL
+        SELECT
+            c.relid::regclass::text AS table_name,
+            c.phase::text AS phase,
+            c.heap_blks_total::bigint AS heap_blks_total,
+            c.heap_blks_scanned::bigint AS heap_blks_scanned,
+            0::bigint AS heap_blks_vacuumed
+        FROM pg_stat_progress_cluster c
+        WHERE c.command = 'VACUUM FULL'";
+
+    let rows = sqlx::query(query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(DatabaseError::from)?;
+
+    let mut progress = Vec::new();
+    for row in rows {
+        let heap_blks_total: i64 = row.try_get("heap_blks_total").unwrap_or(0);
+        let heap_blks_scanned: i64 = row.try_get("heap_blks_scanned").unwrap_or(0);
+
+        progress.push(VacuumProgress {
+            table_name: row
+                .try_get("table_name")
+                .map_err(DatabaseError::from)?,
+            phase: row
+                .try_get("phase")
+                .map_err(DatabaseError::from)?,
+            heap_blks_total,
+            heap_blks_scanned,
+            heap_blks_vacuumed: row.try_get("heap_blks_vacuumed").unwrap_or(0),
+            progress_ratio: progress_ratio(heap_blks_scanned, heap_blks_total),
+        });
+    }
+    Ok(progress)
+}
+
+/// Get progress of running ANALYZE operations (Postgres only)
+pub async fn get_analyze_progress(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<AnalyzeProgress>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Ok(Vec::new());
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let query = "SELECT
+            relid::regclass::text AS table_name,
+            phase::text AS phase,
+            sample_blks_total::bigint AS sample_blks_total,
+            sample_blks_scanned::bigint AS sample_blks_scanned
+        FROM pg_stat_progress_analyze";
+
+    let rows = sqlx::query(query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(DatabaseError::from)?;
+
+    let mut progress = Vec::new();
+    for row in rows {
+        let sample_blks_total: i64 = row.try_get("sample_blks_total").unwrap_or(0);
+        let sample_blks_scanned: i64 = row.try_get("sample_blks_scanned").unwrap_or(0);
+
+        progress.push(AnalyzeProgress {
+            table_name: row
+                .try_get("table_name")
+                .map_err(DatabaseError::from)?,
+            phase: row
+                .try_get("phase")
+                .map_err(DatabaseError::from)?,
+            sample_blks_total,
+            sample_blks_scanned,
+            progress_ratio: progress_ratio(sample_blks_scanned, sample_blks_total),
+        });
+    }
+    Ok(progress)
+}
+
+/// Dead tuple ratio above which a table is flagged for VACUUM ANALYZE
+const DEFAULT_DEAD_TUPLE_THRESHOLD: f32 = 0.2;
+
+/// Get dead tuple statistics per table (Postgres only)
+///
+/// Tables with a dead tuple ratio of at least `threshold_ratio` are flagged for
+/// VACUUM ANALYZE; tables not autovacuumed for more than 7 days are flagged as a
+/// possible autovacuum misconfiguration.
+pub async fn get_dead_tuples(
+    database_id: &str,
+    threshold_ratio: Option<f32>,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<TableDeadTupleInfo>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Ok(Vec::new());
+    }
+
+    let threshold = threshold_ratio.unwrap_or(DEFAULT_DEAD_TUPLE_THRESHOLD);
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let query = "SELECT
+            relname::text AS table_name,
+            schemaname::text AS schema,
+            n_live_tup::bigint AS live_tuples,
+            n_dead_tup::bigint AS dead_tuples,
+            last_autovacuum::text AS last_autovacuum,
+            autovacuum_count::integer AS autovacuum_count,
+            COALESCE(last_autovacuum < now() - interval '7 days', false) AS autovacuum_stale
+        FROM pg_stat_user_tables
+        ORDER BY n_dead_tup DESC";
+
+    let rows = sqlx::query(query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(DatabaseError::from)?;
+
+    let mut tables = Vec::new();
+    for row in rows {
+        let live_tuples: i64 = row.try_get("live_tuples").unwrap_or(0);
+        let dead_tuples: i64 = row.try_get("dead_tuples").unwrap_or(0);
+        let autovacuum_stale: bool = row.try_get("autovacuum_stale").unwrap_or(false);
+
+        let total = live_tuples + dead_tuples;
+        let dead_ratio = if total > 0 {
+            dead_tuples as f32 / total as f32
+        } else {
+            0.0
+        };
+
+        let recommendation = if dead_ratio >= threshold {
+            "VACUUM ANALYZE recommended"
+        } else if autovacuum_stale {
+            "Autovacuum may be disabled or misconfigured"
+        } else {
+            "No action needed"
+        };
+
+        tables.push(TableDeadTupleInfo {
+            table_name: row
+                .try_get("table_name")
+                .map_err(DatabaseError::from)?,
+            schema: row
+                .try_get("schema")
+                .map_err(DatabaseError::from)?,
+            live_tuples,
+            dead_tuples,
+            dead_ratio,
+            last_autovacuum: row.try_get::<Option<String>, _>("last_autovacuum").ok().flatten(),
+            autovacuum_count: row.try_get("autovacuum_count").unwrap_or(0),
+            recommendation: recommendation.to_string(),
+        });
+    }
+    Ok(tables)
+}
+
+/// Storage used by a database, broken down per table
+///
+/// Postgres sizes include TOAST data in `total_bytes`, so it can exceed
+/// `data_bytes + index_bytes`. MySQL sizes come from `information_schema.TABLES` and
+/// are estimates for InnoDB. SQLite reports the file size from its page count and
+/// has no per-table breakdown.
+pub async fn collect_database_size(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<DatabaseSizeInfo, DatabaseError> {
+    let creds = store.get(database_id)?;
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    match creds.db_type {
+        DatabaseType::Postgres => {
+            let total = sqlx::query(
+                "SELECT current_database()::text AS database_name,
+                    pg_database_size(current_database())::bigint AS total_size_bytes",
+            )
+            .fetch_one(&*pool)
+            .await
+            .map_err(DatabaseError::from)?;
+
+            let query = "SELECT
+                    c.relname::text AS table_name,
+                    pg_relation_size(c.oid)::bigint AS data_bytes,
+                    pg_indexes_size(c.oid)::bigint AS index_bytes,
+                    pg_total_relation_size(c.oid)::bigint AS total_bytes
+                FROM pg_class c
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                WHERE c.relkind IN ('r', 'm', 'p')
+                    AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+                    AND n.nspname NOT LIKE 'pg_toast%'
+                ORDER BY total_bytes DESC";
+            let rows = sqlx::query(query)
+                .fetch_all(&*pool)
+                .await
+                .map_err(|e| DatabaseError::query_failed(e, query))?;
+
+            let mut tables = Vec::new();
+            for row in rows {
+                tables.push(TableSizeInfo {
+                    table_name: row
+                        .try_get("table_name")
+                        .map_err(DatabaseError::from)?,
+                    data_bytes: row.try_get("data_bytes").unwrap_or(0),
+                    index_bytes: row.try_get("index_bytes").unwrap_or(0),
+                    total_bytes: row.try_get("total_bytes").unwrap_or(0),
+                });
+            }
+
+            Ok(DatabaseSizeInfo {
+                database_name: total
+                    .try_get("database_name")
+                    .unwrap_or_else(|_| creds.database.clone()),
+                total_size_bytes: total.try_get("total_size_bytes").unwrap_or(0),
+                tables,
+            })
+        }
+        DatabaseType::MySQL => {
+            let query = format!(
+                "SELECT
+                    CAST(table_name AS CHAR) AS table_name,
+                    CAST(COALESCE(data_length, 0) AS SIGNED) AS data_bytes,
+                    CAST(COALESCE(index_length, 0) AS SIGNED) AS index_bytes
+                FROM information_schema.TABLES
+                WHERE table_schema = '{}' AND table_type = 'BASE TABLE'
+                ORDER BY COALESCE(data_length, 0) + COALESCE(index_length, 0) DESC",
+                creds.database.replace('\'', "''")
+            );
+            let rows = sqlx::query(&query)
+                .fetch_all(&*pool)
+                .await
+                .map_err(|e| DatabaseError::query_failed(e, &query))?;
+
+            let mut tables = Vec::new();
+            for row in rows {
+                let data_bytes = get_mysql_int(&row, "data_bytes")
+                    .or_else(|| get_mysql_int(&row, "DATA_BYTES"))
+                    .unwrap_or(0);
+                let index_bytes = get_mysql_int(&row, "index_bytes")
+                    .or_else(|| get_mysql_int(&row, "INDEX_BYTES"))
+                    .unwrap_or(0);
+                tables.push(TableSizeInfo {
+                    table_name: row
+                        .try_get("table_name")
+                        .or_else(|_| row.try_get("TABLE_NAME"))
+                        .map_err(DatabaseError::from)?,
+                    data_bytes,
+                    index_bytes,
+                    total_bytes: data_bytes + index_bytes,
+                });
+            }
+
+            Ok(DatabaseSizeInfo {
+                database_name: creds.database.clone(),
+                total_size_bytes: tables.iter().map(|t| t.total_bytes).sum(),
+                tables,
+            })
+        }
+        DatabaseType::SQLite => {
+            let pragma = |name: &'static str| {
+                let pool = pool.clone();
+                async move {
+                    let row = sqlx::query(&format!("PRAGMA {}", name))
+                        .fetch_one(&*pool)
+                        .await
+                        .map_err(DatabaseError::from)?;
+                    row.try_get::<i64, _>(0)
+                        .map_err(DatabaseError::from)
+                }
+            };
+            let page_count = pragma("page_count").await?;
+            let page_size = pragma("page_size").await?;
+
+            Ok(DatabaseSizeInfo {
+                database_name: creds.database.clone(),
+                total_size_bytes: page_count * page_size,
+                tables: Vec::new(),
+            })
+        }
+        DatabaseType::ClickHouse => Err(DatabaseError::UnsupportedType),
+    }
+}
+
+/// Scan and row modification counters of a table, all `None` where the database does
+/// not track them
+///
+/// Postgres reads `pg_stat_user_tables`; `last_vacuum` and `last_analyze` include
+/// autovacuum and autoanalyze runs. MySQL reads the insert, update and delete counts
+/// from `performance_schema.table_io_waits_summary_by_table` and leaves every field
+/// `None` when the performance schema is disabled. SQLite keeps no statistics.
+pub async fn collect_table_statistics(
+    database_id: &str,
+    table_name: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<TableStatistics, DatabaseError> {
+    let creds = store.get(database_id)?;
+    let mut stats = TableStatistics {
+        table_name: table_name.to_string(),
+        schema: None,
+        seq_scans: None,
+        idx_scans: None,
+        rows_inserted: None,
+        rows_updated: None,
+        rows_deleted: None,
+        last_vacuum: None,
+        last_analyze: None,
+    };
+    let escaped = table_name.replace('\'', "''");
+
+    match creds.db_type {
+        DatabaseType::Postgres => {
+            let pool = pools.get_or_create(database_id, &creds).await?;
+            // Prefer the table visible on the search path when several schemas have one
+            let query = format!(
+                "SELECT
+                    schemaname::text AS schema,
+                    seq_scan::bigint AS seq_scans,
+                    idx_scan::bigint AS idx_scans,
+                    n_tup_ins::bigint AS rows_inserted,
+                    n_tup_upd::bigint AS rows_updated,
+                    n_tup_del::bigint AS rows_deleted,
+                    EXTRACT(EPOCH FROM GREATEST(last_vacuum, last_autovacuum))::bigint
+                        AS last_vacuum,
+                    EXTRACT(EPOCH FROM GREATEST(last_analyze, last_autoanalyze))::bigint
+                        AS last_analyze
+                FROM pg_stat_user_tables
+                WHERE relname = '{}'
+                ORDER BY schemaname = ANY(current_schemas(false)) DESC
+                LIMIT 1",
+                escaped
+            );
+            let row = sqlx::query(&query)
+                .fetch_optional(&*pool)
+                .await
+                .map_err(|e| DatabaseError::query_failed(e, &query))?
+                .ok_or_else(|| DatabaseError::Other(format!("Table not found: {}", table_name)))?;
+
+            let timestamp = |column: &str| {
+                row.try_get::<Option<i64>, _>(column)
+                    .ok()
+                    .flatten()
+                    .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+            };
+            stats.schema = row.try_get("schema").ok();
+            stats.seq_scans = row.try_get::<Option<i64>, _>("seq_scans").ok().flatten();
+            stats.idx_scans = row.try_get::<Option<i64>, _>("idx_scans").ok().flatten();
+            stats.rows_inserted = row.try_get::<Option<i64>, _>("rows_inserted").ok().flatten();
+            stats.rows_updated = row.try_get::<Option<i64>, _>("rows_updated").ok().flatten();
+            stats.rows_deleted = row.try_get::<Option<i64>, _>("rows_deleted").ok().flatten();
+            stats.last_vacuum = timestamp("last_vacuum");
+            stats.last_analyze = timestamp("last_analyze");
+        }
+        DatabaseType::MySQL => {
+            let pool = pools.get_or_create(database_id, &creds).await?;
+            let query = format!(
+                "SELECT
+                    CAST(object_schema AS CHAR) AS table_schema,
+                    count_insert AS rows_inserted,
+                    count_update AS rows_updated,
+                    count_delete AS rows_deleted
+                FROM performance_schema.table_io_waits_summary_by_table
+                WHERE object_schema = '{}' AND object_name = '{}'",
+                creds.database.replace('\'', "''"),
+                escaped
+            );
+            // The performance schema can be disabled, which is not an error here
+            if let Ok(Some(row)) = sqlx::query(&query).fetch_optional(&*pool).await {
+                stats.schema = row
+                    .try_get("table_schema")
+                    .or_else(|_| row.try_get("TABLE_SCHEMA"))
+                    .ok();
+                stats.rows_inserted = get_mysql_int(&row, "rows_inserted")
+                    .or_else(|| get_mysql_int(&row, "ROWS_INSERTED"));
+                stats.rows_updated = get_mysql_int(&row, "rows_updated")
+                    .or_else(|| get_mysql_int(&row, "ROWS_UPDATED"));
+                stats.rows_deleted = get_mysql_int(&row, "rows_deleted")
+                    .or_else(|| get_mysql_int(&row, "ROWS_DELETED"));
+            }
+        }
+        DatabaseType::SQLite | DatabaseType::ClickHouse => {}
+    }
+
+    Ok(stats)
+}
+
+/// Below this estimated size a row count estimate is replaced by an exact count,
+/// which is cheap for small tables and where estimates are least reliable
+const SMALL_TABLE_ROWS: i64 = 10_000;
+
+/// Count the rows of a table, exactly or from the database statistics
+///
+/// Estimates come from `pg_class.reltuples` (Postgres) or
+/// `information_schema.TABLES.TABLE_ROWS` (MySQL). SQLite keeps no row statistics,
+/// so it is always counted exactly, as are tables without statistics or with fewer
+/// than `SMALL_TABLE_ROWS` estimated rows. `schema` qualifies the table name on
+/// Postgres and MySQL and is ignored on SQLite.
+pub async fn count_table_rows(
+    database_id: &str,
+    table_name: &str,
+    schema: Option<&str>,
+    exact: bool,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<TableRowCountResult, DatabaseError> {
+    let creds = store.get(database_id)?;
+    let pool = pools.get_or_create(database_id, &creds).await?;
+    let escaped = table_name.replace('\'', "''");
+    let qualified = qualified_table_name(table_name, schema, &creds.db_type);
+
+    let estimate = match (&creds.db_type, exact) {
+        (_, true) | (DatabaseType::SQLite | DatabaseType::ClickHouse, _) => None,
+        (DatabaseType::Postgres, false) => {
+            let query = format!(
+                "SELECT reltuples::bigint AS estimate FROM pg_class WHERE oid = to_regclass('{}')",
+                qualified.replace('\'', "''")
+            );
+            let row = sqlx::query(&query)
+                .fetch_optional(&*pool)
+                .await
+                .map_err(DatabaseError::from)?;
+            // reltuples is -1 for tables that have never been vacuumed or analyzed
+            row.and_then(|row| row.try_get::<i64, _>("estimate").ok())
+                .filter(|estimate| *estimate >= 0)
+                .map(|estimate| (estimate, "pg_class.reltuples"))
+        }
+        (DatabaseType::MySQL, false) => {
+            let query = format!(
+                "SELECT CAST(TABLE_ROWS AS SIGNED) AS estimate FROM information_schema.TABLES WHERE TABLE_SCHEMA = '{}' AND TABLE_NAME = '{}'",
+                schema.unwrap_or(&creds.database).replace('\'', "''"),
+                escaped
+            );
+            let row = sqlx::query(&query)
+                .fetch_optional(&*pool)
+                .await
+                .map_err(DatabaseError::from)?;
+            row.and_then(|row| {
+                get_mysql_int(&row, "estimate").or_else(|| get_mysql_int(&row, "ESTIMATE"))
+            })
+            .map(|estimate| (estimate, "information_schema.TABLES.TABLE_ROWS"))
+        }
+    };
+
+    if let Some((count, method)) = estimate.filter(|(count, _)| *count >= SMALL_TABLE_ROWS) {
+        return Ok(TableRowCountResult {
+            count,
+            is_exact: false,
+            method_used: method.to_string(),
+        });
+    }
+
+    Ok(TableRowCountResult {
+        count: exact_row_count(&pool, &qualified).await?,
+        is_exact: true,
+        method_used: "COUNT(*)".to_string(),
+    })
+}
+
+/// Count the rows of every table in the database concurrently
+///
+/// Returns the tables from `get_tables` with `row_count` filled in using
+/// `SELECT COUNT(*)`. A table whose count fails keeps `row_count: None` instead of
+/// failing the whole batch.
+pub async fn count_all_table_rows(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<TableInfo>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    let mut tables = get_tables(database_id, store, pools).await?;
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let counts = join_all(tables.iter().map(|table| {
+        let qualified = qualified_table_name(&table.name, table.schema.as_deref(), &creds.db_type);
+        let pool = &pool;
+        async move { exact_row_count(pool, &qualified).await.ok() }
+    }))
+    .await;
+
+    for (table, count) in tables.iter_mut().zip(counts) {
+        table.row_count = count;
+    }
+    Ok(tables)
+}
+
+/// Number of distinct values returned in `ColumnStatistics::sample_values`
+const COLUMN_SAMPLE_VALUES: u32 = 10;
+
+/// Profile the values of a column: range, average, distinct values and NULLs
+///
+/// The aggregates are computed in one query. Integer and float columns keep their
+/// type and get an average; every other column is compared as text, so its
+/// minimum and maximum are lexicographic. Masking rules apply to the minimum,
+/// maximum and sample values.
+pub async fn collect_column_statistics(
+    database_id: &str,
+    table_name: &str,
+    column_name: &str,
+    schema: Option<&str>,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<ColumnStatistics, DatabaseError> {
+    let creds = store.get(database_id)?;
+
+    let table_list = format!("('{}')", table_name.replace('\'', "''"));
+    let column = get_table_schema(database_id, &table_list, schema, store, pools)
+        .await?
+        .into_iter()
+        .find(|s| s.table_name == table_name)
+        .ok_or_else(|| DatabaseError::Other(format!("Table not found: {}", table_name)))?
+        .columns
+        .into_iter()
+        .find(|col| col.name == column_name)
+        .ok_or_else(|| {
+            DatabaseError::Other(format!("Column not found in {}: {}", table_name, column_name))
+        })?;
+
+    let normalized = NormalizedType::from_data_type(&column.data_type);
+    let is_numeric = matches!(normalized, NormalizedType::Integer | NormalizedType::Float);
+    let quoted = quote_identifier(column_name, &creds.db_type);
+    let qualified = qualified_table_name(table_name, schema, &creds.db_type);
+
+    // Casting keeps the values readable by the Any driver, which has no decimal or date types
+    let value_expr = |expr: String| match (&creds.db_type, &normalized) {
+        (DatabaseType::SQLite | DatabaseType::ClickHouse, _) => expr,
+        (DatabaseType::Postgres, NormalizedType::Integer) => format!("({})::bigint", expr),
+        (DatabaseType::Postgres, NormalizedType::Float) => format!("({})::float8", expr),
+        (DatabaseType::Postgres, _) => format!("({})::text", expr),
+        (DatabaseType::MySQL, NormalizedType::Integer) => format!("CAST({} AS SIGNED)", expr),
+        (DatabaseType::MySQL, NormalizedType::Float) => format!("CAST({} AS DOUBLE)", expr),
+        (DatabaseType::MySQL, _) => format!("CAST({} AS CHAR)", expr),
+    };
+    let compared = if is_numeric || matches!(creds.db_type, DatabaseType::SQLite) {
+        quoted.clone()
+    } else {
+        value_expr(quoted.clone())
+    };
+    let avg_expr = match (&creds.db_type, is_numeric) {
+        (_, false) => "NULL".to_string(),
+        (DatabaseType::Postgres, true) => format!("AVG({})::float8", quoted),
+        (DatabaseType::MySQL, true) => format!("CAST(AVG({}) AS DOUBLE)", quoted),
+        (DatabaseType::SQLite | DatabaseType::ClickHouse, true) => format!("AVG({})", quoted),
+    };
+
+    let query = format!(
+        "SELECT {} AS min_value, {} AS max_value, {} AS avg_value,
+            COUNT(DISTINCT {}) AS distinct_count,
+            COUNT(*) - COUNT({}) AS null_count,
+            COUNT(*) AS total_count
+        FROM {}",
+        value_expr(format!("MIN({})", compared)),
+        value_expr(format!("MAX({})", compared)),
+        avg_expr,
+        compared,
+        quoted,
+        qualified
+    );
+    let samples_query = format!(
+        "SELECT DISTINCT {} AS {} FROM {} WHERE {} IS NOT NULL LIMIT {}",
+        value_expr(quoted.clone()),
+        quoted,
+        qualified,
+        quoted,
+        COLUMN_SAMPLE_VALUES
+    );
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let row = sqlx::query(&query)
+        .fetch_one(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, &query))?;
+    let sample_rows = sqlx::query(&samples_query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, &samples_query))?;
+
+    let count = |name: &str| {
+        get_mysql_int(&row, name)
+            .or_else(|| get_mysql_int(&row, &name.to_uppercase()))
+            .unwrap_or(0)
+    };
+    let aggregates = convert_row(&row);
+    let value = |name: &str| aggregates.get(name).filter(|v| !v.is_null()).cloned();
+
+    // Mask the range together with the samples, as rows of the single column
+    let columns = vec![column_name.to_string()];
+    let mut values: Vec<HashMap<String, serde_json::Value>> =
+        [value("min_value"), value("max_value")]
+            .into_iter()
+            .chain(sample_rows.iter().map(|row| convert_row(row).remove(column_name)))
+            .map(|v| {
+                HashMap::from([(column_name.to_string(), v.unwrap_or(serde_json::Value::Null))])
+            })
+            .collect();
+    apply_masking(&creds, &columns, &mut values);
+    let mut values = values
+        .into_iter()
+        .map(|mut row| row.remove(column_name).filter(|v| !v.is_null()));
+
+    Ok(ColumnStatistics {
+        min_value: values.next().flatten(),
+        max_value: values.next().flatten(),
+        avg_value: row.try_get::<f64, _>("avg_value").ok(),
+        distinct_count: count("distinct_count"),
+        null_count: count("null_count"),
+        total_count: count("total_count"),
+        sample_values: values.flatten().collect(),
+    })
+}
+
+/// Run `SELECT COUNT(*)` against an already quoted table name
+async fn exact_row_count(pool: &sqlx::AnyPool, qualified: &str) -> Result<i64, DatabaseError> {
+    let query = format!("SELECT COUNT(*) AS row_count FROM {}", qualified);
+    let row = sqlx::query(&query)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, &query))?;
+    row.try_get(0)
+        .map_err(DatabaseError::from)
+}
+
+/// Quote a table name, prefixed with its schema where the database supports one
+pub(crate) fn qualified_table_name(
+    table_name: &str,
+    schema: Option<&str>,
+    db_type: &DatabaseType,
+) -> String {
+    match (schema, db_type) {
+        (
+            Some(schema),
+            DatabaseType::Postgres | DatabaseType::MySQL | DatabaseType::ClickHouse,
+        ) => format!(
+            "{}.{}",
+            quote_identifier(schema, db_type),
+            quote_identifier(table_name, db_type)
+        ),
+        _ => quote_identifier(table_name, db_type),
+    }
+}
+
+/// Get the statements that create a table and its indexes
+///
+/// MySQL and SQLite return the statement they store. Postgres does not keep one,
+/// so it is rebuilt from the catalog: columns with their defaults and identity or
+/// generation clauses, every constraint as `pg_get_constraintdef` renders it, and
+/// a `CREATE INDEX` for each index that does not back a constraint. Storage
+/// parameters, ownership and grants are left out. Only reads the catalog.
+pub async fn generate_table_ddl(
+    database_id: &str,
+    table_name: &str,
+    schema: Option<&str>,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<TableDDL, DatabaseError> {
+    let creds = store.get(database_id)?;
+    let pool = pools.get_or_create(database_id, &creds).await?;
+    let not_found = || DatabaseError::Other(format!("Table not found: {}", table_name));
+
+    let ddl = match creds.db_type {
+        DatabaseType::Postgres => {
+            let schema = schema.unwrap_or("public");
+            let qualified = qualified_table_name(table_name, Some(schema), &creds.db_type);
+            postgres_table_ddl(&pool, &qualified)
+                .await?
+                .ok_or_else(not_found)?
+        }
+        DatabaseType::MySQL => {
+            let qualified = qualified_table_name(
+                table_name,
+                Some(schema.unwrap_or(&creds.database)),
+                &creds.db_type,
+            );
+            let query = format!("SHOW CREATE TABLE {}", qualified);
+            let row = sqlx::query(&query)
+                .fetch_optional(&*pool)
+                .await
+                .map_err(|e| DatabaseError::query_failed(e, &query))?
+                .ok_or_else(not_found)?;
+            // The statement is the second column, named "Create Table" or "Create View"
+            row.try_get::<String, _>(1)
+                .or_else(|_| {
+                    row.try_get::<Vec<u8>, _>(1)
+                        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                })
+                .map_err(DatabaseError::from)?
+        }
+        DatabaseType::SQLite => {
+            let rows = sqlx::query(
+                "SELECT sql FROM sqlite_master
+                WHERE tbl_name = ? AND type IN ('table', 'view', 'index') AND sql IS NOT NULL
+                ORDER BY type = 'index', name",
+            )
+            .bind(table_name)
+            .fetch_all(&*pool)
+            .await
+            .map_err(DatabaseError::from)?;
+            if rows.is_empty() {
+                return Err(not_found());
+            }
+            rows.iter()
+                .map(|row| row.try_get::<String, _>("sql").map(|sql| format!("{};", sql)))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(DatabaseError::from)?
+                .join("\n\n")
+        }
+        DatabaseType::ClickHouse => return Err(DatabaseError::UnsupportedType),
+    };
+
+    Ok(TableDDL {
+        table_name: table_name.to_string(),
+        ddl,
+        database_type: creds.db_type,
+    })
+}
+
+/// Rebuild the CREATE TABLE statement of a Postgres table, `None` if it does not exist
+async fn postgres_table_ddl(
+    pool: &sqlx::AnyPool,
+    qualified: &str,
+) -> Result<Option<String>, DatabaseError> {
+    let columns_query = "SELECT a.attname::text AS column_name,
+            format_type(a.atttypid, a.atttypmod)::text AS data_type,
+            a.attnotnull AS not_null,
+            a.attidentity::text AS identity,
+            a.attgenerated::text AS generated,
+            pg_get_expr(d.adbin, d.adrelid)::text AS default_value
+        FROM pg_attribute a
+        LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+        WHERE a.attrelid = to_regclass($1) AND a.attnum > 0 AND NOT a.attisdropped
+        ORDER BY a.attnum";
+    let column_rows = sqlx::query(columns_query)
+        .bind(qualified)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, columns_query))?;
+    if column_rows.is_empty() {
+        return Ok(None);
+    }
+
+    let mut definitions = Vec::new();
+    for row in &column_rows {
+        let name: String = row
+            .try_get("column_name")
+            .map_err(DatabaseError::from)?;
+        let data_type: String = row.try_get("data_type").unwrap_or_default();
+        let default_value: Option<String> = row.try_get("default_value").unwrap_or(None);
+        let identity: String = row.try_get("identity").unwrap_or_default();
+        let generated: String = row.try_get("generated").unwrap_or_default();
+
+        let mut definition = format!(
+            "    {} {}",
+            quote_identifier(&name, &DatabaseType::Postgres),
+            data_type
+        );
+        match (identity.as_str(), generated.as_str(), default_value) {
+            ("a", _, _) => definition.push_str(" GENERATED ALWAYS AS IDENTITY"),
+            ("d", _, _) => definition.push_str(" GENERATED BY DEFAULT AS IDENTITY"),
+            (_, "s", Some(expr)) => {
+                definition.push_str(&format!(" GENERATED ALWAYS AS ({}) STORED", expr))
+            }
+            (_, _, Some(default)) => definition.push_str(&format!(" DEFAULT {}", default)),
+            _ => {}
+        }
+        if row.try_get("not_null").unwrap_or(false) {
+            definition.push_str(" NOT NULL");
+        }
+        definitions.push(definition);
+    }
+
+    let constraints_query = "SELECT conname::text AS name,
+            pg_get_constraintdef(oid)::text AS definition
+        FROM pg_constraint
+        WHERE conrelid = to_regclass($1) AND contype IN ('p', 'u', 'f', 'c', 'x')
+        ORDER BY contype <> 'p', conname";
+    let constraint_rows = sqlx::query(constraints_query)
+        .bind(qualified)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, constraints_query))?;
+    for row in &constraint_rows {
+        let name: String = row
+            .try_get("name")
+            .map_err(DatabaseError::from)?;
+        let definition: String = row.try_get("definition").unwrap_or_default();
+        definitions.push(format!(
+            "    CONSTRAINT {} {}",
+            quote_identifier(&name, &DatabaseType::Postgres),
+            definition
+        ));
+    }
+
+    let mut ddl = format!("CREATE TABLE {} (\n{}\n);", qualified, definitions.join(",\n"));
+
+    // Indexes backing a constraint are already covered by the constraint
+    let indexes_query = "SELECT pg_get_indexdef(i.indexrelid)::text AS definition
+        FROM pg_index i
+        WHERE i.indrelid = to_regclass($1)
+            AND NOT EXISTS (SELECT 1 FROM pg_constraint c WHERE c.conindid = i.indexrelid)
+        ORDER BY i.indexrelid";
+    let index_rows = sqlx::query(indexes_query)
+        .bind(qualified)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, indexes_query))?;
+    for row in &index_rows {
+        let definition: String = row
+            .try_get("definition")
+            .map_err(DatabaseError::from)?;
+        ddl.push_str(&format!("\n\n{};", definition));
+    }
+
+    Ok(Some(ddl))
+}
+
+/// List the stored procedures and functions of a database
+///
+/// SQLite and ClickHouse have no stored routines, so they always return an empty list.
+pub async fn get_routines(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<RoutineInfo>, DatabaseError> {
+    let creds = store.get(database_id)?;
+
+    let query = match creds.db_type {
+        DatabaseType::Postgres => {
+            "SELECT routine_name::text AS routine_name,
+                routine_schema::text AS routine_schema,
+                routine_type::text AS routine_type,
+                data_type::text AS return_type,
+                external_language::text AS language,
+                routine_definition::text AS definition
+            FROM information_schema.routines
+            WHERE routine_schema NOT IN ('pg_catalog', 'information_schema')
+            ORDER BY routine_schema, routine_name"
+                .to_string()
+        }
+        DatabaseType::MySQL => format!(
+            "SELECT CAST(ROUTINE_NAME AS CHAR) AS routine_name,
+                CAST(ROUTINE_SCHEMA AS CHAR) AS routine_schema,
+                CAST(ROUTINE_TYPE AS CHAR) AS routine_type,
+                CAST(NULLIF(DTD_IDENTIFIER, '') AS CHAR) AS return_type,
+                CAST(ROUTINE_BODY AS CHAR) AS language,
+                CAST(ROUTINE_DEFINITION AS CHAR) AS definition
+            FROM information_schema.ROUTINES
+            WHERE ROUTINE_SCHEMA = '{}'
+            ORDER BY ROUTINE_NAME",
+            creds.database.replace('\'', "''")
+        ),
+        DatabaseType::SQLite | DatabaseType::ClickHouse => return Ok(Vec::new()),
+    };
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let rows = sqlx::query(&query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, &query))?;
+
+    let mut routines = Vec::new();
+    for row in rows {
+        let name: String = row
+            .try_get("routine_name")
+            .or_else(|_| row.try_get("ROUTINE_NAME"))
+            .map_err(DatabaseError::from)?;
+        let schema: String = row
+            .try_get("routine_schema")
+            .or_else(|_| row.try_get("ROUTINE_SCHEMA"))
+            .unwrap_or_default();
+        // Postgres reports NULL here for aggregate and window functions
+        let routine_type: Option<String> = row
+            .try_get("routine_type")
+            .or_else(|_| row.try_get("ROUTINE_TYPE"))
+            .unwrap_or(None);
+        let optional = |column: &str| -> Option<String> {
+            row.try_get(column)
+                .or_else(|_| row.try_get(column.to_uppercase().as_str()))
+                .unwrap_or(None)
+        };
+
+        routines.push(RoutineInfo {
+            name,
+            schema,
+            routine_type: match routine_type.as_deref() {
+                Some(t) if t.eq_ignore_ascii_case("PROCEDURE") => RoutineType::Procedure,
+                _ => RoutineType::Function,
+            },
+            return_type: optional("return_type"),
+            language: optional("language"),
+            definition: optional("definition"),
+        });
+    }
+
+    Ok(routines)
+}
+
+/// List the enum types of a database with their labels
+///
+/// MySQL has no named enum types, so every `ENUM` column is reported as its own
+/// type named after the table and column. SQLite has no enums.
+pub async fn get_enum_types(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<EnumTypeInfo>, DatabaseError> {
+    let creds = store.get(database_id)?;
+
+    let query = match creds.db_type {
+        DatabaseType::Postgres => {
+            "SELECT n.nspname::text AS type_schema,
+                t.typname::text AS type_name,
+                e.enumlabel::text AS label
+            FROM pg_type t
+            JOIN pg_enum e ON e.enumtypid = t.oid
+            JOIN pg_namespace n ON n.oid = t.typnamespace
+            WHERE n.nspname NOT IN ('pg_catalog', 'information_schema')
+            ORDER BY n.nspname, t.typname, e.enumsortorder"
+                .to_string()
+        }
+        DatabaseType::MySQL => format!(
+            "SELECT CAST(TABLE_SCHEMA AS CHAR) AS type_schema,
+                CAST(CONCAT(TABLE_NAME, '.', COLUMN_NAME) AS CHAR) AS type_name,
+                CAST(COLUMN_TYPE AS CHAR) AS label
+            FROM INFORMATION_SCHEMA.COLUMNS
+            WHERE TABLE_SCHEMA = '{}' AND DATA_TYPE = 'enum'
+            ORDER BY TABLE_NAME, ORDINAL_POSITION",
+            creds.database.replace('\'', "''")
+        ),
+        DatabaseType::SQLite => return Ok(Vec::new()),
+        DatabaseType::ClickHouse => return Err(DatabaseError::UnsupportedType),
+    };
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let rows = sqlx::query(&query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, &query))?;
+
+    let mut enums: Vec<EnumTypeInfo> = Vec::new();
+    for row in rows {
+        let get_string = |column: &str| -> Result<String, DatabaseError> {
+            row.try_get(column)
+                .or_else(|_| row.try_get(column.to_uppercase().as_str()))
+                .map_err(DatabaseError::from)
+        };
+        let schema = get_string("type_schema")?;
+        let name = get_string("type_name")?;
+        let label = get_string("label")?;
+
+        if matches!(creds.db_type, DatabaseType::MySQL) {
+            enums.push(EnumTypeInfo {
+                schema,
+                name,
+                values: parse_mysql_enum_values(&label),
+            });
+            continue;
+        }
+
+        // Postgres returns one row per label, ordered by type
+        match enums.last_mut() {
+            Some(last) if last.schema == schema && last.name == name => last.values.push(label),
+            _ => enums.push(EnumTypeInfo {
+                schema,
+                name,
+                values: vec![label],
+            }),
+        }
+    }
+
+    Ok(enums)
+}
+
+/// Read the labels of a MySQL `COLUMN_TYPE` such as `enum('a','it''s')`
+fn parse_mysql_enum_values(column_type: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut chars = column_type.chars().peekable();
+    let mut current: Option<String> = None;
+
+    while let Some(c) = chars.next() {
+        match (c, current.as_mut()) {
+            ('\'', None) => current = Some(String::new()),
+            // A doubled quote is an escaped quote inside the label
+            ('\'', Some(value)) if chars.peek() == Some(&'\'') => {
+                value.push('\'');
+                chars.next();
+            }
+            ('\'', Some(_)) => values.extend(current.take()),
+            ('\\', Some(value)) => value.extend(chars.next()),
+            (c, Some(value)) => value.push(c),
+            (_, None) => {}
+        }
+    }
+
+    values
+}
+
+/// List the sequences of a Postgres database
+///
+/// Other databases have no sequences and get an empty list, the same as a Postgres
+/// database without any, so the frontend needs no special case for them.
+pub async fn get_sequences(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<SequenceInfo>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Ok(Vec::new());
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    // pg_sequences reports last_value as NULL when the sequence is unused or unreadable
+    let query = "SELECT s.sequence_name::text AS sequence_name,
+            s.sequence_schema::text AS sequence_schema,
+            s.data_type::text AS data_type,
+            s.start_value::bigint AS start_value,
+            s.increment::bigint AS increment,
+            s.minimum_value::bigint AS min_value,
+            s.maximum_value::bigint AS max_value,
+            ps.last_value::bigint AS current_value,
+            s.cycle_option = 'YES' AS is_cycled,
+            owner.owned_by
+        FROM information_schema.sequences s
+        LEFT JOIN pg_sequences ps
+            ON ps.schemaname = s.sequence_schema AND ps.sequencename = s.sequence_name
+        LEFT JOIN LATERAL (
+            SELECT (tbl.relname || '.' || att.attname)::text AS owned_by
+            FROM pg_depend d
+            JOIN pg_class seq ON seq.oid = d.objid
+            JOIN pg_namespace seq_ns ON seq_ns.oid = seq.relnamespace
+            JOIN pg_class tbl ON tbl.oid = d.refobjid
+            JOIN pg_attribute att ON att.attrelid = d.refobjid AND att.attnum = d.refobjsubid
+            WHERE d.classid = 'pg_class'::regclass
+                AND d.deptype IN ('a', 'i')
+                AND seq.relname = s.sequence_name
+                AND seq_ns.nspname = s.sequence_schema
+            LIMIT 1
+        ) owner ON true
+        WHERE s.sequence_schema NOT IN ('pg_catalog', 'information_schema')
+        ORDER BY s.sequence_schema, s.sequence_name";
+
+    let rows = sqlx::query(query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, query))?;
+
+    let mut sequences = Vec::new();
+    for row in rows {
+        let get_i64 = |column: &str| -> Result<i64, DatabaseError> {
+            row.try_get(column)
+                .map_err(DatabaseError::from)
+        };
+
+        sequences.push(SequenceInfo {
+            name: row
+                .try_get("sequence_name")
+                .map_err(DatabaseError::from)?,
+            schema: row.try_get("sequence_schema").unwrap_or_default(),
+            data_type: row.try_get("data_type").unwrap_or_default(),
+            start_value: get_i64("start_value")?,
+            increment: get_i64("increment")?,
+            min_value: get_i64("min_value")?,
+            max_value: get_i64("max_value")?,
+            current_value: row.try_get("current_value").unwrap_or(None),
+            is_cycled: row.try_get("is_cycled").unwrap_or(false),
+            owned_by: row.try_get("owned_by").unwrap_or(None),
+        });
+    }
+
+    Ok(sequences)
+}
+
+/// List the schemas of a database
+///
+/// For Postgres these are the schemas in `information_schema.schemata` apart from
+/// the system ones, with `current_schema()` as the default. A MySQL database is its
+/// own schema and SQLite only has `main`.
+pub async fn get_schemas(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<SchemaInfo>, DatabaseError> {
+    let creds = store.get(database_id)?;
+
+    match creds.db_type {
+        DatabaseType::Postgres => {
+            let pool = pools.get_or_create(database_id, &creds).await?;
+            let query = "SELECT schema_name::text AS schema_name,
+                    schema_owner::text AS schema_owner,
+                    schema_name = current_schema() AS is_default
+                FROM information_schema.schemata
+                WHERE schema_name NOT IN ('pg_catalog', 'information_schema', 'pg_toast')
+                    AND schema_name NOT LIKE 'pg\\_temp\\_%'
+                    AND schema_name NOT LIKE 'pg\\_toast\\_temp\\_%'
+                ORDER BY schema_name";
+
+            let rows = sqlx::query(query)
+                .fetch_all(&*pool)
+                .await
+                .map_err(|e| DatabaseError::query_failed(e, query))?;
+
+            let mut schemas = Vec::new();
+            for row in rows {
+                schemas.push(SchemaInfo {
+                    name: row
+                        .try_get("schema_name")
+                        .map_err(DatabaseError::from)?,
+                    owner: row.try_get("schema_owner").unwrap_or(None),
+                    is_default: row.try_get("is_default").unwrap_or(false),
+                });
+            }
+            Ok(schemas)
+        }
+        DatabaseType::MySQL | DatabaseType::ClickHouse => Ok(vec![SchemaInfo {
+            name: creds.database.clone(),
+            owner: None,
+            is_default: true,
+        }]),
+        DatabaseType::SQLite => Ok(vec![SchemaInfo {
+            name: "main".to_string(),
+            owner: None,
+            is_default: true,
+        }]),
+    }
+}
+
+/// List the triggers defined on the tables of a database
+///
+/// Postgres reports a trigger once per event in `information_schema.triggers`, so
+/// the events are joined into one entry. Only Postgres triggers can be disabled;
+/// MySQL and SQLite triggers are always reported as enabled.
+pub async fn get_triggers(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<TriggerInfo>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let query = match creds.db_type {
+        DatabaseType::Postgres => {
+            "SELECT t.trigger_name::text AS trigger_name,
+                t.event_object_table::text AS table_name,
+                string_agg(t.event_manipulation::text, ' OR ' ORDER BY t.event_manipulation) AS event,
+                t.action_timing::text AS timing,
+                t.action_statement::text AS statement,
+                pg_trigger.tgenabled <> 'D' AS enabled
+            FROM information_schema.triggers t
+            JOIN pg_trigger ON pg_trigger.tgname = t.trigger_name
+                AND pg_trigger.tgrelid = (quote_ident(t.event_object_schema) || '.' || quote_ident(t.event_object_table))::regclass
+            WHERE t.trigger_schema NOT IN ('pg_catalog', 'information_schema')
+            GROUP BY t.trigger_name, t.event_object_schema, t.event_object_table,
+                t.action_timing, t.action_statement, pg_trigger.tgenabled
+            ORDER BY table_name, trigger_name"
+                .to_string()
+        }
+        DatabaseType::MySQL => format!(
+            "SELECT CAST(TRIGGER_NAME AS CHAR) AS trigger_name,
+                CAST(EVENT_OBJECT_TABLE AS CHAR) AS table_name,
+                CAST(EVENT_MANIPULATION AS CHAR) AS event,
+                CAST(ACTION_TIMING AS CHAR) AS timing,
+                CAST(ACTION_STATEMENT AS CHAR) AS statement
+            FROM INFORMATION_SCHEMA.TRIGGERS
+            WHERE TRIGGER_SCHEMA = '{}'
+            ORDER BY EVENT_OBJECT_TABLE, TRIGGER_NAME",
+            creds.database.replace('\'', "''")
+        ),
+        DatabaseType::SQLite => {
+            "SELECT name AS trigger_name, tbl_name AS table_name, sql AS statement
+            FROM sqlite_master WHERE type = 'trigger' ORDER BY tbl_name, name"
+                .to_string()
+        }
+        DatabaseType::ClickHouse => return Err(DatabaseError::UnsupportedType),
+    };
+
+    let rows = sqlx::query(&query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, &query))?;
+
+    let mut triggers = Vec::new();
+    for row in rows {
+        let get_string = |column: &str| -> Option<String> {
+            row.try_get(column)
+                .or_else(|_| row.try_get(column.to_uppercase().as_str()))
+                .ok()
+        };
+
+        let name = get_string("trigger_name")
+            .ok_or_else(|| DatabaseError::QueryError("Missing trigger_name column".to_string()))?;
+        let statement = get_string("statement");
+
+        let (timing, event) = match creds.db_type {
+            DatabaseType::SQLite => parse_sqlite_trigger(statement.as_deref().unwrap_or("")),
+            _ => (
+                get_string("timing").unwrap_or_default(),
+                get_string("event").unwrap_or_default(),
+            ),
+        };
+
+        triggers.push(TriggerInfo {
+            name,
+            table_name: get_string("table_name").unwrap_or_default(),
+            event,
+            timing,
+            statement,
+            enabled: row.try_get("enabled").unwrap_or(true),
+        });
+    }
+
+    Ok(triggers)
+}
+
+/// Read the timing and event from a SQLite `CREATE TRIGGER` statement
+///
+/// Only the part before `ON <table>` is inspected. SQLite defaults to BEFORE when
+/// no timing is given.
+fn parse_sqlite_trigger(sql: &str) -> (String, String) {
+    let upper = sql.to_uppercase();
+    let tokens: Vec<&str> = upper
+        .split_whitespace()
+        .take_while(|token| *token != "ON")
+        .collect();
+
+    let timing = if tokens.windows(2).any(|pair| pair == ["INSTEAD", "OF"]) {
+        "INSTEAD OF"
+    } else if tokens.contains(&"AFTER") {
+        "AFTER"
+    } else {
+        "BEFORE"
+    };
+    let event = tokens
+        .iter()
+        .find(|token| matches!(**token, "INSERT" | "UPDATE" | "DELETE"))
+        .copied()
+        .unwrap_or_default();
+
+    (timing.to_string(), event.to_string())
+}
+
+/// List the CHECK constraints defined on the tables of a database
+///
+/// Postgres reports NOT NULL columns as CHECK constraints too; those are left out.
+/// MySQL (8.0.16 and later) does not record which columns a constraint uses, so
+/// `column_name` is always `None` there. SQLite constraints are read from the
+/// `CREATE TABLE` statements, see `parse_sqlite_check_constraints`.
+pub async fn get_check_constraints(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<CheckConstraintInfo>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let query = match creds.db_type {
+        DatabaseType::Postgres => {
+            "SELECT cc.constraint_name::text AS constraint_name,
+                tc.table_name::text AS table_name,
+                CASE WHEN count(ccu.column_name) = 1 THEN min(ccu.column_name::text) END AS column_name,
+                cc.check_clause::text AS expression
+            FROM information_schema.check_constraints cc
+            JOIN information_schema.table_constraints tc
+                ON tc.constraint_schema = cc.constraint_schema
+                AND tc.constraint_name = cc.constraint_name
+            LEFT JOIN information_schema.constraint_column_usage ccu
+                ON ccu.constraint_schema = cc.constraint_schema
+                AND ccu.constraint_name = cc.constraint_name
+            WHERE tc.constraint_type = 'CHECK'
+                AND cc.constraint_schema NOT IN ('pg_catalog', 'information_schema')
+                AND cc.constraint_name NOT LIKE '%_not_null'
+            GROUP BY cc.constraint_schema, cc.constraint_name, tc.table_name, cc.check_clause
+            ORDER BY table_name, constraint_name"
+                .to_string()
+        }
+        DatabaseType::MySQL => format!(
+            "SELECT CAST(cc.CONSTRAINT_NAME AS CHAR) AS constraint_name,
+                CAST(tc.TABLE_NAME AS CHAR) AS table_name,
+                CAST(cc.CHECK_CLAUSE AS CHAR) AS expression
+            FROM information_schema.CHECK_CONSTRAINTS cc
+            JOIN information_schema.TABLE_CONSTRAINTS tc
+                ON tc.CONSTRAINT_SCHEMA = cc.CONSTRAINT_SCHEMA
+                AND tc.CONSTRAINT_NAME = cc.CONSTRAINT_NAME
+                AND tc.CONSTRAINT_TYPE = 'CHECK'
+            WHERE cc.CONSTRAINT_SCHEMA = '{}'
+            ORDER BY tc.TABLE_NAME, cc.CONSTRAINT_NAME",
+            creds.database.replace('\'', "''")
+        ),
+        DatabaseType::SQLite => {
+            "SELECT name AS table_name, sql AS expression FROM sqlite_master
+            WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND sql IS NOT NULL
+            ORDER BY name"
+                .to_string()
+        }
+        DatabaseType::ClickHouse => return Err(DatabaseError::UnsupportedType),
+    };
+
+    let rows = sqlx::query(&query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, &query))?;
+
+    let mut constraints = Vec::new();
+    for row in rows {
+        let get_string = |column: &str| -> Option<String> {
+            row.try_get(column)
+                .or_else(|_| row.try_get(column.to_uppercase().as_str()))
+                .ok()
+        };
+
+        let table_name = get_string("table_name").unwrap_or_default();
+        let expression = get_string("expression").unwrap_or_default();
+
+        if matches!(creds.db_type, DatabaseType::SQLite) {
+            constraints.extend(parse_sqlite_check_constraints(&table_name, &expression));
+            continue;
+        }
+
+        constraints.push(CheckConstraintInfo {
+            name: get_string("constraint_name").ok_or_else(|| {
+                DatabaseError::QueryError("Missing constraint_name column".to_string())
+            })?,
+            table_name,
+            column_name: get_string("column_name"),
+            expression,
+        });
+    }
+
+    Ok(constraints)
+}
+
+/// Extract the CHECK constraints from a SQLite `CREATE TABLE` statement
+///
+/// Constraints inside a column definition get that column as `column_name`. SQLite
+/// does not name unnamed constraints, so they are called `<table>_check<n>` after
+/// their position in the statement.
+fn parse_sqlite_check_constraints(table_name: &str, sql: &str) -> Vec<CheckConstraintInfo> {
+    let depths = sql_depths(sql);
+    let bytes = sql.as_bytes();
+    let mut constraints = Vec::new();
+
+    let Some(open) = (0..bytes.len()).find(|&i| bytes[i] == b'(' && depths[i] == Some(0)) else {
+        return constraints;
+    };
+
+    // Column definitions and table constraints are separated by top-level commas
+    let mut start = open + 1;
+    for end in open + 1..bytes.len() {
+        let closes_table = bytes[end] == b')' && depths[end] == Some(0);
+        if !(closes_table || (bytes[end] == b',' && depths[end] == Some(1))) {
+            continue;
+        }
+
+        let definition = sql[start..end].trim_start();
+        let first = leading_identifier(definition);
+        let column_name = match first.to_uppercase().as_str() {
+            "CONSTRAINT" | "CHECK" | "PRIMARY" | "UNIQUE" | "FOREIGN" | "" => None,
+            _ => Some(first.to_string()),
+        };
+
+        let mut pos = start;
+        while pos + 5 <= end {
+            let is_keyword = depths[pos] == Some(1)
+                && bytes[pos..pos + 5].eq_ignore_ascii_case(b"CHECK")
+                && (pos == 0 || !is_identifier_byte(bytes[pos - 1]))
+                && !bytes.get(pos + 5).is_some_and(|b| is_identifier_byte(*b));
+            if !is_keyword {
+                pos += 1;
+                continue;
+            }
+
+            let Some(paren) = (pos + 5..end).find(|&i| !bytes[i].is_ascii_whitespace()) else {
+                break;
+            };
+            let close = (paren + 1..end).find(|&i| bytes[i] == b')' && depths[i] == Some(1));
+            let (b'(', Some(close)) = (bytes[paren], close) else {
+                pos = paren;
+                continue;
+            };
+
+            let words: Vec<&str> = sql[start..pos].split_whitespace().collect();
+            let name = match words.as_slice() {
+                [.., keyword, name] if keyword.eq_ignore_ascii_case("CONSTRAINT") => {
+                    leading_identifier(name).to_string()
+                }
+                _ => format!("{}_check{}", table_name, constraints.len() + 1),
+            };
+
+            constraints.push(CheckConstraintInfo {
+                name,
+                table_name: table_name.to_string(),
+                column_name: column_name.clone(),
+                expression: sql[paren + 1..close].trim().to_string(),
+            });
+            pos = close + 1;
+        }
+
+        if closes_table {
+            break;
+        }
+        start = end + 1;
+    }
+
+    constraints
+}
+
+/// Parenthesis depth at each byte of `sql`, `None` inside quotes
+///
+/// An opening parenthesis has the depth outside of it, and so does its closing one.
+fn sql_depths(sql: &str) -> Vec<Option<usize>> {
+    let mut depths = Vec::with_capacity(sql.len());
+    let mut depth = 0usize;
+    let mut quote: Option<u8> = None;
+
+    for &byte in sql.as_bytes() {
+        if let Some(closing) = quote {
+            if byte == closing {
+                quote = None;
+            }
+            depths.push(None);
+            continue;
+        }
+
+        match byte {
+            b'\'' | b'"' | b'`' => {
+                quote = Some(byte);
+                depths.push(None);
+            }
+            b'[' => {
+                quote = Some(b']');
+                depths.push(None);
+            }
+            b'(' => {
+                depths.push(Some(depth));
+                depth += 1;
+            }
+            b')' => {
+                depth = depth.saturating_sub(1);
+                depths.push(Some(depth));
+            }
+            _ => depths.push(Some(depth)),
+        }
+    }
+
+    depths
+}
+
+/// First identifier of a SQLite definition, without its quotes
+fn leading_identifier(definition: &str) -> &str {
+    let closing = match definition.chars().next() {
+        Some('"') => '"',
+        Some('`') => '`',
+        Some('[') => ']',
+        _ => return definition.split_whitespace().next().unwrap_or_default(),
+    };
+    let rest = &definition[1..];
+    rest.find(closing).map_or(rest, |end| &rest[..end])
+}
+
+fn is_identifier_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+/// Find Postgres backends that are waiting on a lock held by another backend
+pub async fn find_blocked_queries(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<BlockedQuery>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Ok(Vec::new());
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    // A lock that is not granted is blocked by every granted lock on the same object
+    let query = "SELECT
+            blocked.pid::integer AS blocked_pid,
+            COALESCE(blocked_activity.query, '')::text AS blocked_query,
+            blocking.pid::integer AS blocking_pid,
+            COALESCE(blocking_activity.query, '')::text AS blocking_query,
+            COALESCE(EXTRACT(EPOCH FROM (now() - blocked_activity.query_start)), 0)::float8 AS blocked_duration_secs,
+            blocked.locktype::text AS lock_type
+        FROM pg_locks blocked
+        JOIN pg_stat_activity blocked_activity ON blocked_activity.pid = blocked.pid
+        JOIN pg_locks blocking
+            ON blocking.locktype = blocked.locktype
+            AND blocking.database IS NOT DISTINCT FROM blocked.database
+            AND blocking.relation IS NOT DISTINCT FROM blocked.relation
+            AND blocking.page IS NOT DISTINCT FROM blocked.page
+            AND blocking.tuple IS NOT DISTINCT FROM blocked.tuple
+            AND blocking.virtualxid IS NOT DISTINCT FROM blocked.virtualxid
+            AND blocking.transactionid IS NOT DISTINCT FROM blocked.transactionid
+            AND blocking.classid IS NOT DISTINCT FROM blocked.classid
+            AND blocking.objid IS NOT DISTINCT FROM blocked.objid
+            AND blocking.objsubid IS NOT DISTINCT FROM blocked.objsubid
+            AND blocking.pid <> blocked.pid
+        JOIN pg_stat_activity blocking_activity ON blocking_activity.pid = blocking.pid
+        WHERE NOT blocked.granted AND blocking.granted
+        ORDER BY blocked_duration_secs DESC";
+
+    let rows = sqlx::query(query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(DatabaseError::from)?;
+
+    let mut blocked = Vec::new();
+    for row in rows {
+        blocked.push(BlockedQuery {
+            blocked_pid: row
+                .try_get("blocked_pid")
+                .map_err(DatabaseError::from)?,
+            blocked_query: row.try_get("blocked_query").unwrap_or_default(),
+            blocking_pid: row
+                .try_get("blocking_pid")
+                .map_err(DatabaseError::from)?,
+            blocking_query: row.try_get("blocking_query").unwrap_or_default(),
+            blocked_duration_secs: row.try_get("blocked_duration_secs").unwrap_or(0.0),
+            lock_type: row.try_get("lock_type").unwrap_or_default(),
+        });
+    }
+    Ok(blocked)
+}
+
+/// Aggregate waiting Postgres locks by the table they are waiting on
+pub async fn summarize_lock_waits(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<TableLockWaitStats>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Ok(Vec::new());
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let query = "SELECT
+            c.relname::text AS table_name,
+            n.nspname::text AS schema,
+            COUNT(*)::bigint AS waiting_queries,
+            COALESCE(MAX(EXTRACT(EPOCH FROM (now() - a.query_start))), 0)::float8 AS max_wait_secs,
+            string_agg(DISTINCT l.mode, ',')::text AS lock_modes
+        FROM pg_locks l
+        JOIN pg_class c ON c.oid = l.relation
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        JOIN pg_stat_activity a ON a.pid = l.pid
+        WHERE NOT l.granted
+        GROUP BY c.relname, n.nspname
+        ORDER BY waiting_queries DESC, max_wait_secs DESC";
+
+    let rows = sqlx::query(query)
+        .fetch_all(&*pool)
+        .await
+        .map_err(DatabaseError::from)?;
+
+    let mut stats = Vec::new();
+    for row in rows {
+        let lock_modes: String = row.try_get("lock_modes").unwrap_or_default();
+
+        stats.push(TableLockWaitStats {
+            table_name: row
+                .try_get("table_name")
+                .map_err(DatabaseError::from)?,
+            schema: row
+                .try_get("schema")
+                .map_err(DatabaseError::from)?,
+            waiting_queries: row.try_get("waiting_queries").unwrap_or(0),
+            max_wait_secs: row.try_get("max_wait_secs").unwrap_or(0.0),
+            lock_modes: lock_modes
+                .split(',')
+                .filter(|mode| !mode.is_empty())
+                .map(|mode| mode.to_string())
+                .collect(),
+        });
+    }
+    Ok(stats)
+}
+
+/// Ratio of processed to total blocks, 0.0 when the total is not known yet
+fn progress_ratio(done: i64, total: i64) -> f32 {
+    if total <= 0 {
+        return 0.0;
+    }
+    (done as f32 / total as f32).min(1.0)
+}
+
+/// Get the replication topology of a MySQL server
+///
+/// The result holds the channels this server replicates from, read from
+/// performance_schema on MySQL 8.0+ and from `SHOW SLAVE STATUS` on older
+/// servers, followed by the replicas connected to this server. Those are the
+/// `Binlog Dump` threads of the process list, with the ports the replicas
+/// registered through `SHOW REPLICAS` (`SHOW SLAVE HOSTS` before 8.0.22).
+pub async fn get_mysql_replicas(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<ReplicaInfo>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::MySQL) {
+        return Err(DatabaseError::UnsupportedType);
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let version_row = sqlx::query("SELECT VERSION()")
+        .fetch_one(&*pool)
+        .await
+        .map_err(DatabaseError::from)?;
+    let version: String = version_row
+        .try_get(0)
+        .map_err(DatabaseError::from)?;
+    let version_number = mysql_version_number(&version);
+
+    let mut replicas = if version_number >= (8, 0, 0) {
+        get_mysql_replicas_performance_schema(&pool).await?
+    } else {
+        get_mysql_replicas_legacy(&pool).await?
+    };
+
+    let hosts_query = if version_number >= (8, 0, 22) && !version.contains("MariaDB") {
+        "SHOW REPLICAS"
+    } else {
+        "SHOW SLAVE HOSTS"
+    };
+    replicas.extend(get_mysql_connected_replicas(&pool, hosts_query).await?);
+    Ok(replicas)
+}
+
+/// Major, minor and patch number of a MySQL `VERSION()` string such as `8.0.35-log`
+fn mysql_version_number(version: &str) -> (u32, u32, u32) {
+    let mut parts = version.split(['.', '-']).map(|part| part.parse().unwrap_or(0));
+    (
+        parts.next().unwrap_or(0),
+        parts.next().unwrap_or(0),
+        parts.next().unwrap_or(0),
+    )
+}
+
+/// Host of a process list entry, without the client port of a TCP connection
+fn process_host(host: &str) -> &str {
+    match host.rsplit_once(':') {
+        Some((address, port)) if port.parse::<u16>().is_ok() => address,
+        _ => host,
+    }
+}
+
+/// Replication channels from performance_schema (MySQL 8.0+)
+async fn get_mysql_replicas_performance_schema(
+    pool: &sqlx::AnyPool,
+) -> Result<Vec<ReplicaInfo>, DatabaseError> {
+    let query = "SELECT
+            CAST(cc.HOST AS CHAR) AS replica_host,
+            CAST(cc.PORT AS SIGNED) AS replica_port,
+            CAST(cc.USER AS CHAR) AS replica_user,
+            CAST(cs.SERVICE_STATE AS CHAR) AS io_state,
+            CAST(aps.SERVICE_STATE AS CHAR) AS sql_state,
+            CAST((
+                SELECT MAX(TIMESTAMPDIFF(SECOND, w.LAST_APPLIED_TRANSACTION_ORIGINAL_COMMIT_TIMESTAMP, NOW()))
+                FROM performance_schema.replication_applier_status_by_worker w
+                WHERE w.CHANNEL_NAME = cc.CHANNEL_NAME
+            ) AS SIGNED) AS seconds_behind
+        FROM performance_schema.replication_connection_configuration cc
+        LEFT JOIN performance_schema.replication_connection_status cs
+            ON cs.CHANNEL_NAME = cc.CHANNEL_NAME
+        LEFT JOIN performance_schema.replication_applier_status aps
+            ON aps.CHANNEL_NAME = cc.CHANNEL_NAME";
+
+    let rows = sqlx::query(query)
+        .fetch_all(pool)
+        .await
+        .map_err(DatabaseError::from)?;
+
+    let mut replicas = Vec::new();
+    for row in rows {
+        let io_state: Option<String> = row.try_get("io_state").ok();
+        let sql_state: Option<String> = row.try_get("sql_state").ok();
+
+        replicas.push(ReplicaInfo {
+            replica_host: row.try_get("replica_host").unwrap_or_default(),
+            replica_port: get_mysql_int(&row, "replica_port").unwrap_or(3306) as u16,
+            replica_user: row.try_get("replica_user").unwrap_or_default(),
+            seconds_behind_master: get_mysql_int(&row, "seconds_behind"),
+            replica_io_running: io_state.as_deref() == Some("ON"),
+            replica_sql_running: sql_state.as_deref() == Some("ON"),
+        });
+    }
+
+    Ok(replicas)
+}
+
+/// Replication channels from SHOW SLAVE STATUS (MySQL < 8.0)
+async fn get_mysql_replicas_legacy(
+    pool: &sqlx::AnyPool,
+) -> Result<Vec<ReplicaInfo>, DatabaseError> {
+    let mut replicas = Vec::new();
+
+    let status_rows = sqlx::query("SHOW SLAVE STATUS")
+        .fetch_all(pool)
+        .await
+        .map_err(DatabaseError::from)?;
+
+    for row in status_rows {
+        let io_running: String = row.try_get("Slave_IO_Running").unwrap_or_default();
+        let sql_running: String = row.try_get("Slave_SQL_Running").unwrap_or_default();
+
+        replicas.push(ReplicaInfo {
+            replica_host: row.try_get("Master_Host").unwrap_or_default(),
+            replica_port: get_mysql_int(&row, "Master_Port").unwrap_or(3306) as u16,
+            replica_user: row.try_get("Master_User").unwrap_or_default(),
+            seconds_behind_master: get_mysql_int(&row, "Seconds_Behind_Master"),
+            replica_io_running: io_running.eq_ignore_ascii_case("Yes"),
+            replica_sql_running: sql_running.eq_ignore_ascii_case("Yes"),
+        });
+    }
+
+    Ok(replicas)
+}
+
+/// Replicas connected to this server, from its `Binlog Dump` threads
+///
+/// `hosts_query` lists the registered replicas, which give the port a replica
+/// listens on. Without the PROCESS privilege the dump threads of other users are
+/// hidden, so the registered replicas are returned as they are.
+async fn get_mysql_connected_replicas(
+    pool: &sqlx::AnyPool,
+    hosts_query: &str,
+) -> Result<Vec<ReplicaInfo>, DatabaseError> {
+    let host_rows = sqlx::query(hosts_query)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, hosts_query))?;
+    let registered: Vec<(String, u16)> = host_rows
+        .iter()
+        .map(|row| {
+            (
+                row.try_get("Host").unwrap_or_default(),
+                get_mysql_int(row, "Port").unwrap_or(3306) as u16,
+            )
+        })
+        .collect();
+
+    let dump_query = "SELECT CAST(USER AS CHAR) AS user, CAST(HOST AS CHAR) AS host
+        FROM information_schema.PROCESSLIST
+        WHERE COMMAND IN ('Binlog Dump', 'Binlog Dump GTID')";
+    let dump_rows = sqlx::query(dump_query)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, dump_query))?;
+
+    // A connected replica's lag is only visible on the replica itself
+    let connected = |replica_host: String, replica_port: u16, replica_user: String| ReplicaInfo {
+        replica_host,
+        replica_port,
+        replica_user,
+        seconds_behind_master: None,
+        replica_io_running: true,
+        replica_sql_running: true,
+    };
+
+    if dump_rows.is_empty() {
+        return Ok(registered
+            .into_iter()
+            .map(|(host, port)| connected(host, port, String::new()))
+            .collect());
+    }
+
+    Ok(dump_rows
+        .iter()
+        .map(|row| {
+            let host: String = row.try_get("host").unwrap_or_default();
+            let host = process_host(&host).to_string();
+            let port = registered
+                .iter()
+                .find(|(registered_host, _)| *registered_host == host)
+                .map_or(3306, |(_, port)| *port);
+            connected(host, port, row.try_get("user").unwrap_or_default())
+        })
+        .collect())
+}
+
+/// Read an integer column that MySQL may report as signed, unsigned or text
+fn get_mysql_int(row: &sqlx::any::AnyRow, column: &str) -> Option<i64> {
+    row.try_get::<i64, _>(column)
+        .ok()
+        .or_else(|| row.try_get::<i32, _>(column).ok().map(i64::from))
+        .or_else(|| {
+            row.try_get::<String, _>(column)
+                .ok()
+                .and_then(|v| v.parse().ok())
+        })
+}
+
+/// PRAGMAs inspected by `get_sqlite_pragma_report`
+const ANALYZED_PRAGMAS: &[&str] = &["journal_mode", "synchronous", "cache_size", "foreign_keys", "page_size"];
+
+/// Recommended page cache size, in pages
+const RECOMMENDED_CACHE_PAGES: i64 = 10000;
+
+/// Inspect performance-related PRAGMAs of a SQLite database and suggest better values
+pub async fn get_sqlite_pragma_report(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<SqlitePragmaReport, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::SQLite) {
+        return Err(DatabaseError::UnsupportedType);
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+    let mut conn = acquire_connection(&pool).await?;
+
+    let mut current_settings = HashMap::new();
+    for pragma in ANALYZED_PRAGMAS {
+        let value = read_pragma(&mut conn, pragma).await?;
+        current_settings.insert(pragma.to_string(), value);
+    }
+
+    let recommendations = recommend_sqlite_pragmas(&current_settings);
+
+    Ok(SqlitePragmaReport {
+        current_settings,
+        recommendations,
+    })
+}
+
+/// Take one connection from the pool, so that PRAGMAs which only apply to the
+/// connection they run on are all read from the same one
+async fn acquire_connection(
+    pool: &sqlx::AnyPool,
+) -> Result<sqlx::pool::PoolConnection<sqlx::Any>, DatabaseError> {
+    pool.acquire()
+        .await
+        .map_err(|e| DatabaseError::ConnectionError(e.to_string()))
+}
+
+/// Read the value of a PRAGMA as text
+async fn read_pragma(
+    conn: &mut sqlx::AnyConnection,
+    pragma: &str,
+) -> Result<String, DatabaseError> {
+    let row = sqlx::query(&format!("PRAGMA {}", pragma))
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(DatabaseError::from)?;
+
+    // Some PRAGMAs return text, others integers
+    row.try_get::<String, _>(0)
+        .or_else(|_| row.try_get::<i64, _>(0).map(|v| v.to_string()))
+        .map_err(DatabaseError::from)
+}
+
+/// Get the journal mode and related PRAGMAs of a SQLite database
+pub async fn get_sqlite_pragmas(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<SqlitePragmas, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::SQLite) {
+        return Err(DatabaseError::UnsupportedType);
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+    let mut conn = acquire_connection(&pool).await?;
+
+    let synchronous = match read_pragma(&mut conn, "synchronous").await?.as_str() {
+        "0" => "OFF".to_string(),
+        "1" => "NORMAL".to_string(),
+        "2" => "FULL".to_string(),
+        "3" => "EXTRA".to_string(),
+        other => other.to_string(),
+    };
+    let number = |value: String| -> Result<i64, DatabaseError> {
+        value
+            .parse()
+            .map_err(|_| DatabaseError::QueryError(format!("Unexpected PRAGMA value: {}", value)))
+    };
+
+    Ok(SqlitePragmas {
+        journal_mode: read_pragma(&mut conn, "journal_mode").await?,
+        synchronous,
+        page_size: number(read_pragma(&mut conn, "page_size").await?)? as u32,
+        cache_size: number(read_pragma(&mut conn, "cache_size").await?)?,
+        foreign_keys: read_pragma(&mut conn, "foreign_keys").await? == "1",
+        wal_autocheckpoint: number(read_pragma(&mut conn, "wal_autocheckpoint").await?)?.max(0)
+            as u32,
+    })
+}
+
+/// Switch the journal mode of a SQLite database and return the mode now in use
+///
+/// WAL is recorded in the database file and applies to every connection, and so
+/// does leaving WAL for DELETE. MEMORY, OFF and TRUNCATE are per-connection: they
+/// only last as long as the connection that ran the PRAGMA, and new connections
+/// start in the mode recorded in the file again. The pool is re-created after the
+/// switch, since connections opened before it keep reporting the mode they
+/// started with. SQLite keeps the current mode when it cannot switch, for instance
+/// WAL on an in-memory database, so the returned mode can differ from the one
+/// asked for.
+pub async fn set_journal_mode(
+    database_id: &str,
+    mode: JournalMode,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<String, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::SQLite) {
+        return Err(DatabaseError::UnsupportedType);
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    // PRAGMA does not take bind parameters; the value comes from the enum
+    let mode_name = match mode {
+        JournalMode::Delete => "DELETE",
+        JournalMode::Wal => "WAL",
+        JournalMode::Memory => "MEMORY",
+        JournalMode::Off => "OFF",
+        JournalMode::Truncate => "TRUNCATE",
+    };
+    let mut conn = acquire_connection(&pool).await?;
+    let current = read_pragma(&mut conn, &format!("journal_mode = {}", mode_name)).await;
+    drop(conn);
+    drop(pool);
+    pools.evict(database_id).await;
+    current
+}
+
+/// Compare PRAGMA values against recommended settings
+fn recommend_sqlite_pragmas(settings: &HashMap<String, String>) -> Vec<PragmaRecommendation> {
+    let mut recommendations = Vec::new();
+    let get = |name: &str| settings.get(name).cloned().unwrap_or_default();
+
+    let journal_mode = get("journal_mode");
+    if !journal_mode.eq_ignore_ascii_case("wal") {
+        recommendations.push(PragmaRecommendation {
+            pragma_name: "journal_mode".to_string(),
+            current_value: journal_mode,
+            recommended_value: "WAL".to_string(),
+            reason: "Write-ahead logging lets readers and a writer work concurrently and is usually faster".to_string(),
+        });
+    }
+
+    // synchronous is reported as 0 (OFF), 1 (NORMAL), 2 (FULL) or 3 (EXTRA)
+    let synchronous = get("synchronous");
+    if synchronous != "1" {
+        let reason = if synchronous == "0" {
+            "OFF risks database corruption on power loss; NORMAL is safe in WAL mode"
+        } else {
+            "NORMAL avoids an fsync on every transaction and is safe in WAL mode"
+        };
+        recommendations.push(PragmaRecommendation {
+            pragma_name: "synchronous".to_string(),
+            current_value: synchronous,
+            recommended_value: "NORMAL".to_string(),
+            reason: reason.to_string(),
+        });
+    }
+
+    // A negative cache_size is a size in KiB rather than a number of pages
+    let cache_size = get("cache_size");
+    let page_size: i64 = get("page_size").parse().unwrap_or(4096).max(1);
+    let cache_pages = match cache_size.parse::<i64>() {
+        Ok(size) if size < 0 => -size * 1024 / page_size,
+        Ok(size) => size,
+        Err(_) => 0,
+    };
+    if cache_pages < RECOMMENDED_CACHE_PAGES {
+        recommendations.push(PragmaRecommendation {
+            pragma_name: "cache_size".to_string(),
+            current_value: cache_size,
+            recommended_value: RECOMMENDED_CACHE_PAGES.to_string(),
+            reason: "A larger page cache reduces disk reads for frequently accessed data".to_string(),
+        });
+    }
+
+    let foreign_keys = get("foreign_keys");
+    if foreign_keys != "1" {
+        recommendations.push(PragmaRecommendation {
+            pragma_name: "foreign_keys".to_string(),
+            current_value: foreign_keys,
+            recommended_value: "ON".to_string(),
+            reason: "SQLite does not enforce foreign key constraints unless this is enabled".to_string(),
+        });
+    }
+
+    recommendations
+}
+
+/// Number of table schemas fetched concurrently when streaming
+const SCHEMA_STREAM_CONCURRENCY: usize = 10;
+
+/// Fetch all table schemas, emitting a `schema-table-loaded` event for each table as
+/// soon as it is available and `schema-fetch-complete` at the end
+pub async fn stream_table_schemas(
+    database_id: &str,
+    window: &tauri::Window,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<(), DatabaseError> {
+    let creds = store.get(database_id)?;
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let table_names = get_all_table_names(&creds, &pool).await?;
+    let table_count = table_names.len();
+
+    // Built up front for the same reason as in `get_all_table_schemas`
+    let fetches: Vec<_> = table_names
+        .iter()
+        .map(|(schema, table_name)| {
+            get_single_table_schema(&creds, &pool, table_name, schema.as_deref())
+        })
+        .collect();
+    let mut schemas = stream::iter(fetches).buffer_unordered(SCHEMA_STREAM_CONCURRENCY);
+
+    let mut loaded = 0;
+    while let Some(schema) = schemas.next().await {
+        let schema = schema?;
+        loaded += 1;
+
+        window
+            .emit(
+                "schema-table-loaded",
+                SchemaTableLoadedEvent {
+                    table_name: schema.table_name.clone(),
+                    schema,
+                    progress: loaded as f32 / table_count as f32,
+                },
+            )
+            .map_err(|e| DatabaseError::Other(format!("Failed to emit event: {}", e)))?;
+    }
+
+    window
+        .emit(
+            "schema-fetch-complete",
+            SchemaFetchCompleteEvent {
+                database_id: database_id.to_string(),
+                table_count,
+            },
+        )
+        .map_err(|e| DatabaseError::Other(format!("Failed to emit event: {}", e)))?;
+
+    Ok(())
+}
+
+// Tauri commands for metadata
+#[tauri::command]
+pub async fn get_database_tables(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<TableInfo>, DatabaseErrorContext> {
+    let result = get_tables(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_tables"))
+}
+
+#[tauri::command]
+pub async fn get_database_table_schema(
+    database_id: String,
+    table_names: String,
+    schema: Option<String>,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<TableSchema>, DatabaseErrorContext> {
+    let result =
+        get_table_schema(&database_id, &table_names, schema.as_deref(), &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_table_schema"))
+}
+
+#[tauri::command]
+pub async fn get_database_relationships(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<Relationship>, DatabaseErrorContext> {
+    let result = get_relationships(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_relationships"))
+}
+
+#[tauri::command]
+pub async fn get_all_databases_relationships(
+    database_ids: Vec<String>,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<HashMap<String, DatabaseRelationships>, DatabaseErrorContext> {
+    let results = get_relationships_for_databases(&database_ids, &store, &pools).await;
+    Ok(results
+        .into_iter()
+        .map(|(database_id, result)| {
+            let entry = match result {
+                Ok(relationships) => DatabaseRelationships {
+                    relationships,
+                    error: None,
+                },
+                Err(e) => DatabaseRelationships {
+                    relationships: Vec::new(),
+                    error: Some(e.to_string()),
+                },
+            };
+            (database_id, entry)
+        })
+        .collect())
+}
+
+// Progress commands are meant to be polled by the frontend (e.g. every 5 seconds)
+// while a maintenance operation is running.
+#[tauri::command]
+pub async fn get_database_vacuum_progress(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<VacuumProgress>, DatabaseErrorContext> {
+    let result = get_vacuum_progress(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_vacuum_progress"))
+}
+
+#[tauri::command]
+pub async fn get_database_analyze_progress(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<AnalyzeProgress>, DatabaseErrorContext> {
+    let result = get_analyze_progress(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_analyze_progress"))
+}
+
+#[tauri::command]
+pub async fn get_database_mysql_replicas(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<ReplicaInfo>, DatabaseErrorContext> {
+    let result = get_mysql_replicas(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_mysql_replicas"))
+}
+
+#[tauri::command]
+pub async fn analyze_sqlite_pragmas(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<SqlitePragmaReport, DatabaseErrorContext> {
+    let result = get_sqlite_pragma_report(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "analyze_sqlite_pragmas"))
+}
+
+#[tauri::command]
+pub async fn get_database_sqlite_pragmas(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<SqlitePragmas, DatabaseErrorContext> {
+    let result = get_sqlite_pragmas(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_sqlite_pragmas"))
+}
+
+#[tauri::command]
+pub async fn set_sqlite_journal_mode(
+    database_id: String,
+    mode: JournalMode,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<String, DatabaseErrorContext> {
+    let result = set_journal_mode(&database_id, mode, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "set_journal_mode"))
+}
+
+#[tauri::command]
+pub async fn fetch_schema_streaming(
+    database_id: String,
+    window: tauri::Window,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<(), DatabaseErrorContext> {
+    let result = stream_table_schemas(&database_id, &window, &store, &pools).await;
+    recheck_on_connection_error(window.app_handle(), &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "fetch_schema_streaming"))
+}
+
+#[tauri::command]
+pub async fn get_table_dead_tuples(
+    database_id: String,
+    threshold_ratio: Option<f32>,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<TableDeadTupleInfo>, DatabaseErrorContext> {
+    let result = get_dead_tuples(&database_id, threshold_ratio, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_table_dead_tuples"))
 }
 
 #[tauri::command]
-pub async fn get_database_relationships(
+pub async fn get_database_size(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<DatabaseSizeInfo, DatabaseErrorContext> {
+    let result = collect_database_size(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "collect_database_size"))
+}
+
+#[tauri::command]
+pub async fn get_table_statistics(
+    database_id: String,
+    table_name: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<TableStatistics, DatabaseErrorContext> {
+    let result = collect_table_statistics(&database_id, &table_name, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "collect_table_statistics")
+                .with_table(&table_name)
+        })
+}
+
+#[tauri::command]
+pub async fn get_blocked_queries(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<BlockedQuery>, DatabaseErrorContext> {
+    let result = find_blocked_queries(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "find_blocked_queries"))
+}
+
+#[tauri::command]
+pub async fn get_lock_wait_summary(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<TableLockWaitStats>, DatabaseErrorContext> {
+    let result = summarize_lock_waits(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "summarize_lock_waits"))
+}
+
+#[tauri::command]
+pub async fn get_database_indexes(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<IndexInfo>, DatabaseErrorContext> {
+    let result = get_indexes(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_indexes"))
+}
+
+#[tauri::command]
+pub async fn get_table_row_count(
+    database_id: String,
+    table_name: String,
+    schema: Option<String>,
+    exact: bool,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<TableRowCountResult, DatabaseErrorContext> {
+    let result = count_table_rows(
+        &database_id,
+        &table_name,
+        schema.as_deref(),
+        exact,
+        &store,
+        &pools,
+    )
+    .await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "count_table_rows").with_table(&table_name)
+        })
+}
+
+#[tauri::command]
+pub async fn get_column_statistics(
+    database_id: String,
+    table_name: String,
+    column_name: String,
+    schema: Option<String>,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<ColumnStatistics, DatabaseErrorContext> {
+    let result = collect_column_statistics(
+        &database_id,
+        &table_name,
+        &column_name,
+        schema.as_deref(),
+        &store,
+        &pools,
+    )
+    .await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "collect_column_statistics")
+                .with_table(&table_name)
+        })
+}
+
+#[tauri::command]
+pub async fn get_all_table_row_counts(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<TableInfo>, DatabaseErrorContext> {
+    let result = count_all_table_rows(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "count_all_table_rows"))
+}
+
+#[tauri::command]
+pub async fn get_table_ddl(
+    database_id: String,
+    table_name: String,
+    schema: Option<String>,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<TableDDL, DatabaseErrorContext> {
+    let result =
+        generate_table_ddl(&database_id, &table_name, schema.as_deref(), &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "generate_table_ddl")
+                .with_table(&table_name)
+        })
+}
+
+#[tauri::command]
+pub async fn get_database_routines(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<RoutineInfo>, DatabaseErrorContext> {
+    let result = get_routines(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_routines"))
+}
+
+#[tauri::command]
+pub async fn get_database_enum_types(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<EnumTypeInfo>, DatabaseErrorContext> {
+    let result = get_enum_types(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_enum_types"))
+}
+
+#[tauri::command]
+pub async fn get_database_sequences(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<SequenceInfo>, DatabaseErrorContext> {
+    let result = get_sequences(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_sequences"))
+}
+
+#[tauri::command]
+pub async fn get_database_schemas(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<SchemaInfo>, DatabaseErrorContext> {
+    let result = get_schemas(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_schemas"))
+}
+
+#[tauri::command]
+pub async fn get_database_triggers(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<TriggerInfo>, DatabaseErrorContext> {
+    let result = get_triggers(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_triggers"))
+}
+
+#[tauri::command]
+pub async fn get_database_check_constraints(
+    database_id: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<CheckConstraintInfo>, DatabaseErrorContext> {
+    let result = get_check_constraints(&database_id, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_check_constraints"))
+}
+
+#[tauri::command]
+pub async fn diff_database_schemas(
+    source_id: String,
+    target_id: String,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<SchemaDiff, DatabaseErrorContext> {
+    diff_schemas(&source_id, &target_id, &store, &pools)
+        .await
+        .map_err(|e| with_context(e, Some(source_id.as_str()), "diff_schemas"))
+}
+
+#[tauri::command]
+pub async fn find_column_across_tables(
+    database_id: String,
+    column_name: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<ColumnSearchResult>, DatabaseErrorContext> {
+    let result = find_column_usages(&database_id, &column_name, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "find_column_usages"))
+}
+
+#[tauri::command]
+pub async fn search_database_schema(
+    database_id: String,
+    query: String,
+    search_tables: bool,
+    search_columns: bool,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<SchemaSearchResult, DatabaseErrorContext> {
+    let result =
+        search_schema(&database_id, &query, search_tables, search_columns, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "search_schema"))
+}
+
+#[tauri::command]
+pub async fn get_dependent_tables(
+    database_id: String,
+    table_name: String,
+    schema: Option<String>,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<DependencyInfo>, DatabaseErrorContext> {
+    let result =
+        get_table_dependents(&database_id, &table_name, schema.as_deref(), &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "get_table_dependents")
+                .with_table(&table_name)
+        })
+}
+
+#[tauri::command]
+pub async fn find_table_usages(
     database_id: String,
+    target_table: String,
+    schema: Option<String>,
+    app: AppHandle,
     store: State<'_, CredentialStore>,
-) -> Result<Vec<Relationship>, String> {
-    get_relationships(&database_id, &store)
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<TableReference>, DatabaseErrorContext> {
+    let result = find_table_references(
+        &database_id,
+        &target_table,
+        schema.as_deref(),
+        &store,
+        &pools,
+    )
+    .await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "find_table_references")
+                .with_table(&target_table)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_fixtures::SqliteFixture;
+
+    #[test]
+    fn test_parse_sqlite_trigger() {
+        assert_eq!(
+            parse_sqlite_trigger(
+                "CREATE TRIGGER after_insert_audit AFTER INSERT ON users BEGIN SELECT 1; END"
+            ),
+            ("AFTER".to_string(), "INSERT".to_string())
+        );
+        assert_eq!(
+            parse_sqlite_trigger("create trigger v_upd instead of update of name on v begin end"),
+            ("INSTEAD OF".to_string(), "UPDATE".to_string())
+        );
+        assert_eq!(
+            parse_sqlite_trigger("CREATE TRIGGER cleanup DELETE ON orders BEGIN SELECT 1; END"),
+            ("BEFORE".to_string(), "DELETE".to_string())
+        );
+    }
+
+    #[test]
+    fn test_search_columns_ignores_case() {
+        let column = |name: &str| ColumnInfo {
+            name: name.to_string(),
+            data_type: "text".to_string(),
+            is_nullable: true,
+            is_primary_key: false,
+            default_value: None,
+            character_max_length: None,
+            is_unique: false,
+            unique_constraint_name: None,
+            is_inherited: false,
+        };
+        let schemas = vec![
+            TableSchema {
+                table_name: "users".to_string(),
+                schema: Some("public".to_string()),
+                columns: vec![column("id"), column("Status")],
+            },
+            TableSchema {
+                table_name: "orders".to_string(),
+                schema: Some("public".to_string()),
+                columns: vec![column("status"), column("status_code")],
+            },
+        ];
+
+        let results = search_columns(&schemas, "STATUS");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].table_name, "users");
+        assert_eq!(results[0].column.name, "Status");
+        assert_eq!(results[1].table_name, "orders");
+    }
+
+    #[test]
+    fn test_rank_by_match() {
+        let names = vec!["order_items", "orders", "customer_orders", "order", "users"];
+        let ranked = rank_by_match(names, "ORDER", |name| *name);
+        assert_eq!(ranked, vec!["order", "order_items", "orders", "customer_orders"]);
+
+        assert!(rank_by_match(vec!["users"], "orders", |name| *name).is_empty());
+    }
+
+    #[test]
+    fn test_infer_self_referential_relationships() {
+        let column = |name: &str, is_primary_key: bool| ColumnInfo {
+            name: name.to_string(),
+            data_type: "integer".to_string(),
+            is_nullable: !is_primary_key,
+            is_primary_key,
+            default_value: None,
+            character_max_length: None,
+            is_unique: false,
+            unique_constraint_name: None,
+            is_inherited: false,
+        };
+        let schemas = vec![TableSchema {
+            table_name: "categories".to_string(),
+            schema: None,
+            columns: vec![column("id", true), column("parent_id", false)],
+        }];
+
+        let relationships = infer_relationships(&schemas);
+        assert_eq!(relationships.len(), 1);
+        assert_eq!(relationships[0].foreign_table, "categories");
+        assert_eq!(relationships[0].foreign_column, "id");
+        assert!(relationships[0].is_self_referential);
+        assert_eq!(relationships[0].relationship_cardinality.as_deref(), Some("self"));
+    }
+
+    #[test]
+    fn test_remove_duplicate_inferred() {
+        let column = |name: &str, is_primary_key: bool| ColumnInfo {
+            name: name.to_string(),
+            data_type: "integer".to_string(),
+            is_nullable: false,
+            is_primary_key,
+            default_value: None,
+            character_max_length: None,
+            is_unique: false,
+            unique_constraint_name: None,
+            is_inherited: false,
+        };
+        let schemas = vec![
+            TableSchema {
+                table_name: "users".to_string(),
+                schema: None,
+                columns: vec![column("id", true)],
+            },
+            TableSchema {
+                table_name: "orders".to_string(),
+                schema: None,
+                columns: vec![column("id", true), column("user_id", false)],
+            },
+        ];
+        let mut relationships = infer_relationships(&schemas);
+        assert_eq!(relationships.len(), 1);
+
+        let mut explicit = relationships[0].clone();
+        explicit.source = RelationshipSource::Explicit;
+        explicit.constraint_name = Some("orders_user_id_fkey".to_string());
+        relationships.insert(0, explicit);
+
+        remove_duplicate_inferred(&mut relationships);
+        assert_eq!(relationships.len(), 1);
+        assert_eq!(relationships[0].source, RelationshipSource::Explicit);
+    }
+
+    #[test]
+    fn test_detect_junction_tables() {
+        let column = |name: &str, is_primary_key: bool| ColumnInfo {
+            name: name.to_string(),
+            data_type: "integer".to_string(),
+            is_nullable: false,
+            is_primary_key,
+            default_value: None,
+            character_max_length: None,
+            is_unique: false,
+            unique_constraint_name: None,
+            is_inherited: false,
+        };
+        let table = |name: &str, columns: Vec<ColumnInfo>| TableSchema {
+            table_name: name.to_string(),
+            schema: Some("public".to_string()),
+            columns,
+        };
+        let mut archived = table("user_roles", vec![column("id", true)]);
+        archived.schema = Some("archive".to_string());
+        let schemas = vec![
+            table("users", vec![column("id", true)]),
+            table("roles", vec![column("id", true)]),
+            table("teams", vec![column("id", true)]),
+            table(
+                "user_roles",
+                vec![
+                    column("user_id", true),
+                    column("role_id", true),
+                    column("team_id", false),
+                ],
+            ),
+            table(
+                "memberships",
+                vec![
+                    column("id", true),
+                    column("user_id", false),
+                    column("team_id", false),
+                    column("joined_at", false),
+                ],
+            ),
+            table(
+                "orders",
+                vec![
+                    column("id", true),
+                    column("user_id", false),
+                    column("team_id", false),
+                    column("total", false),
+                    column("status", false),
+                ],
+            ),
+            table("audit", vec![column("id", true), column("user_id", false)]),
+            archived,
+        ];
+        let relationships = infer_relationships(&schemas);
+
+        let many_to_many = detect_junction_tables(&schemas, &relationships);
+        // The composite key picks users/roles out of the three referencing columns,
+        // and a table of the same name in another schema is not a junction
+        let user_roles: Vec<_> = many_to_many
+            .iter()
+            .filter(|r| r.junction_table.as_deref() == Some("user_roles"))
+            .collect();
+        assert_eq!(user_roles.len(), 2);
+        assert!(user_roles
+            .iter()
+            .any(|r| r.table_name == "users" && r.foreign_table == "roles"));
+        assert!(user_roles
+            .iter()
+            .any(|r| r.table_name == "roles" && r.foreign_table == "users"));
+        assert!(user_roles
+            .iter()
+            .all(|r| r.schema.as_deref() == Some("public")));
+
+        // A surrogate key and one extra column still make a junction, a wide table does not
+        let memberships: Vec<_> = many_to_many
+            .iter()
+            .filter(|r| r.junction_table.as_deref() == Some("memberships"))
+            .collect();
+        assert_eq!(memberships.len(), 2);
+        assert_eq!(memberships[0].confidence.as_deref(), Some("medium"));
+        assert_eq!(many_to_many.len(), 4);
+
+        assert!(is_compound_name("user_roles", "users", "roles"));
+        assert!(is_compound_name("RoleUser", "users", "roles"));
+        assert!(!is_compound_name("audit", "users", "roles"));
+    }
+
+    #[test]
+    fn test_parse_mysql_enum_values() {
+        assert_eq!(
+            parse_mysql_enum_values("enum('small','it''s','a,b','')"),
+            vec!["small", "it's", "a,b", ""]
+        );
+        assert!(parse_mysql_enum_values("enum()").is_empty());
+    }
+
+    #[test]
+    fn test_mysql_replica_helpers() {
+        assert_eq!(mysql_version_number("8.0.35-log"), (8, 0, 35));
+        assert_eq!(mysql_version_number("5.7.44"), (5, 7, 44));
+        assert_eq!(mysql_version_number("10.11.6-MariaDB-0+deb12u1"), (10, 11, 6));
+        assert!(mysql_version_number("8.0.21") < (8, 0, 22));
+
+        assert_eq!(process_host("10.0.0.5:51234"), "10.0.0.5");
+        assert_eq!(process_host("replica-1.internal:40022"), "replica-1.internal");
+        assert_eq!(process_host("localhost"), "localhost");
+    }
+
+    #[test]
+    fn test_recommend_sqlite_pragmas() {
+        let settings = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let names = |recs: &[PragmaRecommendation]| -> Vec<String> {
+            recs.iter().map(|r| r.pragma_name.clone()).collect()
+        };
+
+        // Tuned settings produce no recommendations; journal_mode is case-insensitive
+        let tuned = settings(&[
+            ("journal_mode", "WAL"),
+            ("synchronous", "1"),
+            ("cache_size", "10000"),
+            ("page_size", "4096"),
+            ("foreign_keys", "1"),
+        ]);
+        assert!(recommend_sqlite_pragmas(&tuned).is_empty());
+        let mut lower = tuned.clone();
+        lower.insert("journal_mode".to_string(), "wal".to_string());
+        assert!(recommend_sqlite_pragmas(&lower).is_empty());
+
+        // SQLite defaults trigger every recommendation, in a stable order
+        let defaults = settings(&[
+            ("journal_mode", "delete"),
+            ("synchronous", "2"),
+            ("cache_size", "-2000"),
+            ("page_size", "4096"),
+            ("foreign_keys", "0"),
+        ]);
+        let recs = recommend_sqlite_pragmas(&defaults);
+        assert_eq!(
+            names(&recs),
+            vec!["journal_mode", "synchronous", "cache_size", "foreign_keys"]
+        );
+        assert_eq!(recs[0].current_value, "delete");
+        assert_eq!(recs[0].recommended_value, "WAL");
+        assert_eq!(recs[1].recommended_value, "NORMAL");
+        assert!(recs[1].reason.starts_with("NORMAL"));
+        assert_eq!(recs[2].current_value, "-2000");
+        assert_eq!(recs[2].recommended_value, RECOMMENDED_CACHE_PAGES.to_string());
+        assert_eq!(recs[3].recommended_value, "ON");
+
+        // synchronous OFF gets the corruption warning
+        let mut off = tuned.clone();
+        off.insert("synchronous".to_string(), "0".to_string());
+        let recs = recommend_sqlite_pragmas(&off);
+        assert_eq!(names(&recs), vec!["synchronous"]);
+        assert!(recs[0].reason.starts_with("OFF"));
+
+        // A negative cache_size is KiB: -40000 KiB of 4 KiB pages is 10000 pages
+        let mut kib = tuned.clone();
+        kib.insert("cache_size".to_string(), "-40000".to_string());
+        assert!(recommend_sqlite_pragmas(&kib).is_empty());
+        kib.insert("page_size".to_string(), "8192".to_string());
+        assert_eq!(names(&recommend_sqlite_pragmas(&kib)), vec!["cache_size"]);
+
+        // Missing settings are treated as unset
+        let recs = recommend_sqlite_pragmas(&HashMap::new());
+        assert_eq!(recs.len(), 4);
+        assert!(recs.iter().all(|r| r.current_value.is_empty()));
+    }
+
+    #[test]
+    fn test_parse_sqlite_check_constraints() {
+        let sql = "CREATE TABLE \"order items\" (
+            id INTEGER PRIMARY KEY,
+            qty INTEGER NOT NULL CHECK (qty > 0),
+            price DECIMAL(10, 2) CONSTRAINT positive_price CHECK(price >= 0),
+            note TEXT DEFAULT 'check (none)',
+            CHECK (qty * price < 1000000)
+        )";
+
+        let checks = parse_sqlite_check_constraints("order items", sql);
+        let summary: Vec<(&str, Option<&str>, &str)> = checks
+            .iter()
+            .map(|c| (c.name.as_str(), c.column_name.as_deref(), c.expression.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("order items_check1", Some("qty"), "qty > 0"),
+                ("positive_price", Some("price"), "price >= 0"),
+                ("order items_check3", None, "qty * price < 1000000"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_database_size() {
+        let db = SqliteFixture::new();
+
+        let pool = db.pool().await;
+        sqlx::query("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT)")
+            .execute(&*pool)
+            .await
+            .unwrap();
+
+        let size = collect_database_size("sqlite-1", &db.store, &db.pools).await.unwrap();
+        assert!(size.total_size_bytes > 0);
+        assert_eq!(size.total_size_bytes % 512, 0);
+        assert!(size.tables.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_table_ddl() {
+        let db = SqliteFixture::new();
+        let pool = db.pool().await;
+        for sql in [
+            "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT NOT NULL)",
+            "CREATE INDEX items_name ON items (name)",
+        ] {
+            sqlx::query(sql).execute(&*pool).await.unwrap();
+        }
+
+        let ddl = generate_table_ddl("sqlite-1", "items", None, &db.store, &db.pools)
+            .await
+            .unwrap();
+        assert_eq!(
+            ddl.ddl,
+            "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT NOT NULL);\n\n\
+             CREATE INDEX items_name ON items (name);"
+        );
+        assert!(generate_table_ddl("sqlite-1", "missing", None, &db.store, &db.pools)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_column_statistics() {
+        let db = SqliteFixture::new();
+        let pool = db.pool().await;
+        for sql in [
+            "CREATE TABLE \"order\" (id INTEGER PRIMARY KEY, qty INTEGER, note TEXT)",
+            "INSERT INTO \"order\" (qty, note) VALUES (2, 'b'), (4, 'a'), (4, NULL), (NULL, 'c')",
+        ] {
+            sqlx::query(sql).execute(&*pool).await.unwrap();
+        }
+
+        let qty = collect_column_statistics("sqlite-1", "order", "qty", None, &db.store, &db.pools)
+            .await
+            .unwrap();
+        assert_eq!(qty.min_value, Some(serde_json::json!(2)));
+        assert_eq!(qty.max_value, Some(serde_json::json!(4)));
+        assert_eq!(qty.avg_value, Some(10.0 / 3.0));
+        assert_eq!((qty.distinct_count, qty.null_count, qty.total_count), (2, 1, 4));
+        assert_eq!(qty.sample_values.len(), 2);
+
+        let note =
+            collect_column_statistics("sqlite-1", "order", "note", None, &db.store, &db.pools)
+                .await
+                .unwrap();
+        assert_eq!(note.min_value, Some(serde_json::json!("a")));
+        assert_eq!(note.max_value, Some(serde_json::json!("c")));
+        assert_eq!(note.avg_value, None);
+
+        assert!(
+            collect_column_statistics("sqlite-1", "order", "missing", None, &db.store, &db.pools)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_unique_columns() {
+        let db = SqliteFixture::new();
+        let pool = db.pool().await;
+        for sql in [
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT UNIQUE, a TEXT, b TEXT)",
+            "CREATE UNIQUE INDEX users_a_b ON users (a, b)",
+        ] {
+            sqlx::query(sql).execute(&*pool).await.unwrap();
+        }
+
+        let schema = get_single_table_schema(&db.creds, &pool, "users", None).await.unwrap();
+        let unique: Vec<&str> = schema
+            .columns
+            .iter()
+            .filter(|col| col.is_unique)
+            .map(|col| col.name.as_str())
+            .collect();
+        assert_eq!(unique, vec!["email"]);
+        let email = schema.columns.iter().find(|col| col.name == "email").unwrap();
+        assert!(email.unique_constraint_name.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_quoted_table_names() {
+        let db = SqliteFixture::new();
+        let pool = db.pool().await;
+        sqlx::query("CREATE TABLE \"user's-order\" (id INTEGER PRIMARY KEY, code TEXT UNIQUE)")
+            .execute(&*pool)
+            .await
+            .unwrap();
+
+        let schema = get_single_table_schema(&db.creds, &pool, "user's-order", None).await.unwrap();
+        let names: Vec<&str> = schema.columns.iter().map(|col| col.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "code"]);
+        assert!(schema.columns[1].is_unique);
+    }
+
+    #[tokio::test]
+    async fn test_relationships_for_databases_keep_going_after_a_failure() {
+        let db = SqliteFixture::new();
+
+        let pool = db.pool().await;
+        sqlx::raw_sql(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY);
+             CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER REFERENCES users(id))",
+        )
+        .execute(&*pool)
         .await
-        .map_err(|e| e.to_string())
+        .unwrap();
+
+        let ids = vec!["sqlite-1".to_string(), "missing".to_string()];
+        let results = get_relationships_for_databases(&ids, &db.store, &db.pools).await;
+        assert_eq!(results.len(), 2);
+        assert!(results["sqlite-1"]
+            .as_ref()
+            .unwrap()
+            .iter()
+            .any(|rel| rel.table_name == "posts" && rel.foreign_table == "users"));
+        assert!(matches!(results["missing"], Err(DatabaseError::CredentialsError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_schemas() {
+        let db = SqliteFixture::new();
+
+        let schemas = get_schemas("sqlite-1", &db.store, &db.pools).await.unwrap();
+        assert_eq!(
+            schemas,
+            vec![SchemaInfo {
+                name: "main".to_string(),
+                owner: None,
+                is_default: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_references_table_matches_the_schema() {
+        let rel = Relationship {
+            table_name: "orders".to_string(),
+            column_name: "customer_id".to_string(),
+            foreign_table: "customers".to_string(),
+            foreign_column: "id".to_string(),
+            constraint_name: Some("orders_customer_id_fkey".to_string()),
+            source: RelationshipSource::Explicit,
+            confidence: None,
+            junction_table: None,
+            is_self_referential: false,
+            relationship_cardinality: None,
+            schema: Some("sales".to_string()),
+            foreign_schema: Some("sales".to_string()),
+        };
+        assert!(references_table(&rel, "customers", Some("sales")));
+        assert!(!references_table(&rel, "customers", Some("public")));
+        assert!(!references_table(&rel, "orders", Some("sales")));
+
+        let unqualified = Relationship {
+            foreign_schema: None,
+            ..rel
+        };
+        assert!(references_table(&unqualified, "customers", Some("public")));
+        assert!(references_table(&unqualified, "customers", None));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_find_table_references() {
+        let db = SqliteFixture::new();
+        let pool = db.pool().await;
+        for sql in [
+            "CREATE TABLE customers (id INTEGER PRIMARY KEY, name TEXT)",
+            "CREATE TABLE orders (
+                id INTEGER PRIMARY KEY,
+                customer_id INTEGER REFERENCES customers(id)
+            )",
+        ] {
+            sqlx::query(sql).execute(&*pool).await.unwrap();
+        }
+
+        let references = find_table_references("sqlite-1", "customers", None, &db.store, &db.pools)
+            .await
+            .unwrap();
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].from_table, "orders");
+        assert_eq!(references[0].from_column, "customer_id");
+        assert_eq!(references[0].relationship_type, "foreign_key");
+
+        let references = find_table_references("sqlite-1", "orders", None, &db.store, &db.pools)
+            .await
+            .unwrap();
+        assert!(references.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_journal_mode() {
+        let db = SqliteFixture::new();
+        let postgres: crate::db::types::DatabaseCredentials =
+            serde_json::from_value(serde_json::json!({
+                "id": "postgres-1",
+                "name": "Postgres",
+                "db_type": "postgres",
+                "host": "localhost",
+                "database": "app",
+            }))
+            .unwrap();
+        db.store.add(postgres).unwrap();
+
+        let pragmas = get_sqlite_pragmas("sqlite-1", &db.store, &db.pools).await.unwrap();
+        assert_eq!(pragmas.journal_mode, "delete");
+        assert!(pragmas.page_size > 0);
+
+        let mode = set_journal_mode("sqlite-1", JournalMode::Wal, &db.store, &db.pools)
+            .await
+            .unwrap();
+        assert_eq!(mode, "wal");
+        let pragmas = get_sqlite_pragmas("sqlite-1", &db.store, &db.pools).await.unwrap();
+        assert_eq!(pragmas.journal_mode, "wal");
+
+        assert!(matches!(
+            set_journal_mode("postgres-1", JournalMode::Wal, &db.store, &db.pools).await,
+            Err(DatabaseError::UnsupportedType)
+        ));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, set INSPEKTOR_TEST_POSTGRES_URL"]
+    async fn test_postgres_table_inheritance() {
+        let url = std::env::var("INSPEKTOR_TEST_POSTGRES_URL").unwrap();
+        let dir = std::env::temp_dir().join(format!("inspektor-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let creds = crate::db::types::DatabaseCredentials::from_url(&url).unwrap();
+        let store = CredentialStore::new(dir.join("credentials.json"));
+        store.add(creds.clone()).unwrap();
+        let pools = PoolRegistry::new();
+
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        let parent = format!("inspektor_parent_{}", suffix);
+        let child = format!("inspektor_child_{}", suffix);
+        let pool = pools.get_or_create(&creds.id, &creds).await.unwrap();
+        for sql in [
+            format!("CREATE TABLE {} (id INTEGER, name TEXT)", parent),
+            format!("CREATE TABLE {} (extra TEXT) INHERITS ({})", child, parent),
+        ] {
+            sqlx::query(&sql).execute(&*pool).await.unwrap();
+        }
+
+        let tables = get_tables(&creds.id, &store, &pools).await;
+        let schemas =
+            get_table_schema(&creds.id, &format!("('{}')", child), None, &store, &pools).await;
+
+        sqlx::query(&format!("DROP TABLE {}, {}", child, parent))
+            .execute(&*pool)
+            .await
+            .unwrap();
+        pools.evict(&creds.id).await;
+        std::fs::remove_dir_all(dir).ok();
+
+        let tables = tables.unwrap();
+        let parent_of = |name: &str| {
+            tables
+                .iter()
+                .find(|table| table.name == name)
+                .and_then(|table| table.parent_table.clone())
+        };
+        assert_eq!(parent_of(&child), Some(parent.clone()));
+        assert_eq!(parent_of(&parent), None);
+
+        let schemas = schemas.unwrap();
+        let inherited: Vec<(&str, bool)> = schemas[0]
+            .columns
+            .iter()
+            .map(|column| (column.name.as_str(), column.is_inherited))
+            .collect();
+        assert_eq!(inherited, vec![("id", true), ("name", true), ("extra", false)]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, set INSPEKTOR_TEST_POSTGRES_URL"]
+    async fn test_postgres_single_table_schema_reads_its_schema() {
+        let url = std::env::var("INSPEKTOR_TEST_POSTGRES_URL").unwrap();
+        let creds = crate::db::types::DatabaseCredentials::from_url(&url).unwrap();
+        let pools = PoolRegistry::new();
+
+        let schema = format!("inspektor_{}", uuid::Uuid::new_v4().simple());
+        let pool = pools.get_or_create(&creds.id, &creds).await.unwrap();
+        for sql in [
+            format!("CREATE SCHEMA {}", schema),
+            format!("CREATE TABLE {}.accounts (id INTEGER PRIMARY KEY, code TEXT UNIQUE)", schema),
+            "CREATE TABLE public.accounts (name TEXT)".to_string(),
+        ] {
+            sqlx::query(&sql).execute(&*pool).await.unwrap();
+        }
+
+        let scoped = get_single_table_schema(&creds, &pool, "accounts", Some(&schema)).await;
+        let public = get_single_table_schema(&creds, &pool, "accounts", None).await;
+
+        for sql in [
+            format!("DROP SCHEMA {} CASCADE", schema),
+            "DROP TABLE public.accounts".to_string(),
+        ] {
+            sqlx::query(&sql).execute(&*pool).await.unwrap();
+        }
+        pools.evict(&creds.id).await;
+
+        let scoped = scoped.unwrap();
+        assert_eq!(scoped.schema.as_deref(), Some(schema.as_str()));
+        let columns: Vec<(&str, bool, bool)> = scoped
+            .columns
+            .iter()
+            .map(|col| (col.name.as_str(), col.is_primary_key, col.is_unique))
+            .collect();
+        assert_eq!(columns, vec![("id", true, false), ("code", false, true)]);
+
+        let public = public.unwrap();
+        assert_eq!(public.schema.as_deref(), Some("public"));
+        assert_eq!(public.columns.len(), 1);
+    }
 }
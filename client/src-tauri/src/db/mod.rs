@@ -1,13 +1,33 @@
 pub mod types;
 pub mod connection;
+pub mod clickhouse;
+pub mod tunnel;
 pub mod credentials;
 pub mod query;
 pub mod metadata;
 pub mod encryption;
+pub mod json_schema;
+pub mod dml;
+pub mod ddl;
+pub mod erd;
+pub mod masking;
+pub mod history;
+pub mod notify;
+pub mod report;
+pub mod export;
+pub mod diagnostics;
+pub mod saved_queries;
+pub mod admin;
+pub mod diff;
+pub mod utils;
 
-pub use types::*;
-pub use connection::*;
-pub use credentials::*;
-pub use query::*;
-pub use metadata::*;
-pub use encryption::*;
+// Flat re-exports of every module; the crate itself mostly uses the full paths
+#[allow(unused_imports)]
+pub use {
+    types::*, connection::*, clickhouse::*, tunnel::*, credentials::*, query::*, metadata::*,
+    encryption::*, json_schema::*, dml::*, ddl::*, erd::*, masking::*, history::*, notify::*,
+    report::*, export::*, diagnostics::*, saved_queries::*, admin::*, diff::*, utils::*,
+};
+
+#[cfg(test)]
+mod test_fixtures;
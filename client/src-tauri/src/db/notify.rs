@@ -0,0 +1,290 @@
+use super::connection::{build_connection_string, PoolRegistry};
+use super::credentials::CredentialStore;
+use super::tunnel::uses_ssh_tunnel;
+use super::types::{
+    with_context, DatabaseError, DatabaseErrorContext, DatabaseType, PgNotification,
+    PgNotificationEvent,
+};
+use sqlx::postgres::PgListener;
+use sqlx::Row;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::task::JoinHandle;
+
+/// How long to wait for notifications after subscribing to the channels
+const NOTIFICATION_POLL_MS: u64 = 500;
+
+/// List the channels this app's pooled connection is listening on
+///
+/// `pg_listening_channels()` only reports the session it runs in, which is the
+/// pooled connection this query happens to get. Channels show up after a `LISTEN`
+/// was run through the query editor on that same connection; subscriptions made
+/// with `listen_channel` have their own connection and are never listed.
+pub async fn list_listening_channels(
+    database_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<String>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Err(DatabaseError::UnsupportedType);
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let rows = sqlx::query("SELECT pg_listening_channels()::text AS channel")
+        .fetch_all(&*pool)
+        .await
+        .map_err(DatabaseError::from)?;
+
+    rows.iter()
+        .map(|row| {
+            row.try_get("channel")
+                .map_err(DatabaseError::from)
+        })
+        .collect()
+}
+
+/// Collect the notifications sent on `channels` during a short polling window
+///
+/// Postgres only delivers notifications to sessions that were listening when they
+/// were sent, so a dedicated connection subscribes to the channels and gathers what
+/// arrives within `NOTIFICATION_POLL_MS`. The listener cannot go through an SSH
+/// tunnel.
+pub async fn poll_notifications(
+    database_id: &str,
+    channels: &[String],
+    store: &CredentialStore,
+) -> Result<Vec<PgNotification>, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Err(DatabaseError::UnsupportedType);
+    }
+    if uses_ssh_tunnel(&creds) {
+        return Err(DatabaseError::SSHError(
+            "Notifications cannot be received through an SSH tunnel".to_string(),
+        ));
+    }
+    if channels.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let conn_str = build_connection_string(&creds).await?;
+    let mut listener = PgListener::connect(&conn_str)
+        .await
+        .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
+    listener
+        .listen_all(channels.iter().map(String::as_str))
+        .await
+        .map_err(DatabaseError::from)?;
+
+    let deadline = tokio::time::Instant::now() + Duration::from_millis(NOTIFICATION_POLL_MS);
+    let mut notifications = Vec::new();
+    while let Ok(received) = tokio::time::timeout_at(deadline, listener.recv()).await {
+        let notification = received.map_err(DatabaseError::from)?;
+        notifications.push(PgNotification {
+            channel: notification.channel().to_string(),
+            payload: notification.payload().to_string(),
+            process_id: notification.process_id(),
+        });
+    }
+
+    Ok(notifications)
+}
+
+/// Background tasks of the active `listen_postgres_channel` subscriptions, keyed
+/// by handle id
+///
+/// Each task owns a dedicated connection, which is closed when the task is
+/// cancelled.
+pub struct NotificationListeners {
+    tasks: Mutex<HashMap<String, JoinHandle<()>>>,
+}
+
+impl NotificationListeners {
+    pub fn new() -> Self {
+        Self {
+            tasks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn insert(&self, handle_id: String, task: JoinHandle<()>) {
+        self.tasks.lock().unwrap().insert(handle_id, task);
+    }
+
+    /// Cancel a subscription, returning false when the handle is unknown
+    pub fn cancel(&self, handle_id: &str) -> bool {
+        match self.tasks.lock().unwrap().remove(handle_id) {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Default for NotificationListeners {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Subscribe to a Postgres channel and emit every notification as a
+/// `postgres-notification` event until the subscription is cancelled
+///
+/// The subscription uses its own connection rather than one from the shared pool,
+/// since a pooled connection could be handed to another command while listening.
+/// Returns the handle id to pass to `unlisten_postgres_channel`.
+pub async fn listen_channel(
+    app: &AppHandle,
+    database_id: &str,
+    channel_name: &str,
+    store: &CredentialStore,
+    listeners: &NotificationListeners,
+) -> Result<String, DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Err(DatabaseError::UnsupportedType);
+    }
+    if uses_ssh_tunnel(&creds) {
+        return Err(DatabaseError::SSHError(
+            "Notifications cannot be received through an SSH tunnel".to_string(),
+        ));
+    }
+
+    let conn_str = build_connection_string(&creds).await?;
+    let mut listener = PgListener::connect(&conn_str)
+        .await
+        .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
+    listener
+        .listen(channel_name)
+        .await
+        .map_err(DatabaseError::from)?;
+
+    let handle_id = uuid::Uuid::new_v4().to_string();
+    let task = {
+        let app = app.clone();
+        let handle_id = handle_id.clone();
+        tokio::spawn(async move {
+            loop {
+                let notification = match listener.recv().await {
+                    Ok(notification) => notification,
+                    Err(e) => {
+                        eprintln!("Postgres listener {} stopped: {}", handle_id, e);
+                        break;
+                    }
+                };
+                let payload = notification.payload();
+                let event = PgNotificationEvent {
+                    handle_id: handle_id.clone(),
+                    channel: notification.channel().to_string(),
+                    payload: (!payload.is_empty()).then(|| payload.to_string()),
+                    pid: notification.process_id(),
+                };
+                if let Err(e) = app.emit("postgres-notification", event) {
+                    eprintln!("Failed to emit notification: {}", e);
+                }
+            }
+            app.state::<NotificationListeners>()
+                .tasks
+                .lock()
+                .unwrap()
+                .remove(&handle_id);
+        })
+    };
+    listeners.insert(handle_id.clone(), task);
+
+    Ok(handle_id)
+}
+
+/// Send a notification on a Postgres channel
+pub async fn send_notification(
+    database_id: &str,
+    channel: &str,
+    payload: Option<&str>,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<(), DatabaseError> {
+    let creds = store.get(database_id)?;
+    if !matches!(creds.db_type, DatabaseType::Postgres) {
+        return Err(DatabaseError::UnsupportedType);
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(channel.to_string())
+        .bind(payload.unwrap_or_default().to_string())
+        .execute(&*pool)
+        .await
+        .map_err(DatabaseError::from)?;
+    Ok(())
+}
+
+// Tauri commands for Postgres LISTEN/NOTIFY
+#[tauri::command]
+pub async fn get_postgres_listening_channels(
+    database_id: String,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<Vec<String>, DatabaseErrorContext> {
+    list_listening_channels(&database_id, &store, &pools)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "list_listening_channels"))
+}
+
+#[tauri::command]
+pub async fn get_postgres_active_notifications(
+    database_id: String,
+    channels: Vec<String>,
+    store: State<'_, CredentialStore>,
+) -> Result<Vec<PgNotification>, DatabaseErrorContext> {
+    poll_notifications(&database_id, &channels, &store)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "poll_notifications"))
+}
+
+#[tauri::command]
+pub async fn notify_channel(
+    database_id: String,
+    channel: String,
+    payload: Option<String>,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<(), DatabaseErrorContext> {
+    send_notification(&database_id, &channel, payload.as_deref(), &store, &pools)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "send_notification"))
+}
+
+#[tauri::command]
+pub async fn listen_postgres_channel(
+    database_id: String,
+    channel_name: String,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    listeners: State<'_, NotificationListeners>,
+) -> Result<String, DatabaseErrorContext> {
+    listen_channel(&app, &database_id, &channel_name, &store, &listeners)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "listen_channel"))
+}
+
+#[tauri::command]
+pub async fn unlisten_postgres_channel(
+    handle_id: String,
+    listeners: State<'_, NotificationListeners>,
+) -> Result<(), DatabaseErrorContext> {
+    if listeners.cancel(&handle_id) {
+        Ok(())
+    } else {
+        Err(with_context(
+            DatabaseError::Other(format!("Unknown listener handle: {}", handle_id)),
+            None,
+            "unlisten_channel",
+        ))
+    }
+}
@@ -1,143 +1,3096 @@
-use super::connection::create_pool;
+use super::clickhouse;
+use super::connection::{build_full_connection_uri, recheck_on_connection_error, PoolRegistry};
 use super::credentials::CredentialStore;
-use super::types::{DatabaseError, QueryResult};
+use super::history::QueryHistory;
+use super::masking::apply_masking;
+use super::metadata::{get_table_schema, get_tables, qualified_table_name};
+use super::types::{
+    with_context, AutocompleteContext, ColumnSuggestion, ColumnType, DatabaseCredentials,
+    DatabaseError, DatabaseErrorContext, DatabaseType, ExplainResult, NormalizedType, PageResult,
+    QueryCacheStats, QueryResult, SampleMethod, SortDirection, TableSearchOptions,
+    TransactionResult,
+};
+use super::utils::quote_identifier;
+use dashmap::DashMap;
+use futures::TryStreamExt;
 use serde_json::Value;
-use sqlx::{Column, Row, TypeInfo};
+use sqlformat::{FormatOptions, Indent, QueryParams};
+use sqlparser::ast::{SetExpr, Statement};
+use sqlparser::dialect::{
+    ClickHouseDialect, Dialect, MySqlDialect, PostgreSqlDialect, SQLiteDialect,
+};
+use sqlparser::parser::Parser;
+use sqlparser::tokenizer::{Token, Tokenizer, Whitespace};
+use sqlx::any::{AnyArguments, AnyRow};
+use sqlx::pool::PoolConnection;
+use sqlx::query::Query;
+use sqlx::{Any, AnyConnection, AnyPool, Column, Connection, Either, Row, TypeInfo};
+use std::future::Future;
 use std::collections::HashMap;
-use std::time::Instant;
-use tauri::State;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+use tokio::time::timeout;
+use xxhash_rust::xxh3::xxh3_64_with_seed;
 
-/// List of SQL keywords that indicate destructive operations
-const DESTRUCTIVE_KEYWORDS: &[&str] = &[
-    "DROP", "DELETE", "TRUNCATE", "ALTER", "CREATE", "INSERT", "UPDATE", "GRANT", "REVOKE",
-];
+/// sqlparser dialect of the target database
+fn dialect(db_type: &DatabaseType) -> Box<dyn Dialect> {
+    match db_type {
+        DatabaseType::Postgres => Box::new(PostgreSqlDialect {}),
+        DatabaseType::MySQL => Box::new(MySqlDialect {}),
+        DatabaseType::SQLite => Box::new(SQLiteDialect {}),
+        DatabaseType::ClickHouse => Box::new(ClickHouseDialect {}),
+    }
+}
+
+/// Parse SQL into statements using the dialect of the target database
+fn parse_statements(sql: &str, db_type: &DatabaseType) -> Result<Vec<Statement>, DatabaseError> {
+    let dialect = dialect(db_type);
+
+    let statements = Parser::parse_sql(dialect.as_ref(), sql)
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to parse SQL: {}", e)))?;
+    if statements.is_empty() {
+        return Err(DatabaseError::QueryError("No SQL statement given".to_string()));
+    }
+
+    Ok(statements)
+}
 
 /// Validate SQL query for safety
-fn validate_query(sql: &str) -> Result<(), DatabaseError> {
-    let sql_upper = sql.trim().to_uppercase();
+///
+/// Every statement has to be a query (SELECT, VALUES or WITH); anything that could
+/// modify data or schema is rejected, including queries that write through
+/// `SELECT ... INTO` or take row locks with `FOR UPDATE`/`FOR SHARE`. Comments and
+/// semicolons inside string literals are handled by the parser.
+pub(crate) fn validate_query(
+    sql: &str,
+    db_type: &DatabaseType,
+) -> Result<Vec<Statement>, DatabaseError> {
+    let statements = parse_statements(sql, db_type)?;
 
-    // Check for destructive operations
-    for keyword in DESTRUCTIVE_KEYWORDS {
-        if sql_upper.starts_with(keyword) {
+    for statement in &statements {
+        if !matches!(statement, Statement::Query(_)) {
+            let rendered = statement.to_string();
+            let kind = rendered.split_whitespace().next().unwrap_or("This");
             return Err(DatabaseError::DestructiveOperation(format!(
-                "{} operations are not allowed",
-                keyword
+                "{} statements are not allowed",
+                kind.to_uppercase()
             )));
         }
+        if let Statement::Query(query) = statement {
+            if let Some(reason) = query_write_reason(query) {
+                return Err(DatabaseError::DestructiveOperation(reason.to_string()));
+            }
+        }
     }
 
-    // Basic SQL injection checks
-    // Note: This is a simple check. In production, use parameterized queries
-    let suspicious_patterns = vec![
-        "--",      // SQL comments
-        "/*",      // Multi-line comments
-        ";",       // Multiple statements (could be legitimate in some cases)
-        "xp_",     // SQL Server extended procedures
-        "sp_",     // SQL Server stored procedures
-    ];
+    Ok(statements)
+}
+
+/// SQL that passed the checks, rendered the way it is sent to the database
+pub(crate) struct ExecutableSql {
+    pub statements: Vec<Statement>,
+    pub sql: String, // Every statement as rendered by the parser, separated by `;`
+}
 
-    for pattern in suspicious_patterns {
-        if sql.contains(pattern) && pattern == ";" {
-            // Allow semicolon at the end only
-            if sql.trim().ends_with(';') && sql.matches(';').count() == 1 {
-                continue;
+/// Comment the parser skips but the database would read as SQL
+///
+/// sqlparser nests block comments, which only Postgres does, so elsewhere a nested
+/// `/*` ends the comment early. MySQL runs `/*! ... */` comments as SQL.
+fn hidden_sql_reason(sql: &str, db_type: &DatabaseType) -> Option<&'static str> {
+    // A SQL that does not tokenize fails to parse later with a better error
+    let tokens = Tokenizer::new(dialect(db_type).as_ref(), sql).tokenize().ok()?;
+    tokens.iter().find_map(|token| match token {
+        Token::Whitespace(Whitespace::MultiLineComment(comment)) => {
+            if matches!(db_type, DatabaseType::MySQL) && comment.starts_with('!') {
+                Some("MySQL executable comments are not allowed")
+            } else if !matches!(db_type, DatabaseType::Postgres) && comment.contains("/*") {
+                Some("Nested block comments are not allowed")
+            } else {
+                None
             }
-            return Err(DatabaseError::SQLInjection);
-        } else if sql.contains(pattern) && pattern != ";" {
-            return Err(DatabaseError::SQLInjection);
+        }
+        _ => None,
+    })
+}
+
+/// Parse SQL and render the statements that will be executed
+///
+/// With `read_only` every statement has to pass `validate_query`. Comments that the
+/// database would read differently from the parser are rejected as SQL injection,
+/// since text that passed the checks could hide a write in them. The SQL is not
+/// executed as written either: the parser's rendering has no comments and is
+/// exactly what was checked.
+pub(crate) fn executable_sql(
+    sql: &str,
+    db_type: &DatabaseType,
+    read_only: bool,
+) -> Result<ExecutableSql, DatabaseError> {
+    if let Some(reason) = hidden_sql_reason(sql, db_type) {
+        return Err(DatabaseError::SQLInjection(reason.to_string()));
+    }
+    let statements = if read_only {
+        validate_query(sql, db_type)?
+    } else {
+        parse_statements(sql, db_type)?
+    };
+    let sql = statements
+        .iter()
+        .map(Statement::to_string)
+        .collect::<Vec<_>>()
+        .join(";\n");
+    Ok(ExecutableSql { statements, sql })
+}
+
+/// Why a parsed query is not read-only, looking through CTEs and set operations
+fn query_write_reason(query: &sqlparser::ast::Query) -> Option<&'static str> {
+    if !query.locks.is_empty() {
+        return Some("FOR UPDATE and FOR SHARE locking clauses are not allowed");
+    }
+    let ctes = query.with.iter().flat_map(|with| &with.cte_tables);
+    for cte in ctes {
+        if let Some(reason) = query_write_reason(&cte.query) {
+            return Some(reason);
         }
     }
+    set_expr_write_reason(&query.body)
+}
 
-    Ok(())
+fn set_expr_write_reason(body: &SetExpr) -> Option<&'static str> {
+    match body {
+        SetExpr::Select(select) if select.into.is_some() => {
+            Some("SELECT INTO statements are not allowed")
+        }
+        SetExpr::Query(query) => query_write_reason(query),
+        SetExpr::SetOperation { left, right, .. } => {
+            set_expr_write_reason(left).or_else(|| set_expr_write_reason(right))
+        }
+        SetExpr::Insert(_) | SetExpr::Update(_) => Some("Data-modifying statements are not allowed"),
+        _ => None,
+    }
+}
+
+/// Largest number of statements kept in the query cache; it is emptied when full
+const MAX_QUERY_CACHE_ENTRIES: usize = 1000;
+
+/// SQL accepted by `QueryCache::validate`
+#[derive(Debug, Clone)]
+pub struct ValidatedStatement {
+    pub sql: Arc<String>, // From `executable_sql`, the only SQL that may be executed
+    pub has_limit: bool,  // The first statement has a top-level LIMIT or FETCH of its own
+    pub has_locks: bool,  // The first statement has a FOR UPDATE/FOR SHARE clause
+}
+
+impl ValidatedStatement {
+    fn new(executable: &ExecutableSql) -> Self {
+        let (has_limit, has_locks) = match executable.statements.first() {
+            Some(Statement::Query(query)) => (
+                query.limit.is_some() || query.fetch.is_some(),
+                !query.locks.is_empty(),
+            ),
+            _ => (false, false),
+        };
+        Self {
+            sql: Arc::new(executable.sql.clone()),
+            has_limit,
+            has_locks,
+        }
+    }
+}
+
+/// Validated statements, keyed by an xxh3 hash of the SQL and the database dialect
+///
+/// Only single statements are cached. The cached value is the statement as rendered
+/// by the parser, which drops comments as well as trailing whitespace and semicolons,
+/// so a hit can be executed, paginated or limited without parsing the SQL again.
+/// Entries keep the original SQL so a hash collision is treated as a miss.
+pub struct QueryCache {
+    entries: DashMap<u64, (String, ValidatedStatement)>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl QueryCache {
+    pub fn new() -> Self {
+        Self {
+            entries: DashMap::new(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn key(sql: &str, db_type: &DatabaseType) -> u64 {
+        let seed = match db_type {
+            DatabaseType::Postgres => 0,
+            DatabaseType::MySQL => 1,
+            DatabaseType::SQLite => 2,
+            DatabaseType::ClickHouse => 3,
+        };
+        xxh3_64_with_seed(sql.as_bytes(), seed)
+    }
+
+    /// Validate `sql` with `executable_sql` unless it was validated before
+    ///
+    /// Returns the rendered statements and the number of statements.
+    pub fn validate(
+        &self,
+        sql: &str,
+        db_type: &DatabaseType,
+    ) -> Result<(ValidatedStatement, usize), DatabaseError> {
+        let key = Self::key(sql, db_type);
+        if let Some(entry) = self.entries.get(&key) {
+            let (source, normalized) = entry.value();
+            if source == sql {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok((normalized.clone(), 1));
+            }
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let executable = executable_sql(sql, db_type, true)?;
+        let normalized = ValidatedStatement::new(&executable);
+        let statement_count = executable.statements.len();
+        if statement_count == 1 {
+            if self.entries.len() >= MAX_QUERY_CACHE_ENTRIES {
+                self.entries.clear();
+            }
+            self.entries.insert(key, (sql.to_string(), normalized.clone()));
+        }
+
+        Ok((normalized, statement_count))
+    }
+
+    pub fn clear(&self) {
+        self.entries.clear();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> QueryCacheStats {
+        QueryCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.len(),
+        }
+    }
+}
+
+impl Default for QueryCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cache used by `execute_query`
+static QUERY_CACHE: LazyLock<QueryCache> = LazyLock::new(QueryCache::new);
+
+/// Default upper bound for `QueryOptions::page_size`
+pub const DEFAULT_MAX_PAGE_SIZE: u64 = 10_000;
+
+/// Largest page size accepted by `execute_query`, adjustable with `set_max_page_size`
+static MAX_PAGE_SIZE: AtomicU64 = AtomicU64::new(DEFAULT_MAX_PAGE_SIZE);
+
+/// Change the largest page size accepted by `execute_query`
+pub fn set_max_page_size(max_page_size: u64) {
+    MAX_PAGE_SIZE.store(max_page_size.max(1), Ordering::Relaxed);
+}
+
+/// Largest page size accepted by `execute_query`
+pub fn max_page_size() -> u64 {
+    MAX_PAGE_SIZE.load(Ordering::Relaxed)
+}
+
+/// Default for the row limit `execute_query` adds to queries without a LIMIT
+pub const DEFAULT_ROW_LIMIT: u64 = 1000;
+
+/// Row limit used when `QueryOptions::max_rows` is not set, 0 for no limit
+static ROW_LIMIT: AtomicU64 = AtomicU64::new(DEFAULT_ROW_LIMIT);
+
+/// Change the row limit used when `QueryOptions::max_rows` is not set
+pub fn set_row_limit(limit: u64) {
+    ROW_LIMIT.store(limit, Ordering::Relaxed);
+}
+
+/// Row limit used when `QueryOptions::max_rows` is not set
+pub fn row_limit() -> u64 {
+    ROW_LIMIT.load(Ordering::Relaxed)
+}
+
+/// Seconds `execute_query` waits for the database when no timeout is given
+pub const DEFAULT_QUERY_TIMEOUT_SECS: u64 = 30;
+
+/// Seconds to wait for the database to accept a cancellation
+const CANCEL_TIMEOUT_SECS: u64 = 5;
+
+/// Cancels the statement running on a pool connection when it times out
+///
+/// Dropping a future that timed out only stops waiting: the database keeps running
+/// the statement and the connection stays busy until it finishes. The canceller
+/// knows the server-side id of its connection and cancels the statement from
+/// another connection instead. SQLite cannot be interrupted through the Any driver,
+/// so there its statement still runs to completion.
+pub(crate) struct QueryCanceller {
+    db_type: DatabaseType,
+    backend_id: Option<i64>,
+}
+
+impl QueryCanceller {
+    /// Take a connection from the pool, with a canceller for statements run on it
+    pub async fn acquire(
+        db_type: &DatabaseType,
+        pool: &AnyPool,
+    ) -> Result<(PoolConnection<Any>, Self), DatabaseError> {
+        let mut conn = pool.acquire().await.map_err(DatabaseError::from)?;
+        let id_sql = match db_type {
+            DatabaseType::Postgres => Some("SELECT CAST(pg_backend_pid() AS BIGINT)"),
+            DatabaseType::MySQL => Some("SELECT CAST(CONNECTION_ID() AS SIGNED)"),
+            DatabaseType::SQLite | DatabaseType::ClickHouse => None,
+        };
+        let backend_id = match id_sql {
+            Some(id_sql) => Some(
+                sqlx::query_scalar(id_sql)
+                    .fetch_one(&mut *conn)
+                    .await
+                    .map_err(DatabaseError::from)?,
+            ),
+            None => None,
+        };
+        let canceller = Self {
+            db_type: db_type.clone(),
+            backend_id,
+        };
+        Ok((conn, canceller))
+    }
+
+    /// Await `fetch`, cancelling its statement when it runs longer than `timeout_secs`
+    ///
+    /// The database error, if any, is returned as is for the caller to map.
+    pub async fn timeout<T>(
+        &self,
+        pool: &AnyPool,
+        timeout_secs: u64,
+        fetch: impl Future<Output = Result<T, sqlx::Error>>,
+    ) -> Result<Result<T, sqlx::Error>, DatabaseError> {
+        match timeout(Duration::from_secs(timeout_secs), fetch).await {
+            Ok(result) => Ok(result),
+            Err(_) => {
+                self.cancel(pool).await;
+                Err(DatabaseError::QueryTimeout(timeout_secs))
+            }
+        }
+    }
+
+    async fn cancel(&self, pool: &AnyPool) {
+        let Some(id) = self.backend_id else {
+            return;
+        };
+        let sql = match self.db_type {
+            DatabaseType::Postgres => format!("SELECT pg_cancel_backend({})", id),
+            DatabaseType::MySQL => format!("KILL QUERY {}", id),
+            DatabaseType::SQLite | DatabaseType::ClickHouse => return,
+        };
+        // Best effort, the timeout is reported whether or not the cancel arrives
+        let limit = Duration::from_secs(CANCEL_TIMEOUT_SECS);
+        let _ = timeout(limit, sqlx::query(&sql).execute(pool)).await;
+    }
+}
+
+/// Optional execution settings for `execute_query`
+#[derive(Debug, Clone, Default)]
+pub struct QueryOptions {
+    /// Number of rows per page; when set the query is paginated
+    pub page_size: Option<u64>,
+    /// Zero-based page number, defaults to the first page
+    pub page: Option<u64>,
+    /// Seconds to wait for the query, defaults to `DEFAULT_QUERY_TIMEOUT_SECS`
+    pub timeout_secs: Option<u64>,
+    /// Rows to return from a query without a LIMIT of its own, defaults to
+    /// `row_limit()`; 0 disables the limit
+    pub max_rows: Option<u64>,
 }
 
 pub async fn execute_query(
     database_id: &str,
     sql: &str,
+    options: &QueryOptions,
     store: &CredentialStore,
+    pools: &PoolRegistry,
 ) -> Result<QueryResult, DatabaseError> {
-    // Validate the query first
-    validate_query(sql)?;
+    run_query(database_id, sql, options, None, store, pools).await
+}
+
+/// `execute_query`, with the result ordered by a column the caller checked exists
+async fn run_query(
+    database_id: &str,
+    sql: &str,
+    options: &QueryOptions,
+    sort: Option<(&str, SortDirection)>,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<QueryResult, DatabaseError> {
+    let max_page_size = max_page_size();
+    if let Some(page_size) = options.page_size {
+        if page_size == 0 || page_size > max_page_size {
+            return Err(DatabaseError::QueryError(format!(
+                "page_size must be between 1 and {}",
+                max_page_size
+            )));
+        }
+    }
 
     // Get credentials
     let creds = store.get(database_id)?;
 
+    // Validate the query before connecting
+    let (statement, statement_count) = QUERY_CACHE.validate(sql, &creds.db_type)?;
+    let inner_sql = &statement.sql;
+    if (options.page_size.is_some() || sort.is_some()) && statement_count > 1 {
+        return Err(DatabaseError::QueryError(
+            "Pagination and sorting require a single statement".to_string(),
+        ));
+    }
+
+    // Only the rendered statement is executed. Pagination and sorting wrap it in a
+    // subquery, which a trailing comment or semicolon of the original SQL could
+    // otherwise break. The ORDER BY goes on that outermost query, before its LIMIT,
+    // since the database does not have to keep the order of a subquery.
+    let order_by = sort
+        .map(|(column, direction)| {
+            format!(
+                " ORDER BY {} {}",
+                quote_identifier(column, &creds.db_type),
+                direction.keyword()
+            )
+        })
+        .unwrap_or_default();
+    let page = options.page.unwrap_or(0);
+    let paged_sql = options.page_size.map(|page_size| {
+        format!(
+            "SELECT * FROM ({}) AS _inspektor_page{} LIMIT {} OFFSET {}",
+            inner_sql,
+            order_by,
+            page_size,
+            page.saturating_mul(page_size)
+        )
+    });
+    let base_sql = if sort.is_some() {
+        format!("SELECT * FROM ({}) AS _inspektor_page{}", inner_sql, order_by)
+    } else {
+        inner_sql.to_string()
+    };
+
+    // Unpaginated queries without a LIMIT get one, fetching an extra row to tell
+    // whether the limit cut the result short
+    let max_rows = options.max_rows.unwrap_or_else(row_limit);
+    let limited_sql = if paged_sql.is_none()
+        && statement_count == 1
+        && (sort.is_some() || (!statement.has_limit && !statement.has_locks))
+        && max_rows > 0
+    {
+        Some(format!("{} LIMIT {}", base_sql, max_rows.saturating_add(1)))
+    } else {
+        None
+    };
+
+    let timeout_secs = options.timeout_secs.unwrap_or(DEFAULT_QUERY_TIMEOUT_SECS);
+    let run_sql = paged_sql
+        .as_deref()
+        .or(limited_sql.as_deref())
+        .unwrap_or(base_sql.as_str());
+    let count_sql = format!("SELECT COUNT(*) FROM ({}) AS _inspektor_count", inner_sql);
+
+    if matches!(creds.db_type, DatabaseType::ClickHouse) {
+        return execute_clickhouse_query(
+            &creds,
+            run_sql,
+            paged_sql.is_some().then_some(count_sql.as_str()),
+            limited_sql.is_some().then_some(max_rows),
+            options,
+        )
+        .await;
+    }
+
     // Create connection pool
-    let pool = create_pool(&creds).await?;
+    let pool = pools.get_or_create(database_id, &creds).await?;
+    let (mut conn, canceller) = QueryCanceller::acquire(&creds.db_type, &pool).await?;
 
     // Execute query and measure time
     let start = Instant::now();
 
-    let rows = sqlx::query(sql)
-        .fetch_all(&pool)
-        .await
-        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+    let fetch = fetch_all_with_declared_types(&creds, &mut conn, run_sql);
+    let (mut rows, column_types) = canceller
+        .timeout(&pool, timeout_secs, fetch)
+        .await?
+        .map_err(DatabaseError::from)?;
 
     let execution_time = start.elapsed();
 
-    // Convert rows to our format
-    let mut result_rows: Vec<HashMap<String, Value>> = Vec::new();
-    let mut columns: Vec<String> = Vec::new();
+    let was_truncated = limited_sql.is_some() && rows.len() as u64 > max_rows;
+    if was_truncated {
+        rows.truncate(max_rows as usize);
+    }
 
-    if !rows.is_empty() {
-        // Get column names from first row
-        columns = rows[0]
-            .columns()
-            .iter()
-            .map(|col| col.name().to_string())
-            .collect();
+    let total_rows = if paged_sql.is_some() {
+        let count = sqlx::query(&count_sql).fetch_one(&mut *conn);
+        let row = canceller
+            .timeout(&pool, timeout_secs, count)
+            .await?
+            .map_err(DatabaseError::from)?;
+        let count: i64 = row
+            .try_get(0)
+            .map_err(DatabaseError::from)?;
+        Some(count.max(0) as u64)
+    } else {
+        None
+    };
 
-        // Convert each row
-        for row in &rows {
-            let mut row_map = HashMap::new();
+    let (columns, mut result_rows) = convert_rows(&rows);
+    let value_coercions_applied = coerce_values(&columns, &column_types, &mut result_rows);
+    let masked_columns_applied = apply_masking(&creds, &columns, &mut result_rows);
+
+    Ok(QueryResult {
+        columns,
+        row_count: result_rows.len(),
+        rows: result_rows,
+        execution_time_ms: execution_time.as_millis() as u64,
+        matched_columns: Vec::new(),
+        total_rows,
+        page: if options.page_size.is_some() { page } else { 0 },
+        page_size: options.page_size.unwrap_or(0),
+        masked_columns_applied,
+        value_coercions_applied,
+        sampling_method_used: None,
+        column_types,
+        was_truncated,
+    })
+}
 
-            for (idx, column) in row.columns().iter().enumerate() {
-                let col_name = column.name().to_string();
+/// `execute_query` for ClickHouse, which is queried over HTTP instead of a pool
+///
+/// `count_sql` is set for paginated queries, `max_rows` when a LIMIT was added to
+/// tell whether the result was cut short. A timed out request is dropped, which
+/// closes its connection.
+async fn execute_clickhouse_query(
+    creds: &DatabaseCredentials,
+    sql: &str,
+    count_sql: Option<&str>,
+    max_rows: Option<u64>,
+    options: &QueryOptions,
+) -> Result<QueryResult, DatabaseError> {
+    let timeout_secs = options.timeout_secs.unwrap_or(DEFAULT_QUERY_TIMEOUT_SECS);
+    let limit = Duration::from_secs(timeout_secs);
 
-                // Try to get value as different types
-                let value: Value = if let Ok(val) = row.try_get::<String, _>(idx) {
-                    Value::String(val)
-                } else if let Ok(val) = row.try_get::<i64, _>(idx) {
-                    Value::Number(val.into())
-                } else if let Ok(val) = row.try_get::<i32, _>(idx) {
-                    Value::Number(val.into())
-                } else if let Ok(val) = row.try_get::<f64, _>(idx) {
-                    Value::Number(
-                        serde_json::Number::from_f64(val)
-                            .unwrap_or_else(|| serde_json::Number::from(0)),
-                    )
-                } else if let Ok(val) = row.try_get::<bool, _>(idx) {
-                    Value::Bool(val)
-                } else {
-                    // Try to get as raw bytes and convert to string
-                    Value::Null
-                };
+    let start = Instant::now();
+    let mut fetched = timeout(limit, clickhouse::run_query(creds, sql))
+        .await
+        .map_err(|_| DatabaseError::QueryTimeout(timeout_secs))??;
+    let execution_time = start.elapsed();
 
-                row_map.insert(col_name, value);
-            }
+    let was_truncated = match max_rows {
+        Some(max) if fetched.rows.len() as u64 > max => {
+            fetched.rows.truncate(max as usize);
+            true
+        }
+        _ => false,
+    };
 
-            result_rows.push(row_map);
+    let total_rows = match count_sql {
+        Some(count_sql) => {
+            let counted = timeout(limit, clickhouse::run_query(creds, count_sql))
+                .await
+                .map_err(|_| DatabaseError::QueryTimeout(timeout_secs))??;
+            let count = counted
+                .columns
+                .first()
+                .and_then(|column| counted.rows.first()?.get(column))
+                .and_then(Value::as_u64)
+                .ok_or_else(|| DatabaseError::QueryError("Missing row count".to_string()))?;
+            Some(count)
         }
+        None => None,
+    };
+
+    let columns = fetched.columns;
+    let column_types = fetched.column_types;
+    let mut result_rows = fetched.rows;
+    let value_coercions_applied = coerce_values(&columns, &column_types, &mut result_rows);
+    let masked_columns_applied = apply_masking(creds, &columns, &mut result_rows);
+
+    Ok(QueryResult {
+        columns,
+        row_count: result_rows.len(),
+        rows: result_rows,
+        execution_time_ms: execution_time.as_millis() as u64,
+        matched_columns: Vec::new(),
+        total_rows,
+        page: if options.page_size.is_some() { options.page.unwrap_or(0) } else { 0 },
+        page_size: options.page_size.unwrap_or(0),
+        masked_columns_applied,
+        value_coercions_applied,
+        sampling_method_used: None,
+        column_types,
+        was_truncated,
+    })
+}
+
+/// Run a single statement with bound parameters
+///
+/// Parameters are sent separately from the SQL, so their values can never change
+/// the statement. Placeholders follow the database: `$1, $2, ...` for Postgres and
+/// `?` for MySQL and SQLite. The statement must pass the same checks as
+/// `execute_query`.
+pub async fn execute_query_with_params(
+    database_id: &str,
+    sql: &str,
+    params: &[Value],
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<QueryResult, DatabaseError> {
+    let creds = store.get(database_id)?;
+
+    let executable = executable_sql(sql, &creds.db_type, true)?;
+    if executable.statements.len() > 1 {
+        return Err(DatabaseError::QueryError(
+            "Parameterized queries must be a single statement".to_string(),
+        ));
     }
 
-    pool.close().await;
+    let pool = pools.get_or_create(database_id, &creds).await?;
+    let (mut conn, canceller) = QueryCanceller::acquire(&creds.db_type, &pool).await?;
+
+    let query = params
+        .iter()
+        .fold(sqlx::query(&executable.sql), |query, param| bind_value(query, param));
+
+    let start = Instant::now();
+    let rows = canceller
+        .timeout(&pool, DEFAULT_QUERY_TIMEOUT_SECS, query.fetch_all(&mut *conn))
+        .await?
+        .map_err(DatabaseError::from)?;
+    let execution_time = start.elapsed();
+
+    let (columns, mut result_rows) = convert_rows(&rows);
+    let column_types = column_types(&rows);
+    let value_coercions_applied = coerce_values(&columns, &column_types, &mut result_rows);
+    let masked_columns_applied = apply_masking(&creds, &columns, &mut result_rows);
 
     Ok(QueryResult {
         columns,
         row_count: result_rows.len(),
         rows: result_rows,
         execution_time_ms: execution_time.as_millis() as u64,
+        matched_columns: Vec::new(),
+        total_rows: None,
+        page: 0,
+        page_size: 0,
+        masked_columns_applied,
+        value_coercions_applied,
+        sampling_method_used: None,
+        column_types,
+        was_truncated: false,
     })
 }
 
-// Tauri command for executing queries
-#[tauri::command]
-pub async fn execute_sql_query(
-    database_id: String,
-    sql: String,
-    store: State<'_, CredentialStore>,
-) -> Result<QueryResult, String> {
-    execute_query(&database_id, &sql, &store)
+/// Run statements in a single transaction
+///
+/// Each entry must be exactly one statement. With `allow_mutations` the read-only
+/// check is skipped so INSERT, UPDATE and DELETE can be used; otherwise every
+/// statement is validated like `execute_query`. BEGIN, COMMIT, ROLLBACK and
+/// savepoint statements are always rejected. All statements are checked before
+/// the transaction starts. If a statement fails the transaction is rolled back and
+/// the results of the statements before it are returned with the error as
+/// `rollback_reason`.
+pub async fn execute_transaction(
+    database_id: &str,
+    statements: &[String],
+    allow_mutations: bool,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<TransactionResult, DatabaseError> {
+    let creds = store.get(database_id)?;
+
+    if statements.is_empty() {
+        return Err(DatabaseError::QueryError("No SQL statement given".to_string()));
+    }
+    let mut rendered = Vec::with_capacity(statements.len());
+    for sql in statements {
+        let executable = executable_sql(sql, &creds.db_type, !allow_mutations)?;
+        let parsed = &executable.statements;
+        if parsed.len() > 1 {
+            return Err(DatabaseError::QueryError(format!(
+                "Each transaction entry must be a single statement:\n{}",
+                sql
+            )));
+        }
+        // These would end or split the transaction this command manages
+        if parsed.iter().any(|statement| {
+            matches!(
+                statement,
+                Statement::StartTransaction { .. }
+                    | Statement::Commit { .. }
+                    | Statement::Rollback { .. }
+                    | Statement::Savepoint { .. }
+                    | Statement::ReleaseSavepoint { .. }
+            )
+        }) {
+            return Err(DatabaseError::QueryError(format!(
+                "Transaction control statements are not allowed inside a transaction:\n{}",
+                sql
+            )));
+        }
+        rendered.push(executable.sql);
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+    let (mut conn, canceller) = QueryCanceller::acquire(&creds.db_type, &pool).await?;
+
+    // Dropping the transaction without committing rolls it back
+    let mut tx = conn
+        .begin()
+        .await
+        .map_err(DatabaseError::from)?;
+
+    let mut results = Vec::with_capacity(statements.len());
+    for (idx, sql) in rendered.iter().enumerate() {
+        let start = Instant::now();
+        let fetch = sqlx::query(sql).fetch_all(&mut *tx);
+        let rows = match canceller.timeout(&pool, DEFAULT_QUERY_TIMEOUT_SECS, fetch).await {
+            Ok(Ok(rows)) => rows,
+            Ok(Err(e)) => {
+                tx.rollback()
+                    .await
+                    .map_err(DatabaseError::from)?;
+                return Ok(TransactionResult {
+                    results,
+                    committed: false,
+                    rollback_reason: Some(format!("Statement {} failed: {}", idx + 1, e)),
+                });
+            }
+            // The statement was cancelled, dropping the transaction rolls it back
+            Err(e) => return Err(e),
+        };
+        let execution_time = start.elapsed();
+
+        let (columns, mut result_rows) = convert_rows(&rows);
+        let column_types = column_types(&rows);
+        let value_coercions_applied = coerce_values(&columns, &column_types, &mut result_rows);
+        let masked_columns_applied = apply_masking(&creds, &columns, &mut result_rows);
+
+        results.push(QueryResult {
+            columns,
+            row_count: result_rows.len(),
+            rows: result_rows,
+            execution_time_ms: execution_time.as_millis() as u64,
+            matched_columns: Vec::new(),
+            total_rows: None,
+            page: 0,
+            page_size: 0,
+            masked_columns_applied,
+            value_coercions_applied,
+            sampling_method_used: None,
+            column_types,
+            was_truncated: false,
+        });
+    }
+
+    tx.commit()
         .await
-        .map_err(|e| e.to_string())
+        .map_err(DatabaseError::from)?;
+
+    Ok(TransactionResult {
+        results,
+        committed: true,
+        rollback_reason: None,
+    })
+}
+
+/// Run a query with its result ordered by one of its columns
+///
+/// The ORDER BY is applied by `execute_query` to the outermost query, after any
+/// pagination wrapper. `sort_column` is only used after checking it against the
+/// columns of the result, read from a one-row run of the query or, when that returns
+/// no rows, from a description of it, and is quoted as an identifier, so it cannot
+/// add SQL of its own. Pagination and row limits in `options` apply to the sorted
+/// query.
+pub async fn execute_sorted_query(
+    database_id: &str,
+    sql: &str,
+    sort_column: &str,
+    sort_direction: SortDirection,
+    options: &QueryOptions,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<QueryResult, DatabaseError> {
+    let creds = store.get(database_id)?;
+
+    let (statement, statement_count) = QUERY_CACHE.validate(sql, &creds.db_type)?;
+    if statement_count > 1 {
+        return Err(DatabaseError::QueryError(
+            "Sorting requires a single statement".to_string(),
+        ));
+    }
+
+    let dry_run_options = QueryOptions {
+        timeout_secs: options.timeout_secs,
+        ..Default::default()
+    };
+    let dry_run_sql = format!("SELECT * FROM ({}) _inspektor_result LIMIT 1", statement.sql);
+    let dry_run = execute_query(database_id, &dry_run_sql, &dry_run_options, store, pools).await?;
+    let columns = if dry_run.columns.is_empty() {
+        let pool = pools.get_or_create(database_id, &creds).await?;
+        describe_columns(&creds, &pool, &statement.sql).await
+    } else {
+        Some(dry_run.columns)
+    };
+    match columns {
+        Some(columns) if columns.iter().any(|column| column == sort_column) => {}
+        Some(_) => {
+            return Err(DatabaseError::QueryError(format!(
+                "Cannot sort by '{}', the result has no such column",
+                sort_column
+            )))
+        }
+        None => {
+            return Err(DatabaseError::QueryError(format!(
+                "Cannot sort by '{}', the columns of the result are unknown",
+                sort_column
+            )))
+        }
+    }
+
+    let sort = Some((sort_column, sort_direction));
+    run_query(database_id, sql, options, sort, store, pools).await
+}
+
+/// Column names of a query, read without running it
+///
+/// Used when a query returns no rows, which leaves its columns unknown. ClickHouse
+/// reports the columns of an empty result itself, so it is never described.
+async fn describe_columns(
+    creds: &DatabaseCredentials,
+    pool: &AnyPool,
+    sql: &str,
+) -> Option<Vec<String>> {
+    use sqlx::Executor;
+
+    match creds.db_type {
+        DatabaseType::SQLite => sqlite_declared_columns(creds, sql)
+            .await
+            .map(|columns| columns.into_iter().map(|(name, _)| name).collect()),
+        DatabaseType::Postgres | DatabaseType::MySQL => {
+            let described = pool.describe(sql).await.ok()?;
+            Some(described.columns().iter().map(|c| c.name().to_string()).collect())
+        }
+        DatabaseType::ClickHouse => None,
+    }
+}
+
+/// Run SQL that can return several result sets, such as a MySQL stored procedure
+///
+/// On MySQL a single `CALL` statement is sent as-is and every result set of the
+/// procedure becomes its own `QueryResult`, each limited to `max_rows` like
+/// `execute_query`. The procedure body cannot be checked by `validate_query`, so a
+/// `CALL` is only run with `allow_mutations`. Anything else goes through
+/// `execute_query` and is returned as a single result; this includes Postgres CTEs
+/// with `RETURNING` clauses, which already come back as one result.
+pub async fn execute_multi_result(
+    database_id: &str,
+    sql: &str,
+    options: &QueryOptions,
+    allow_mutations: bool,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<QueryResult>, DatabaseError> {
+    let creds = store.get(database_id)?;
+
+    let executable = executable_sql(sql, &creds.db_type, false)?;
+    let is_call = matches!(creds.db_type, DatabaseType::MySQL)
+        && matches!(executable.statements.as_slice(), [Statement::Call(_)]);
+    if !is_call {
+        return Ok(vec![execute_query(database_id, sql, options, store, pools).await?]);
+    }
+    if !allow_mutations {
+        return Err(DatabaseError::DestructiveOperation(
+            "CALL statements can modify data and need allow_mutations".to_string(),
+        ));
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+    let (mut conn, canceller) = QueryCanceller::acquire(&creds.db_type, &pool).await?;
+
+    let timeout_secs = options.timeout_secs.unwrap_or(DEFAULT_QUERY_TIMEOUT_SECS);
+
+    let max_rows = options.max_rows.unwrap_or_else(row_limit);
+
+    let start = Instant::now();
+
+    // Every result set ends with a summary, and the CALL itself ends with one more.
+    // Rows past `max_rows` are still read so the stream reaches the next result set.
+    let fetch = async {
+        let mut result_sets = Vec::new();
+        let mut current = Vec::new();
+        let mut truncated = false;
+        let mut stream = sqlx::raw_sql(&executable.sql).fetch_many(&mut *conn);
+        while let Some(item) = stream.try_next().await? {
+            match item {
+                Either::Left(_) => {
+                    result_sets.push((std::mem::take(&mut current), truncated));
+                    truncated = false;
+                }
+                Either::Right(_) if max_rows > 0 && current.len() as u64 >= max_rows => {
+                    truncated = true;
+                }
+                Either::Right(row) => current.push(row),
+            }
+        }
+        Ok::<_, sqlx::Error>(result_sets)
+    };
+    let mut result_sets = canceller
+        .timeout(&pool, timeout_secs, fetch)
+        .await?
+        .map_err(DatabaseError::from)?;
+    if result_sets.len() > 1 && result_sets.last().is_some_and(|(rows, _)| rows.is_empty()) {
+        result_sets.pop();
+    }
+
+    let execution_time = start.elapsed();
+
+    let mut results = Vec::with_capacity(result_sets.len());
+    for (rows, was_truncated) in result_sets {
+        let (columns, mut result_rows) = convert_rows(&rows);
+        let column_types = column_types(&rows);
+        let value_coercions_applied = coerce_values(&columns, &column_types, &mut result_rows);
+        let masked_columns_applied = apply_masking(&creds, &columns, &mut result_rows);
+
+        results.push(QueryResult {
+            columns,
+            row_count: result_rows.len(),
+            rows: result_rows,
+            execution_time_ms: execution_time.as_millis() as u64,
+            matched_columns: Vec::new(),
+            total_rows: None,
+            page: 0,
+            page_size: 0,
+            masked_columns_applied,
+            value_coercions_applied,
+            sampling_method_used: None,
+            column_types,
+            was_truncated,
+        });
+    }
+
+    Ok(results)
+}
+
+/// Bind a JSON value as a query parameter of the matching SQL type
+///
+/// Integers that fit in an `i64` are bound as integers, other numbers as floats.
+/// Arrays and objects are bound as their JSON text.
+pub(crate) fn bind_value<'q>(
+    query: Query<'q, Any, AnyArguments<'q>>,
+    value: &Value,
+) -> Query<'q, Any, AnyArguments<'q>> {
+    match value {
+        Value::Null => query.bind(None::<String>),
+        Value::Bool(b) => query.bind(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => query.bind(i),
+            None => query.bind(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => query.bind(s.clone()),
+        other => query.bind(other.to_string()),
+    }
+}
+
+/// Convert fetched rows into column names and JSON row maps
+pub(crate) fn convert_rows(rows: &[AnyRow]) -> (Vec<String>, Vec<HashMap<String, Value>>) {
+    let columns: Vec<String> = rows
+        .first()
+        .map(|row| row.columns().iter().map(|col| col.name().to_string()).collect())
+        .unwrap_or_default();
+
+    (columns, rows.iter().map(convert_row).collect())
+}
+
+/// Convert a single row into a JSON map keyed by column name
+pub(crate) fn convert_row(row: &AnyRow) -> HashMap<String, Value> {
+    let mut row_map = HashMap::new();
+
+    for (idx, column) in row.columns().iter().enumerate() {
+        let column_type = NormalizedType::from_data_type(column.type_info().name());
+        row_map.insert(column.name().to_string(), decode_value(row, idx, column_type));
+    }
+
+    row_map
+}
+
+/// Decode a value, trying the Rust type that matches the column type first
+///
+/// Without the hint a boolean `0` could decode as an integer and a whole-number
+/// float as `i64`. Columns of other or unknown types try String, i64, i32, f64
+/// and bool in that order.
+fn decode_value(row: &AnyRow, idx: usize, column_type: ColumnType) -> Value {
+    let typed = match column_type {
+        ColumnType::Boolean => row.try_get::<bool, _>(idx).ok().map(Value::Bool),
+        ColumnType::Integer => row
+            .try_get::<i64, _>(idx)
+            .or_else(|_| row.try_get::<i32, _>(idx).map(i64::from))
+            .ok()
+            .map(|val| Value::Number(val.into())),
+        ColumnType::Float => row.try_get::<f64, _>(idx).ok().map(float_value),
+        _ => None,
+    };
+    if let Some(value) = typed {
+        return value;
+    }
+
+    if let Ok(val) = row.try_get::<String, _>(idx) {
+        Value::String(val)
+    } else if let Ok(val) = row.try_get::<i64, _>(idx) {
+        Value::Number(val.into())
+    } else if let Ok(val) = row.try_get::<i32, _>(idx) {
+        Value::Number(val.into())
+    } else if let Ok(val) = row.try_get::<f64, _>(idx) {
+        float_value(val)
+    } else if let Ok(val) = row.try_get::<bool, _>(idx) {
+        Value::Bool(val)
+    } else {
+        // NULL, or a value none of the types above can decode
+        Value::Null
+    }
+}
+
+fn float_value(val: f64) -> Value {
+    Value::Number(serde_json::Number::from_f64(val).unwrap_or_else(|| serde_json::Number::from(0)))
+}
+
+/// Normalized type of each result column, as reported by the driver
+pub(crate) fn column_types(rows: &[AnyRow]) -> Vec<NormalizedType> {
+    rows.first()
+        .map(|row| {
+            row.columns()
+                .iter()
+                .map(|column| NormalizedType::from_data_type(column.type_info().name()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Fetch all rows of an unparameterized query along with their column types
+///
+/// The Any driver refuses to decode SQLite columns declared BOOLEAN, DATE, TIME or
+/// DATETIME even though SQLite stores them as plain integers, reals or text. When
+/// that happens the query is re-run with those columns selected as `+"column"`
+/// expressions, which carry no declared type, and the declared types are reported
+/// in their place so `coerce_values` can still turn `0`/`1` into booleans. `sql`
+/// has to be a single statement from `executable_sql` or built by the caller, never
+/// SQL as the user wrote it.
+pub(crate) async fn fetch_all_with_declared_types(
+    creds: &DatabaseCredentials,
+    conn: &mut AnyConnection,
+    sql: &str,
+) -> Result<(Vec<AnyRow>, Vec<NormalizedType>), sqlx::Error> {
+    let error = match sqlx::query(sql).fetch_all(&mut *conn).await {
+        Ok(rows) => {
+            let types = column_types(&rows);
+            return Ok((rows, types));
+        }
+        Err(e @ sqlx::Error::ColumnDecode { .. })
+            if matches!(creds.db_type, DatabaseType::SQLite) =>
+        {
+            e
+        }
+        Err(e) => return Err(e),
+    };
+
+    let Some(columns) = sqlite_declared_columns(creds, sql).await else {
+        return Err(error);
+    };
+    let select_list = columns
+        .iter()
+        .map(|(name, declared)| {
+            let quoted = quote_identifier(name, &DatabaseType::SQLite);
+            match declared {
+                Some(_) => format!("+{} AS {}", quoted, quoted),
+                None => quoted,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let wrapped = format!(
+        "SELECT {} FROM ({}) AS _inspektor_sqlite",
+        select_list,
+        sql.trim().trim_end_matches(';')
+    );
+
+    let rows = sqlx::query(&wrapped).fetch_all(&mut *conn).await?;
+    let mut types = column_types(&rows);
+    for (column_type, (_, declared)) in types.iter_mut().zip(&columns) {
+        if let Some(declared) = declared {
+            *column_type = *declared;
+        }
+    }
+    Ok((rows, types))
+}
+
+/// Result columns of a SQLite query, with the normalized type of each one the Any
+/// driver cannot decode
+///
+/// Uses a separate native connection because describing through the Any driver
+/// fails on the same declared types. Returns `None` when the query cannot be
+/// described or its column names are not unique, since it then cannot be rewritten.
+async fn sqlite_declared_columns(
+    creds: &DatabaseCredentials,
+    sql: &str,
+) -> Option<Vec<(String, Option<NormalizedType>)>> {
+    use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection};
+    use sqlx::{ConnectOptions, Connection, Executor};
+
+    let url = build_full_connection_uri(creds).ok()?;
+    let options = SqliteConnectOptions::from_url(&url)
+        .ok()?
+        .create_if_missing(false)
+        .read_only(true);
+    let mut conn = SqliteConnection::connect_with(&options).await.ok()?;
+    let described = conn.describe(sql).await;
+    conn.close().await.ok();
+    let described = described.ok()?;
+
+    let mut columns: Vec<(String, Option<NormalizedType>)> = Vec::new();
+    for column in described.columns() {
+        let name = column.name().to_string();
+        if columns.iter().any(|(existing, _)| existing == &name) {
+            return None;
+        }
+        let type_name = column.type_info().name();
+        let declared = matches!(type_name, "BOOLEAN" | "DATE" | "TIME" | "DATETIME")
+            .then(|| NormalizedType::from_data_type(type_name));
+        columns.push((name, declared));
+    }
+    Some(columns)
+}
+
+/// Convert values to the JSON representation that matches their column type
+///
+/// Timestamps become ISO 8601 strings, MySQL `0`/`1` booleans become `true`/`false`,
+/// JSON columns are embedded as structured JSON and UUIDs are normalized to their
+/// lowercase hyphenated form. Values that do not parse are left as they are.
+/// Returns the number of values that were changed.
+pub(crate) fn coerce_values(
+    columns: &[String],
+    column_types: &[NormalizedType],
+    rows: &mut [HashMap<String, Value>],
+) -> u32 {
+    let mut coerced = 0;
+
+    for (column, column_type) in columns.iter().zip(column_types) {
+        for row in rows.iter_mut() {
+            let Some(value) = row.get_mut(column) else {
+                continue;
+            };
+            if let Some(converted) = coerce_value(value, column_type) {
+                if converted != *value {
+                    *value = converted;
+                    coerced += 1;
+                }
+            }
+        }
+    }
+
+    coerced
+}
+
+fn coerce_value(value: &Value, column_type: &NormalizedType) -> Option<Value> {
+    match (column_type, value) {
+        (NormalizedType::Timestamp, Value::String(s)) => {
+            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s)
+                .or_else(|_| chrono::DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f%#z"))
+            {
+                return Some(Value::String(dt.to_rfc3339()));
+            }
+            chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+                .ok()
+                .map(|dt| Value::String(dt.format("%Y-%m-%dT%H:%M:%S%.f").to_string()))
+        }
+        (NormalizedType::Boolean, Value::Number(n)) => match n.as_i64() {
+            Some(0) => Some(Value::Bool(false)),
+            Some(1) => Some(Value::Bool(true)),
+            _ => None,
+        },
+        (NormalizedType::Json, Value::String(s)) => serde_json::from_str(s).ok(),
+        (NormalizedType::Uuid, Value::String(s)) => uuid::Uuid::parse_str(s)
+            .ok()
+            .map(|uuid| Value::String(uuid.hyphenated().to_string())),
+        _ => None,
+    }
+}
+
+/// Maximum number of rows returned by a table data search
+const MAX_SEARCH_ROWS: u64 = 1000;
+
+/// Search the text columns of a table for a term
+///
+/// All text columns are searched unless `search.columns` narrows the search down.
+/// The term is matched literally, with `%` and `_` escaped. On Postgres, tables
+/// with a GIN index are also matched through full-text search.
+pub async fn search_table(
+    database_id: &str,
+    table_name: &str,
+    search: &TableSearchOptions,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<QueryResult, DatabaseError> {
+    let creds = store.get(database_id)?;
+    let search_term = search.search_term.as_str();
+    let table_schema = match creds.db_type {
+        DatabaseType::Postgres => Some(search.schema.as_deref().unwrap_or("public")),
+        _ => search.schema.as_deref(),
+    };
+
+    let table_list = format!("('{}')", table_name.replace('\'', "''"));
+    let schema = get_table_schema(database_id, &table_list, table_schema, store, pools)
+        .await?
+        .into_iter()
+        .find(|s| s.table_name == table_name)
+        .ok_or_else(|| DatabaseError::Other(format!("Table not found: {}", table_name)))?;
+
+    let target_columns: Vec<String> = match &search.columns {
+        Some(requested) => {
+            // Only accept columns that actually exist so nothing user-supplied reaches the SQL
+            for name in requested {
+                if !schema.columns.iter().any(|col| &col.name == name) {
+                    return Err(DatabaseError::Other(format!(
+                        "Column not found in {}: {}",
+                        table_name, name
+                    )));
+                }
+            }
+            requested.to_vec()
+        }
+        None => schema
+            .columns
+            .iter()
+            .filter(|col| NormalizedType::from_data_type(&col.data_type) == NormalizedType::Text)
+            .map(|col| col.name.clone())
+            .collect(),
+    };
+
+    if target_columns.is_empty() {
+        return Err(DatabaseError::Other(format!(
+            "Table {} has no text columns to search",
+            table_name
+        )));
+    }
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+    let pattern = format!("%{}%", escape_like(search_term));
+    let limit = search.limit.clamp(1, MAX_SEARCH_ROWS);
+    let quoted: Vec<String> = target_columns
+        .iter()
+        .map(|col| quote_identifier(col, &creds.db_type))
+        .collect();
+
+    let mut conditions: Vec<String> = match creds.db_type {
+        DatabaseType::Postgres => quoted
+            .iter()
+            .map(|col| format!("{}::text ILIKE $1 ESCAPE '!'", col))
+            .collect(),
+        DatabaseType::MySQL | DatabaseType::SQLite | DatabaseType::ClickHouse => quoted
+            .iter()
+            .map(|col| format!("{} LIKE ? ESCAPE '!'", col))
+            .collect(),
+    };
+
+    let use_full_text = match (&creds.db_type, table_schema) {
+        (DatabaseType::Postgres, Some(table_schema)) => {
+            has_gin_index(&pool, table_schema, table_name).await?
+        }
+        _ => false,
+    };
+    if use_full_text {
+        let document = quoted
+            .iter()
+            .map(|col| format!("coalesce({}::text, '')", col))
+            .collect::<Vec<_>>()
+            .join(" || ' ' || ");
+        conditions.push(format!(
+            "to_tsvector('simple', {}) @@ plainto_tsquery('simple', $2)",
+            document
+        ));
+    }
+
+    let sql = format!(
+        "SELECT * FROM {} WHERE {} LIMIT {}",
+        qualified_table_name(table_name, table_schema, &creds.db_type),
+        conditions.join(" OR "),
+        limit
+    );
+
+    let mut query = sqlx::query(&sql);
+    match creds.db_type {
+        DatabaseType::Postgres => {
+            query = query.bind(pattern.clone());
+            if use_full_text {
+                query = query.bind(search_term.to_string());
+            }
+        }
+        DatabaseType::MySQL | DatabaseType::SQLite | DatabaseType::ClickHouse => {
+            // Positional placeholders need one binding per occurrence
+            for _ in &target_columns {
+                query = query.bind(pattern.clone());
+            }
+        }
+    }
+
+    let start = Instant::now();
+    let rows = query
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, &sql))?;
+    let execution_time = start.elapsed();
+
+    let (result_columns, mut result_rows) = convert_rows(&rows);
+
+    let needle = search_term.to_lowercase();
+    let matched_columns = target_columns
+        .into_iter()
+        .filter(|col| {
+            result_rows.iter().any(|row| match row.get(col) {
+                Some(Value::String(v)) => v.to_lowercase().contains(&needle),
+                Some(Value::Null) | None => false,
+                Some(other) => other.to_string().to_lowercase().contains(&needle),
+            })
+        })
+        .collect();
+    let masked_columns_applied = apply_masking(&creds, &result_columns, &mut result_rows);
+
+    Ok(QueryResult {
+        columns: result_columns,
+        row_count: result_rows.len(),
+        rows: result_rows,
+        execution_time_ms: execution_time.as_millis() as u64,
+        matched_columns,
+        total_rows: None,
+        page: 0,
+        page_size: 0,
+        masked_columns_applied,
+        value_coercions_applied: 0,
+        sampling_method_used: None,
+        column_types: column_types(&rows),
+        was_truncated: false,
+    })
+}
+
+/// Escape the LIKE wildcards of a search term, using `!` as the escape character
+///
+/// `!` is used rather than a backslash, since MySQL would read a backslash in the
+/// `ESCAPE` literal as a string escape.
+fn escape_like(term: &str) -> String {
+    let mut escaped = String::with_capacity(term.len());
+    for c in term.chars() {
+        if matches!(c, '!' | '%' | '_') {
+            escaped.push('!');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Check whether a Postgres table has a GIN index (typically used for full-text search)
+async fn has_gin_index(
+    pool: &sqlx::AnyPool,
+    schema: &str,
+    table_name: &str,
+) -> Result<bool, DatabaseError> {
+    let query = format!(
+        "SELECT COUNT(*)::bigint AS gin_count FROM pg_indexes
+        WHERE schemaname = '{}' AND tablename = '{}' AND indexdef ILIKE '%USING gin%'",
+        schema.replace('\'', "''"),
+        table_name.replace('\'', "''")
+    );
+    let row = sqlx::query(&query)
+        .fetch_one(pool)
+        .await
+        .map_err(DatabaseError::from)?;
+    let count: i64 = row.try_get("gin_count").unwrap_or(0);
+    Ok(count > 0)
+}
+
+/// Maximum number of rows returned by a table sample
+const MAX_SAMPLE_ROWS: u64 = 10_000;
+
+/// Fetch a page of a table in primary key order, starting after the key `after`
+///
+/// Keyset pagination filters on the primary key instead of skipping rows, so late
+/// pages are as fast as the first. The table needs a single-column primary key.
+/// One row more than `page_size` is fetched to tell whether another page follows;
+/// pass `next_cursor` as `after` to get it. The cursor is taken before masking so
+/// it still matches a row when the key column is masked.
+pub async fn fetch_table_page(
+    database_id: &str,
+    table_name: &str,
+    schema: Option<&str>,
+    page_size: u64,
+    after: Option<&Value>,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<PageResult, DatabaseError> {
+    let max_page_size = max_page_size();
+    if page_size == 0 || page_size > max_page_size {
+        return Err(DatabaseError::QueryError(format!(
+            "page_size must be between 1 and {}",
+            max_page_size
+        )));
+    }
+
+    let creds = store.get(database_id)?;
+
+    let table_list = format!("('{}')", table_name.replace('\'', "''"));
+    let table_schema = get_table_schema(database_id, &table_list, schema, store, pools)
+        .await?
+        .into_iter()
+        .find(|s| s.table_name == table_name)
+        .ok_or_else(|| DatabaseError::Other(format!("Table not found: {}", table_name)))?;
+    let primary_key = match table_schema
+        .columns
+        .iter()
+        .filter(|col| col.is_primary_key)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [column] => (*column).clone(),
+        _ => {
+            return Err(DatabaseError::QueryError(format!(
+                "Keyset pagination needs a single-column primary key, which {} does not have",
+                table_name
+            )))
+        }
+    };
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+
+    let key_type = ColumnType::from_data_type(&primary_key.data_type);
+    let key = quote_identifier(&primary_key.name, &creds.db_type);
+    // Postgres does not compare a text or bigint parameter with every key type
+    let placeholder = match creds.db_type {
+        DatabaseType::Postgres => format!(
+            "CAST($1 AS {})",
+            postgres_column_type(&pool, table_name, schema, &primary_key.name).await?
+        ),
+        DatabaseType::MySQL | DatabaseType::SQLite | DatabaseType::ClickHouse => "?".to_string(),
+    };
+    let sql = format!(
+        "SELECT * FROM {}{} ORDER BY {} LIMIT {}",
+        qualified_table_name(table_name, schema, &creds.db_type),
+        if after.is_some() {
+            format!(" WHERE {} > {}", key, placeholder)
+        } else {
+            String::new()
+        },
+        key,
+        page_size + 1
+    );
+
+    let query = match after {
+        Some(cursor) => bind_value(sqlx::query(&sql), &cursor_value(cursor, key_type)?),
+        None => sqlx::query(&sql),
+    };
+
+    let (mut conn, canceller) = QueryCanceller::acquire(&creds.db_type, &pool).await?;
+    let mut rows = canceller
+        .timeout(&pool, DEFAULT_QUERY_TIMEOUT_SECS, query.fetch_all(&mut *conn))
+        .await?
+        .map_err(|e| DatabaseError::query_failed(e, &sql))?;
+
+    let has_more = rows.len() as u64 > page_size;
+    rows.truncate(page_size as usize);
+
+    let (columns, mut result_rows) = convert_rows(&rows);
+    let next_cursor = if has_more {
+        // Without a cursor the caller would take this for the last page
+        let cursor = result_rows
+            .last()
+            .and_then(|row| row.get(&primary_key.name))
+            .filter(|value| !value.is_null())
+            .cloned()
+            .ok_or_else(|| {
+                DatabaseError::QueryError(format!(
+                    "The {} key of {} could not be read as a pagination cursor",
+                    primary_key.name, table_name
+                ))
+            })?;
+        Some(cursor)
+    } else {
+        None
+    };
+    coerce_values(&columns, &column_types(&rows), &mut result_rows);
+    apply_masking(&creds, &columns, &mut result_rows);
+
+    Ok(PageResult {
+        rows: result_rows,
+        has_more,
+        next_cursor,
+    })
+}
+
+/// Type of a Postgres column as a quoted, schema-qualified name usable in a CAST
+///
+/// `information_schema.columns.data_type` is `USER-DEFINED` for enums and domains
+/// and `ARRAY` for arrays, so the underlying `udt_schema` and `udt_name` are used.
+async fn postgres_column_type(
+    pool: &sqlx::AnyPool,
+    table_name: &str,
+    schema: Option<&str>,
+    column_name: &str,
+) -> Result<String, DatabaseError> {
+    let sql = "SELECT udt_schema::text AS udt_schema, udt_name::text AS udt_name
+        FROM information_schema.columns
+        WHERE table_schema = $1 AND table_name = $2 AND column_name = $3";
+    let row = sqlx::query(sql)
+        .bind(schema.unwrap_or("public"))
+        .bind(table_name)
+        .bind(column_name)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| DatabaseError::query_failed(e, sql))?;
+    let udt_schema: String = row.try_get("udt_schema").map_err(DatabaseError::from)?;
+    let udt_name: String = row.try_get("udt_name").map_err(DatabaseError::from)?;
+    Ok(format!(
+        "{}.{}",
+        quote_identifier(&udt_schema, &DatabaseType::Postgres),
+        quote_identifier(&udt_name, &DatabaseType::Postgres)
+    ))
+}
+
+/// Convert a pagination cursor to the JSON type that binds as the key's type
+fn cursor_value(cursor: &Value, key_type: ColumnType) -> Result<Value, DatabaseError> {
+    let invalid = || {
+        DatabaseError::QueryError(format!("Invalid cursor for a {:?} key: {}", key_type, cursor))
+    };
+
+    match (key_type, cursor) {
+        (_, Value::Null | Value::Array(_) | Value::Object(_)) => Err(invalid()),
+        (ColumnType::Integer, Value::Number(n)) if n.is_i64() => Ok(cursor.clone()),
+        (ColumnType::Integer, Value::String(s)) => {
+            s.trim().parse::<i64>().map(Value::from).map_err(|_| invalid())
+        }
+        (ColumnType::Integer, _) => Err(invalid()),
+        (ColumnType::Float, Value::Number(_)) => Ok(cursor.clone()),
+        (ColumnType::Float, Value::String(s)) => {
+            s.trim().parse::<f64>().map(Value::from).map_err(|_| invalid())
+        }
+        (ColumnType::Float, _) => Err(invalid()),
+        (_, Value::String(_)) => Ok(cursor.clone()),
+        (_, other) => Ok(Value::String(other.to_string())),
+    }
+}
+
+/// Fetch a random sample of roughly `sample_percent` percent of a table's rows
+///
+/// Postgres uses `TABLESAMPLE` with the given method. MySQL has no TABLESAMPLE and
+/// filters on `RAND()`, SQLite picks random rowids; both ignore `method`. Samples
+/// are capped at `MAX_SAMPLE_ROWS` rows.
+pub async fn sample_table(
+    database_id: &str,
+    table_name: &str,
+    sample_percent: f32,
+    method: SampleMethod,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<QueryResult, DatabaseError> {
+    if !(sample_percent > 0.0 && sample_percent <= 100.0) {
+        return Err(DatabaseError::QueryError(
+            "sample_percent must be greater than 0 and at most 100".to_string(),
+        ));
+    }
+
+    let creds = store.get(database_id)?;
+    let table = quote_identifier(table_name, &creds.db_type);
+
+    let (sql, method_used) = match creds.db_type {
+        DatabaseType::Postgres => {
+            let method_name = match method {
+                SampleMethod::System => "SYSTEM",
+                SampleMethod::Bernoulli => "BERNOULLI",
+            };
+            (
+                format!(
+                    "SELECT * FROM {} TABLESAMPLE {}({}) LIMIT {}",
+                    table, method_name, sample_percent, MAX_SAMPLE_ROWS
+                ),
+                format!("TABLESAMPLE {}", method_name),
+            )
+        }
+        DatabaseType::MySQL => (
+            format!(
+                "SELECT * FROM {} WHERE RAND() < {} LIMIT {}",
+                table,
+                sample_percent / 100.0,
+                MAX_SAMPLE_ROWS
+            ),
+            "RAND() filter".to_string(),
+        ),
+        DatabaseType::SQLite => (
+            format!(
+                "SELECT * FROM {0} WHERE rowid IN (SELECT rowid FROM {0} ORDER BY random() LIMIT (SELECT MIN(CAST(COUNT(*) * {1} / 100.0 AS INTEGER), {2}) FROM {0}))",
+                table, sample_percent, MAX_SAMPLE_ROWS
+            ),
+            "random() rowid selection".to_string(),
+        ),
+        DatabaseType::ClickHouse => return Err(DatabaseError::UnsupportedType),
+    };
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+    let (mut conn, canceller) = QueryCanceller::acquire(&creds.db_type, &pool).await?;
+
+    let start = Instant::now();
+    let fetch = sqlx::query(&sql).fetch_all(&mut *conn);
+    let rows = canceller
+        .timeout(&pool, DEFAULT_QUERY_TIMEOUT_SECS, fetch)
+        .await?
+        .map_err(|e| DatabaseError::query_failed(e, &sql))?;
+    let execution_time = start.elapsed();
+
+    let (columns, mut result_rows) = convert_rows(&rows);
+    let column_types = column_types(&rows);
+    let value_coercions_applied = coerce_values(&columns, &column_types, &mut result_rows);
+    let masked_columns_applied = apply_masking(&creds, &columns, &mut result_rows);
+
+    Ok(QueryResult {
+        columns,
+        row_count: result_rows.len(),
+        rows: result_rows,
+        execution_time_ms: execution_time.as_millis() as u64,
+        matched_columns: Vec::new(),
+        total_rows: None,
+        page: 0,
+        page_size: 0,
+        masked_columns_applied,
+        value_coercions_applied,
+        sampling_method_used: Some(method_used),
+        column_types,
+        was_truncated: false,
+    })
+}
+
+/// Get the query plan for a statement
+///
+/// Plain EXPLAIN never runs the statement, so destructive statements may be
+/// explained. EXPLAIN ANALYZE does run it, so with `analyze` the statement has to
+/// pass the same checks as `execute_query`.
+pub async fn explain_query(
+    database_id: &str,
+    sql: &str,
+    analyze: bool,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<ExplainResult, DatabaseError> {
+    let creds = store.get(database_id)?;
+
+    let executable = executable_sql(sql, &creds.db_type, analyze)?;
+    if executable.statements.len() > 1 {
+        return Err(DatabaseError::QueryError(
+            "Only a single statement can be explained".to_string(),
+        ));
+    }
+
+    let prefix = match (&creds.db_type, analyze) {
+        (DatabaseType::Postgres, false) | (DatabaseType::MySQL, false) => "EXPLAIN",
+        (DatabaseType::Postgres, true) | (DatabaseType::MySQL, true) => "EXPLAIN ANALYZE",
+        (DatabaseType::SQLite, false) => "EXPLAIN QUERY PLAN",
+        (DatabaseType::SQLite, true) => {
+            return Err(DatabaseError::QueryError(
+                "SQLite does not support EXPLAIN ANALYZE".to_string(),
+            ));
+        }
+        (DatabaseType::ClickHouse, _) => return Err(DatabaseError::UnsupportedType),
+    };
+
+    let pool = pools.get_or_create(database_id, &creds).await?;
+    let (mut conn, canceller) = QueryCanceller::acquire(&creds.db_type, &pool).await?;
+    let explain_sql = format!("{} {}", prefix, executable.sql);
+
+    // EXPLAIN ANALYZE runs the query, so it gets the same timeout as the query itself
+    let fetch = sqlx::query(&explain_sql).fetch_all(&mut *conn);
+    let rows = canceller
+        .timeout(&pool, DEFAULT_QUERY_TIMEOUT_SECS, fetch)
+        .await?
+        .map_err(DatabaseError::from)?;
+
+    let (columns, plan_rows) = convert_rows(&rows);
+
+    // SQLite puts the readable plan in "detail"; Postgres and MySQL ANALYZE return a
+    // single text column; MySQL EXPLAIN returns a table, rendered tab-separated
+    let text_columns: Vec<&String> = match columns.iter().find(|c| c.as_str() == "detail") {
+        Some(detail) => vec![detail],
+        None => columns.iter().collect(),
+    };
+    let plan_text = plan_rows
+        .iter()
+        .map(|row| {
+            text_columns
+                .iter()
+                .map(|col| match row.get(*col) {
+                    Some(Value::String(v)) => v.clone(),
+                    Some(Value::Null) | None => String::new(),
+                    Some(other) => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("\t")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(ExplainResult {
+        plan_text,
+        plan_rows,
+    })
+}
+
+/// Pretty-print SQL: one clause per line, indented subqueries and uppercase keywords
+///
+/// With a `dialect` hint (`postgres`, `mysql`, `sqlite` or `clickhouse`) the input and the output
+/// are both parsed with that dialect and the formatted SQL is only returned when
+/// both parse to the same statements. Input the parser does not understand is
+/// formatted without this check.
+pub fn format_query(sql: &str, dialect: Option<&str>) -> Result<String, DatabaseError> {
+    let db_type = dialect
+        .map(|d| match d.to_lowercase().as_str() {
+            "postgres" | "postgresql" => Ok(DatabaseType::Postgres),
+            "mysql" => Ok(DatabaseType::MySQL),
+            "sqlite" => Ok(DatabaseType::SQLite),
+            "clickhouse" => Ok(DatabaseType::ClickHouse),
+            other => Err(DatabaseError::QueryError(format!("Unknown SQL dialect: {}", other))),
+        })
+        .transpose()?;
+
+    let options = FormatOptions {
+        indent: Indent::Spaces(2),
+        uppercase: true,
+        lines_between_queries: 1,
+    };
+    let formatted = sqlformat::format(sql, &QueryParams::None, options);
+
+    // Statements are compared ignoring case, since the formatter may also uppercase
+    // unquoted names such as COUNT
+    let render = |statements: Vec<Statement>| {
+        statements
+            .iter()
+            .map(|statement| statement.to_string().to_lowercase())
+            .collect::<Vec<_>>()
+    };
+    if let Some(db_type) = db_type {
+        if let Ok(original) = parse_statements(sql, &db_type) {
+            let reformatted = parse_statements(&formatted, &db_type).ok().map(render);
+            if reformatted != Some(render(original)) {
+                return Err(DatabaseError::QueryError(
+                    "Formatting would change the meaning of the query".to_string(),
+                ));
+            }
+        }
+    }
+
+    Ok(formatted)
+}
+
+/// Keywords suggested by `get_autocomplete_context` outside table and column names
+const COMPLETION_KEYWORDS: &[&str] = &[
+    "SELECT", "FROM", "WHERE", "JOIN", "LEFT JOIN", "INNER JOIN", "ON", "AND", "OR", "NOT",
+    "IN", "IS NULL", "IS NOT NULL", "LIKE", "BETWEEN", "AS", "DISTINCT", "GROUP BY",
+    "ORDER BY", "HAVING", "LIMIT", "OFFSET", "UNION", "WITH", "CASE", "WHEN", "THEN", "ELSE",
+    "END", "ASC", "DESC", "COUNT", "SUM", "AVG", "MIN", "MAX",
+];
+
+/// Words that end a table reference instead of being its alias
+const ALIAS_STOP_WORDS: &[&str] = &[
+    "WHERE", "ON", "USING", "JOIN", "LEFT", "RIGHT", "INNER", "OUTER", "FULL", "CROSS",
+    "NATURAL", "GROUP", "ORDER", "HAVING", "LIMIT", "OFFSET", "UNION", "SET", "VALUES",
+    "RETURNING", "WINDOW",
+];
+
+/// What the word under the cursor of a partial query refers to
+#[derive(Debug, PartialEq)]
+enum CompletionPosition {
+    /// After FROM, JOIN, INTO, UPDATE or TABLE
+    Table { prefix: String },
+    /// After `table.` or `alias.`, with the alias resolved to its table
+    Column { table: String, prefix: String },
+    /// Anywhere else, with the tables the query references
+    Other { prefix: String, tables: Vec<String> },
+}
+
+/// Work out what to complete at byte offset `cursor` of a partial query
+///
+/// This looks at words rather than parsing the SQL, since the query is usually
+/// incomplete while it is being typed.
+fn completion_position(sql: &str, cursor: usize) -> CompletionPosition {
+    let mut end = cursor.min(sql.len());
+    while !sql.is_char_boundary(end) {
+        end -= 1;
+    }
+    let before = &sql[..end];
+
+    let is_word_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '.' | '"' | '`');
+    let word_start = before
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !is_word_char(*c))
+        .map_or(0, |(idx, c)| idx + c.len_utf8());
+    let word = &before[word_start..];
+    let references = table_references(sql);
+
+    if let Some((qualifier, prefix)) = word.rsplit_once('.') {
+        let qualifier = unquote_identifier(qualifier);
+        let table = references
+            .iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(&qualifier))
+            .map(|(_, table)| table.clone())
+            .unwrap_or(qualifier);
+        return CompletionPosition::Column {
+            table,
+            prefix: unquote_identifier(prefix),
+        };
+    }
+
+    let previous = before[..word_start]
+        .split_whitespace()
+        .last()
+        .unwrap_or_default()
+        .to_uppercase();
+    if matches!(previous.as_str(), "FROM" | "JOIN" | "INTO" | "UPDATE" | "TABLE") {
+        return CompletionPosition::Table {
+            prefix: unquote_identifier(word),
+        };
+    }
+
+    let mut tables: Vec<String> = Vec::new();
+    for (_, table) in references {
+        if !tables.contains(&table) {
+            tables.push(table);
+        }
+    }
+    CompletionPosition::Other {
+        prefix: unquote_identifier(word),
+        tables,
+    }
+}
+
+/// Find `(alias, table)` pairs for the tables named after FROM, JOIN, INTO and UPDATE
+///
+/// Tables without an alias are paired with their own name. Schema qualifiers are
+/// dropped.
+fn table_references(sql: &str) -> Vec<(String, String)> {
+    let words: Vec<&str> = sql
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | '(' | ')' | ';'))
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    let mut references = Vec::new();
+    for (idx, word) in words.iter().enumerate() {
+        if !matches!(
+            word.to_uppercase().as_str(),
+            "FROM" | "JOIN" | "INTO" | "UPDATE"
+        ) {
+            continue;
+        }
+        let Some(name) = words.get(idx + 1) else {
+            continue;
+        };
+        let table = unquote_identifier(name.rsplit('.').next().unwrap_or(name));
+        if table.is_empty() || table.eq_ignore_ascii_case("SELECT") {
+            continue;
+        }
+
+        let mut alias_idx = idx + 2;
+        if words
+            .get(alias_idx)
+            .is_some_and(|word| word.eq_ignore_ascii_case("AS"))
+        {
+            alias_idx += 1;
+        }
+        let alias = words.get(alias_idx).filter(|alias| {
+            !ALIAS_STOP_WORDS.contains(&alias.to_uppercase().as_str())
+                && alias.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '"' | '`'))
+        });
+
+        references.push((table.clone(), table.clone()));
+        if let Some(alias) = alias {
+            references.push((unquote_identifier(alias), table));
+        }
+    }
+
+    references
+}
+
+/// Convert an offset in UTF-16 code units, as JavaScript counts string positions,
+/// to a byte offset of `sql`
+///
+/// Offsets past the end of the string or inside a character are moved back to the
+/// last character that ends before them.
+fn utf16_to_byte_offset(sql: &str, utf16_offset: usize) -> usize {
+    let mut units = 0;
+    for (idx, c) in sql.char_indices() {
+        units += c.len_utf16();
+        if units > utf16_offset {
+            return idx;
+        }
+    }
+    sql.len()
+}
+
+/// Strip the quotes around an identifier
+fn unquote_identifier(name: &str) -> String {
+    name.trim_matches(|c| c == '"' || c == '`').to_string()
+}
+
+/// Suggest tables, columns or keywords for the cursor position in a partial query
+///
+/// After FROM or JOIN the tables of the database are suggested, after `table.` or
+/// `alias.` the columns of that table. Elsewhere keywords are suggested along with
+/// the columns of the tables the query already references. Suggestions start with
+/// the word under the cursor, ignoring case. `cursor_position` is counted in UTF-16
+/// code units, like JavaScript string offsets in the editor. Tables and columns come
+/// from the metadata cached in the `PoolRegistry`, which is read on first use.
+pub async fn get_autocomplete_context(
+    database_id: &str,
+    partial_sql: &str,
+    cursor_position: usize,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<AutocompleteContext, DatabaseError> {
+    let mut context = AutocompleteContext::default();
+    let cursor = utf16_to_byte_offset(partial_sql, cursor_position);
+    match completion_position(partial_sql, cursor) {
+        CompletionPosition::Table { prefix } => {
+            let tables = match pools.cached_tables(database_id).await {
+                Some(tables) => tables,
+                None => {
+                    let tables = get_tables(database_id, store, pools).await?;
+                    pools.cache_tables(database_id, tables.clone()).await;
+                    tables
+                }
+            };
+            context.table_suggestions = tables
+                .into_iter()
+                .map(|table| table.name)
+                .filter(|name| starts_with_ignore_case(name, &prefix))
+                .collect();
+        }
+        CompletionPosition::Column { table, prefix } => {
+            context.column_suggestions = suggest_columns(database_id, &[table], store, pools)
+                .await?
+                .into_iter()
+                .filter(|suggestion| starts_with_ignore_case(&suggestion.column, &prefix))
+                .collect();
+        }
+        CompletionPosition::Other { prefix, tables } => {
+            context.keyword_suggestions = COMPLETION_KEYWORDS
+                .iter()
+                .filter(|keyword| starts_with_ignore_case(keyword, &prefix))
+                .map(|keyword| keyword.to_string())
+                .collect();
+            if !tables.is_empty() {
+                context.column_suggestions = suggest_columns(database_id, &tables, store, pools)
+                    .await?
+                    .into_iter()
+                    .filter(|suggestion| starts_with_ignore_case(&suggestion.column, &prefix))
+                    .collect();
+            }
+        }
+    }
+
+    Ok(context)
+}
+
+fn starts_with_ignore_case(candidate: &str, prefix: &str) -> bool {
+    candidate.to_lowercase().starts_with(&prefix.to_lowercase())
+}
+
+/// Columns of the given tables, in table order
+///
+/// Only the tables missing from the metadata cache are read from the database.
+async fn suggest_columns(
+    database_id: &str,
+    tables: &[String],
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<Vec<ColumnSuggestion>, DatabaseError> {
+    let mut schemas = pools.cached_table_schemas(database_id, tables).await;
+    let missing: Vec<&String> = tables
+        .iter()
+        .filter(|table| !schemas.iter().any(|schema| schema.table_name == **table))
+        .collect();
+
+    if !missing.is_empty() {
+        let table_list = format!(
+            "({})",
+            missing
+                .iter()
+                .map(|table| format!("'{}'", table.replace('\'', "''")))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        let fetched = get_table_schema(database_id, &table_list, None, store, pools).await?;
+        pools.cache_table_schemas(database_id, &fetched).await;
+        schemas.extend(fetched);
+    }
+    schemas.sort_by_key(|schema| tables.iter().position(|t| *t == schema.table_name));
+
+    Ok(schemas
+        .into_iter()
+        .flat_map(|schema| {
+            let table = schema.table_name;
+            schema.columns.into_iter().map(move |col| ColumnSuggestion {
+                table: table.clone(),
+                column: col.name,
+                data_type: col.data_type,
+            })
+        })
+        .collect())
+}
+
+// Tauri command for executing queries
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_sql_query(
+    database_id: String,
+    sql: String,
+    page_size: Option<u64>,
+    page: Option<u64>,
+    timeout_secs: Option<u64>,
+    max_rows: Option<u64>,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+    history: State<'_, QueryHistory>,
+    app: AppHandle,
+) -> Result<QueryResult, DatabaseErrorContext> {
+    let options = QueryOptions {
+        page_size,
+        page,
+        timeout_secs,
+        max_rows,
+    };
+    let result = execute_query(&database_id, &sql, &options, &store, &pools).await;
+    let result = recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "execute_query"))?;
+
+    // A history write failure should not hide the query result
+    if let Err(e) = history.record(
+        &database_id,
+        &sql,
+        result.execution_time_ms,
+        result.row_count,
+    ) {
+        eprintln!("Failed to save query history: {}", e);
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_sorted_sql_query(
+    database_id: String,
+    sql: String,
+    sort_column: String,
+    sort_direction: SortDirection,
+    page_size: Option<u64>,
+    page: Option<u64>,
+    timeout_secs: Option<u64>,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+    history: State<'_, QueryHistory>,
+    app: AppHandle,
+) -> Result<QueryResult, DatabaseErrorContext> {
+    let options = QueryOptions {
+        page_size,
+        page,
+        timeout_secs,
+        ..Default::default()
+    };
+    let result = execute_sorted_query(
+        &database_id,
+        &sql,
+        &sort_column,
+        sort_direction,
+        &options,
+        &store,
+        &pools,
+    )
+    .await;
+    let result = recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "execute_sorted_query"))?;
+
+    // A history write failure should not hide the query result
+    if let Err(e) = history.record(
+        &database_id,
+        &sql,
+        result.execution_time_ms,
+        result.row_count,
+    ) {
+        eprintln!("Failed to save query history: {}", e);
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn clear_query_cache() -> Result<(), DatabaseErrorContext> {
+    QUERY_CACHE.clear();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn query_cache_stats() -> Result<QueryCacheStats, DatabaseErrorContext> {
+    Ok(QUERY_CACHE.stats())
+}
+
+#[tauri::command]
+pub async fn set_default_row_limit(limit: u64) -> Result<(), DatabaseErrorContext> {
+    set_row_limit(limit);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_default_row_limit() -> Result<u64, DatabaseErrorContext> {
+    Ok(row_limit())
+}
+
+#[tauri::command]
+pub async fn set_query_max_page_size(max_page_size: u64) -> Result<(), DatabaseErrorContext> {
+    set_max_page_size(max_page_size);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_query_max_page_size() -> Result<u64, DatabaseErrorContext> {
+    Ok(max_page_size())
+}
+
+#[tauri::command]
+pub async fn execute_parameterized_query(
+    database_id: String,
+    sql: String,
+    params: Vec<Value>,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+    history: State<'_, QueryHistory>,
+    app: AppHandle,
+) -> Result<QueryResult, DatabaseErrorContext> {
+    let result = execute_query_with_params(&database_id, &sql, &params, &store, &pools).await;
+    let result = recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "execute_query_with_params"))?;
+
+    // A history write failure should not hide the query result
+    if let Err(e) = history.record(
+        &database_id,
+        &sql,
+        result.execution_time_ms,
+        result.row_count,
+    ) {
+        eprintln!("Failed to save query history: {}", e);
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn execute_sql_transaction(
+    database_id: String,
+    statements: Vec<String>,
+    allow_mutations: bool,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+    history: State<'_, QueryHistory>,
+    app: AppHandle,
+) -> Result<TransactionResult, DatabaseErrorContext> {
+    let result =
+        execute_transaction(&database_id, &statements, allow_mutations, &store, &pools).await;
+    let result = recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "execute_transaction"))?;
+
+    // Only committed statements are worth re-running from the history
+    if result.committed {
+        for (sql, statement) in statements.iter().zip(&result.results) {
+            if let Err(e) = history.record(
+                &database_id,
+                sql,
+                statement.execution_time_ms,
+                statement.row_count,
+            ) {
+                eprintln!("Failed to save query history: {}", e);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_multi_result_query(
+    database_id: String,
+    sql: String,
+    timeout_secs: Option<u64>,
+    allow_mutations: bool,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+    history: State<'_, QueryHistory>,
+    app: AppHandle,
+) -> Result<Vec<QueryResult>, DatabaseErrorContext> {
+    let options = QueryOptions {
+        timeout_secs,
+        ..Default::default()
+    };
+    let result =
+        execute_multi_result(&database_id, &sql, &options, allow_mutations, &store, &pools).await;
+    let results = recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "execute_multi_result"))?;
+
+    let execution_time_ms = results.first().map_or(0, |r| r.execution_time_ms);
+    let row_count = results.iter().map(|r| r.row_count).sum();
+    if let Err(e) = history.record(&database_id, &sql, execution_time_ms, row_count) {
+        eprintln!("Failed to save query history: {}", e);
+    }
+
+    Ok(results)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn search_table_data(
+    database_id: String,
+    table_name: String,
+    schema: Option<String>,
+    search_term: String,
+    columns: Option<Vec<String>>,
+    limit: u64,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<QueryResult, DatabaseErrorContext> {
+    let search = TableSearchOptions {
+        search_term,
+        schema,
+        columns,
+        limit,
+    };
+    let result = search_table(&database_id, &table_name, &search, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "search_table_data").with_table(&table_name)
+        })
+}
+
+#[tauri::command]
+pub async fn sample_table_rows(
+    database_id: String,
+    table_name: String,
+    sample_percent: f32,
+    method: SampleMethod,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<QueryResult, DatabaseErrorContext> {
+    let result = sample_table(
+        &database_id,
+        &table_name,
+        sample_percent,
+        method,
+        &store,
+        &pools,
+    )
+    .await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "sample_table").with_table(&table_name)
+        })
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_table_page(
+    database_id: String,
+    table_name: String,
+    schema: Option<String>,
+    page_size: u64,
+    after: Option<Value>,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<PageResult, DatabaseErrorContext> {
+    let result = fetch_table_page(
+        &database_id,
+        &table_name,
+        schema.as_deref(),
+        page_size,
+        after.as_ref(),
+        &store,
+        &pools,
+    )
+    .await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| {
+            with_context(e, Some(database_id.as_str()), "fetch_table_page").with_table(&table_name)
+        })
+}
+
+#[tauri::command]
+pub async fn explain_sql_query(
+    database_id: String,
+    sql: String,
+    analyze: bool,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<ExplainResult, DatabaseErrorContext> {
+    let result = explain_query(&database_id, &sql, analyze, &store, &pools).await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "explain_query"))
+}
+
+#[tauri::command]
+pub async fn get_sql_autocomplete(
+    database_id: String,
+    partial_sql: String,
+    cursor_position: usize,
+    app: AppHandle,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<AutocompleteContext, DatabaseErrorContext> {
+    let result =
+        get_autocomplete_context(&database_id, &partial_sql, cursor_position, &store, &pools)
+            .await;
+    recheck_on_connection_error(&app, &database_id, result)
+        .await
+        .map_err(|e| with_context(e, Some(database_id.as_str()), "get_autocomplete_context"))
+}
+
+#[tauri::command]
+pub async fn format_sql(
+    sql: String,
+    dialect: Option<String>,
+) -> Result<String, DatabaseErrorContext> {
+    format_query(&sql, dialect.as_deref()).map_err(|e| with_context(e, None, "format_query"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_fixtures::SqliteFixture;
+
+    #[test]
+    fn test_coerce_values() {
+        let columns: Vec<String> = ["created_at", "active", "payload", "id"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let types = [
+            NormalizedType::Timestamp,
+            NormalizedType::Boolean,
+            NormalizedType::Json,
+            NormalizedType::Uuid,
+        ];
+        let mut rows = vec![HashMap::from([
+            ("created_at".to_string(), Value::from("2024-03-01 12:30:00")),
+            ("active".to_string(), Value::from(1)),
+            ("payload".to_string(), Value::from("{\"a\": [1, 2]}")),
+            ("id".to_string(), Value::from("not-a-uuid")),
+        ])];
+
+        assert_eq!(coerce_values(&columns, &types, &mut rows), 3);
+        assert_eq!(rows[0]["created_at"], "2024-03-01T12:30:00");
+        assert_eq!(rows[0]["active"], Value::Bool(true));
+        assert_eq!(rows[0]["payload"]["a"][1], 2);
+        assert_eq!(rows[0]["id"], "not-a-uuid");
+    }
+
+    #[test]
+    fn test_validate_query_allows_read_only_statements() {
+        let db = DatabaseType::Postgres;
+        assert!(validate_query("SELECT 1 -- trailing comment", &db).is_ok());
+        assert!(validate_query("/* report */ SELECT * FROM users WHERE name = 'a;b'", &db).is_ok());
+        assert!(validate_query("WITH t AS (SELECT 1 AS x) SELECT x FROM t;", &db).is_ok());
+        assert_eq!(validate_query("SELECT 1; SELECT 2", &db).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_executable_sql_rejects_comments_the_database_would_run() {
+        // sqlparser nests block comments, SQLite does not and would run the DELETE
+        let nested = "SELECT 1 /* /* */ ; DELETE FROM t; -- */";
+        assert!(matches!(
+            executable_sql(nested, &DatabaseType::SQLite, true),
+            Err(DatabaseError::SQLInjection(_))
+        ));
+        let executable = executable_sql(nested, &DatabaseType::Postgres, true).unwrap();
+        assert_eq!(executable.sql, "SELECT 1");
+
+        // MySQL runs executable comments, which the parser skips
+        for sql in [
+            "SELECT 1 /*! INTO OUTFILE '/tmp/x' */",
+            "SELECT * FROM users /*! FOR UPDATE */",
+        ] {
+            assert!(matches!(
+                executable_sql(sql, &DatabaseType::MySQL, true),
+                Err(DatabaseError::SQLInjection(_))
+            ));
+        }
+
+        // Plain comments are dropped from the SQL that is executed
+        let executable =
+            executable_sql("SELECT 1 /* note */", &DatabaseType::MySQL, true).unwrap();
+        assert_eq!(executable.sql, "SELECT 1");
+
+        let executable =
+            executable_sql("SELECT 1; -- a\nSELECT 2", &DatabaseType::Postgres, true).unwrap();
+        assert_eq!(executable.sql, "SELECT 1;\nSELECT 2");
+    }
+
+    #[test]
+    fn test_locking_statements_are_not_limited() {
+        let executable =
+            executable_sql("SELECT * FROM users FOR UPDATE", &DatabaseType::MySQL, false).unwrap();
+        let validated = ValidatedStatement::new(&executable);
+        assert!(validated.has_locks);
+        assert!(!validated.has_limit);
+    }
+
+    #[test]
+    fn test_query_cache() {
+        let cache = QueryCache::new();
+        let db_type = DatabaseType::Postgres;
+
+        let (normalized, count) = cache.validate("SELECT 1; -- refresh\n", &db_type).unwrap();
+        assert_eq!((normalized.sql.as_str(), count), ("SELECT 1", 1));
+        assert!(!normalized.has_limit);
+        assert!(cache.validate("SELECT 1 LIMIT 5", &db_type).unwrap().0.has_limit);
+        cache.validate("SELECT 1; -- refresh\n", &db_type).unwrap();
+        assert!(cache.validate("DELETE FROM users", &db_type).is_err());
+        assert!(cache.validate("DELETE FROM users", &db_type).is_err());
+        assert_eq!(cache.validate("SELECT 1; SELECT 2", &db_type).unwrap().1, 2);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 5, 2));
+
+        cache.clear();
+        assert_eq!(cache.stats().entries, 0);
+
+        // An entry stored under the same hash for other SQL is not a hit
+        let (select, _) = cache.validate("SELECT 1", &db_type).unwrap();
+        let key = QueryCache::key("DELETE FROM users", &db_type);
+        cache.entries.insert(key, ("SELECT 1".to_string(), select));
+        assert!(cache.validate("DELETE FROM users", &db_type).is_err());
+    }
+
+    #[test]
+    fn test_validate_query_rejects_writes() {
+        let db = DatabaseType::MySQL;
+        assert!(matches!(
+            validate_query("DELETE FROM users", &db),
+            Err(DatabaseError::DestructiveOperation(_))
+        ));
+        assert!(matches!(
+            validate_query("SELECT 1; DROP TABLE users", &db),
+            Err(DatabaseError::DestructiveOperation(_))
+        ));
+        assert!(matches!(
+            validate_query("SELECT (1", &db),
+            Err(DatabaseError::QueryError(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_query_rejects_select_into() {
+        let db = DatabaseType::Postgres;
+        assert!(matches!(
+            validate_query("SELECT * INTO users_copy FROM users", &db),
+            Err(DatabaseError::DestructiveOperation(_))
+        ));
+        assert!(matches!(
+            validate_query("SELECT 1 UNION SELECT id INTO copy FROM users", &db),
+            Err(DatabaseError::DestructiveOperation(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_query_rejects_locking_clauses() {
+        for db in [DatabaseType::Postgres, DatabaseType::MySQL] {
+            assert!(matches!(
+                validate_query("SELECT * FROM users FOR UPDATE", &db),
+                Err(DatabaseError::DestructiveOperation(_))
+            ));
+            assert!(matches!(
+                validate_query("SELECT * FROM users WHERE id = 1 FOR SHARE", &db),
+                Err(DatabaseError::DestructiveOperation(_))
+            ));
+        }
+        assert!(matches!(
+            validate_query(
+                "WITH t AS (SELECT * FROM users FOR UPDATE) SELECT * FROM t",
+                &DatabaseType::Postgres
+            ),
+            Err(DatabaseError::DestructiveOperation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_query_timeout() {
+        let db = SqliteFixture::new();
+
+        let slow = "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c \
+                    WHERE x < 30000000) SELECT COUNT(*) AS n FROM c";
+        let options = QueryOptions {
+            timeout_secs: Some(1),
+            ..Default::default()
+        };
+        let started = Instant::now();
+        let result = execute_query("sqlite-1", slow, &options, &db.store, &db.pools).await;
+        assert!(matches!(result, Err(DatabaseError::QueryTimeout(1))));
+        assert!(started.elapsed() < Duration::from_secs(3));
+
+        // The connection that timed out is not handed out again until it is usable
+        let options = QueryOptions::default();
+        let result = execute_query("sqlite-1", "SELECT 1 AS one", &options, &db.store, &db.pools)
+            .await
+            .unwrap();
+        assert_eq!(result.rows[0]["one"], 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, set INSPEKTOR_TEST_POSTGRES_URL"]
+    async fn test_postgres_query_timeout_cancels_the_statement() {
+        let url = std::env::var("INSPEKTOR_TEST_POSTGRES_URL").unwrap();
+        let dir = std::env::temp_dir().join(format!("inspektor-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let creds = crate::db::types::DatabaseCredentials::from_url(&url).unwrap();
+        let store = CredentialStore::new(dir.join("credentials.json"));
+        store.add(creds.clone()).unwrap();
+        let pools = PoolRegistry::new();
+
+        let marker = format!("inspektor_sleep_{}", uuid::Uuid::new_v4().simple());
+        let sql = format!("SELECT pg_sleep(30) AS {}", marker);
+        let options = QueryOptions {
+            timeout_secs: Some(1),
+            ..Default::default()
+        };
+        let result = execute_query(&creds.id, &sql, &options, &store, &pools).await;
+        assert!(matches!(result, Err(DatabaseError::QueryTimeout(1))));
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let pool = pools.get_or_create(&creds.id, &creds).await.unwrap();
+        let running: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM pg_stat_activity WHERE state = 'active' AND query LIKE '%{}%' \
+             AND query NOT LIKE '%pg_stat_activity%'",
+            marker
+        ))
+        .fetch_one(&*pool)
+        .await
+        .unwrap();
+
+        drop(pools);
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(running, 0);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_runs_only_the_validated_statement() {
+        let db = SqliteFixture::new();
+
+        let pool = db.pool().await;
+        sqlx::query("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+            .execute(&*pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO t (id) VALUES (1), (2)")
+            .execute(&*pool)
+            .await
+            .unwrap();
+
+        let sql = "SELECT 1 /* /* */ ; DELETE FROM t; -- */";
+        let options = QueryOptions::default();
+        assert!(matches!(
+            execute_query("sqlite-1", sql, &options, &db.store, &db.pools).await,
+            Err(DatabaseError::SQLInjection(_))
+        ));
+
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM t")
+            .fetch_one(&*pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 2);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_transaction() {
+        let db = SqliteFixture::new();
+
+        let pool = db.pool().await;
+        sqlx::query("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+            .execute(&*pool)
+            .await
+            .unwrap();
+
+        let inserts = vec!["INSERT INTO items (name) VALUES ('a')".to_string()];
+        assert!(matches!(
+            execute_transaction("sqlite-1", &inserts, false, &db.store, &db.pools).await,
+            Err(DatabaseError::DestructiveOperation(_))
+        ));
+
+        let statements = vec![
+            "INSERT INTO items (name) VALUES ('a')".to_string(),
+            "SELECT COUNT(*) AS n FROM items".to_string(),
+            "INSERT INTO items (name) VALUES (NULL)".to_string(),
+        ];
+        let result = execute_transaction("sqlite-1", &statements, true, &db.store, &db.pools)
+            .await
+            .unwrap();
+        assert!(!result.committed);
+        assert_eq!(result.results.len(), 2);
+        assert_eq!(result.results[1].rows[0]["n"], 1);
+        assert!(result.rollback_reason.unwrap().starts_with("Statement 3 failed"));
+
+        let result = execute_transaction("sqlite-1", &statements[..2], true, &db.store, &db.pools)
+            .await
+            .unwrap();
+        assert!(result.committed);
+        assert_eq!(result.results[1].rows[0]["n"], 1);
+
+        for control in ["COMMIT", "ROLLBACK", "BEGIN", "SAVEPOINT sp", "RELEASE SAVEPOINT sp"] {
+            let statements = vec![statements[0].clone(), control.to_string()];
+            assert!(matches!(
+                execute_transaction("sqlite-1", &statements, true, &db.store, &db.pools).await,
+                Err(DatabaseError::QueryError(_))
+            ));
+        }
+        let count = execute_query(
+            "sqlite-1",
+            "SELECT COUNT(*) AS n FROM items",
+            &QueryOptions::default(),
+            &db.store,
+            &db.pools,
+        )
+        .await
+        .unwrap();
+        assert_eq!(count.rows[0]["n"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_column_types() {
+        let db = SqliteFixture::new();
+
+        let pool = db.pool().await;
+        for sql in [
+            "CREATE TABLE flags (id INTEGER, price REAL, active BOOLEAN, label TEXT)",
+            "INSERT INTO flags VALUES (1, 2.0, 0, 'a')",
+        ] {
+            sqlx::query(sql).execute(&*pool).await.unwrap();
+        }
+
+        let sql = "SELECT id, price, active, label FROM flags";
+        let result = execute_query("sqlite-1", sql, &QueryOptions::default(), &db.store, &db.pools)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.column_types,
+            vec![ColumnType::Integer, ColumnType::Float, ColumnType::Boolean, ColumnType::Text]
+        );
+        let row = &result.rows[0];
+        assert_eq!(row["id"], serde_json::json!(1));
+        assert_eq!(row["price"], serde_json::json!(2.0));
+        assert_eq!(row["active"], serde_json::json!(false));
+        assert_eq!(row["label"], serde_json::json!("a"));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_row_limit() {
+        let db = SqliteFixture::new();
+
+        let pool = db.pool().await;
+        for sql in [
+            "CREATE TABLE items (id INTEGER PRIMARY KEY)",
+            "INSERT INTO items (id) VALUES (1), (2), (3)",
+        ] {
+            sqlx::query(sql).execute(&*pool).await.unwrap();
+        }
+
+        let options = QueryOptions {
+            max_rows: Some(2),
+            ..Default::default()
+        };
+        let sql = "SELECT id FROM items ORDER BY id";
+        let result = execute_query("sqlite-1", sql, &options, &db.store, &db.pools).await.unwrap();
+        assert_eq!(result.row_count, 2);
+        assert!(result.was_truncated);
+
+        // A LIMIT in the query itself takes precedence
+        let sql = "SELECT id FROM items LIMIT 3";
+        let result = execute_query("sqlite-1", sql, &options, &db.store, &db.pools).await.unwrap();
+        assert_eq!(result.row_count, 3);
+        assert!(!result.was_truncated);
+
+        let options = QueryOptions {
+            max_rows: Some(3),
+            ..Default::default()
+        };
+        let sql = "SELECT id FROM items";
+        let result = execute_query("sqlite-1", sql, &options, &db.store, &db.pools).await.unwrap();
+        assert_eq!(result.row_count, 3);
+        assert!(!result.was_truncated);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_multi_result_is_single() {
+        let db = SqliteFixture::new();
+
+        let options = QueryOptions::default();
+        let results = execute_multi_result(
+            "sqlite-1",
+            "SELECT 1 AS one",
+            &options,
+            false,
+            &db.store,
+            &db.pools,
+        )
+        .await
+        .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].rows[0]["one"], serde_json::json!(1));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_sorted_query() {
+        let db = SqliteFixture::new();
+
+        let sql = "SELECT 2 AS n UNION ALL SELECT 3 UNION ALL SELECT 1;";
+        let options = QueryOptions::default();
+        let sorted = execute_sorted_query(
+            "sqlite-1",
+            sql,
+            "n",
+            SortDirection::Desc,
+            &options,
+            &db.store,
+            &db.pools,
+        )
+        .await
+        .unwrap();
+        let values: Vec<_> = sorted.rows.iter().map(|row| row["n"].clone()).collect();
+        assert_eq!(values, vec![3, 2, 1]);
+
+        let paged = QueryOptions {
+            page_size: Some(2),
+            page: Some(1),
+            ..Default::default()
+        };
+        let second_page = execute_sorted_query(
+            "sqlite-1",
+            sql,
+            "n",
+            SortDirection::Asc,
+            &paged,
+            &db.store,
+            &db.pools,
+        )
+        .await
+        .unwrap();
+        assert_eq!(second_page.rows.len(), 1);
+        assert_eq!(second_page.rows[0]["n"], serde_json::json!(3));
+        assert_eq!(second_page.total_rows, Some(3));
+
+        let injected = execute_sorted_query(
+            "sqlite-1",
+            sql,
+            "n; DROP TABLE users",
+            SortDirection::Asc,
+            &options,
+            &db.store,
+            &db.pools,
+        )
+        .await;
+        assert!(matches!(injected, Err(DatabaseError::QueryError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_sorted_query_checks_the_column_of_an_empty_result() {
+        let db = SqliteFixture::new();
+
+        let pool = db.pool().await;
+        sqlx::query("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT)")
+            .execute(&*pool)
+            .await
+            .unwrap();
+
+        let sql = "SELECT id, name FROM items";
+        let options = QueryOptions::default();
+        let unknown = execute_sorted_query(
+            "sqlite-1",
+            sql,
+            "missing",
+            SortDirection::Asc,
+            &options,
+            &db.store,
+            &db.pools,
+        )
+        .await;
+        assert!(matches!(unknown, Err(DatabaseError::QueryError(_))));
+
+        let sorted = execute_sorted_query(
+            "sqlite-1",
+            sql,
+            "name",
+            SortDirection::Asc,
+            &options,
+            &db.store,
+            &db.pools,
+        )
+        .await
+        .unwrap();
+        assert!(sorted.rows.is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MySQL server, set INSPEKTOR_TEST_MYSQL_URL"]
+    async fn test_mysql_multi_result_call() {
+        let url = std::env::var("INSPEKTOR_TEST_MYSQL_URL").unwrap();
+        let dir = std::env::temp_dir().join(format!("inspektor-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let creds = crate::db::types::DatabaseCredentials::from_url(&url).unwrap();
+        let store = CredentialStore::new(dir.join("credentials.json"));
+        store.add(creds.clone()).unwrap();
+        let pools = PoolRegistry::new();
+
+        let procedure = format!("inspektor_multi_{}", uuid::Uuid::new_v4().simple());
+        let pool = pools.get_or_create(&creds.id, &creds).await.unwrap();
+        sqlx::raw_sql(&format!(
+            "CREATE PROCEDURE {}() BEGIN SELECT 1 AS a UNION ALL SELECT 4; SELECT 2 AS b, 3 AS c; END",
+            procedure
+        ))
+        .execute(&*pool)
+        .await
+        .unwrap();
+
+        let sql = format!("CALL {}()", procedure);
+        let options = QueryOptions {
+            max_rows: Some(1),
+            ..Default::default()
+        };
+        let rejected = execute_multi_result(&creds.id, &sql, &options, false, &store, &pools).await;
+        let results = execute_multi_result(&creds.id, &sql, &options, true, &store, &pools).await;
+
+        sqlx::raw_sql(&format!("DROP PROCEDURE {}", procedure))
+            .execute(&*pool)
+            .await
+            .unwrap();
+        pools.evict(&creds.id).await;
+        std::fs::remove_dir_all(dir).ok();
+
+        assert!(matches!(rejected, Err(DatabaseError::DestructiveOperation(_))));
+        let results = results.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].columns, vec!["a"]);
+        assert_eq!((results[0].row_count, results[0].was_truncated), (1, true));
+        assert_eq!(results[1].columns, vec!["b", "c"]);
+        assert!(!results[1].was_truncated);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_search_matches_wildcards_literally() {
+        let db = SqliteFixture::new();
+
+        let pool = db.pool().await;
+        sqlx::raw_sql(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT);
+             INSERT INTO notes (body) VALUES ('50% off'), ('500 off'), ('a_b'), ('axb'), ('wow!')",
+        )
+        .execute(&*pool)
+        .await
+        .unwrap();
+
+        for (term, expected) in [
+            ("0%", vec!["50% off"]),
+            ("a_b", vec!["a_b"]),
+            ("w!", vec!["wow!"]),
+            ("0 off", vec!["500 off"]),
+        ] {
+            let options = TableSearchOptions {
+                search_term: term.to_string(),
+                schema: None,
+                columns: None,
+                limit: 10,
+            };
+            let result = search_table("sqlite-1", "notes", &options, &db.store, &db.pools)
+                .await
+                .unwrap();
+            let bodies: Vec<&str> = result
+                .rows
+                .iter()
+                .map(|row| row["body"].as_str().unwrap())
+                .collect();
+            assert_eq!(bodies, expected, "searching for {}", term);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_keyset_pagination() {
+        let db = SqliteFixture::new();
+
+        let pool = db.pool().await;
+        for sql in [
+            "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT)",
+            "INSERT INTO items (id, name) VALUES (3, 'c'), (1, 'a'), (5, 'e'), (2, 'b'), (4, 'd')",
+            "CREATE TABLE pairs (a INTEGER, b INTEGER, PRIMARY KEY (a, b))",
+        ] {
+            sqlx::query(sql).execute(&*pool).await.unwrap();
+        }
+
+        let mut ids = Vec::new();
+        let mut after: Option<Value> = None;
+        loop {
+            let page =
+                fetch_table_page("sqlite-1", "items", None, 2, after.as_ref(), &db.store, &db.pools)
+                    .await
+                    .unwrap();
+            ids.extend(page.rows.iter().map(|row| row["id"].clone()));
+            if !page.has_more {
+                assert_eq!(page.next_cursor, None);
+                break;
+            }
+            after = page.next_cursor;
+        }
+        assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+
+        // Cursors given as strings are converted to the key type
+        let cursor = Value::from("4");
+        let page =
+            fetch_table_page("sqlite-1", "items", None, 10, Some(&cursor), &db.store, &db.pools)
+                .await
+                .unwrap();
+        assert_eq!(page.rows.len(), 1);
+        let cursor = Value::from("x");
+        assert!(
+            fetch_table_page("sqlite-1", "items", None, 2, Some(&cursor), &db.store, &db.pools)
+                .await
+                .is_err()
+        );
+        assert!(fetch_table_page("sqlite-1", "pairs", None, 2, None, &db.store, &db.pools)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_autocomplete_uses_cached_metadata() {
+        let db = SqliteFixture::new();
+
+        let pool = db.pool().await;
+        sqlx::raw_sql("CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT)")
+            .execute(&*pool)
+            .await
+            .unwrap();
+
+        let complete = |sql: &'static str| {
+            get_autocomplete_context("sqlite-1", sql, sql.len(), &db.store, &db.pools)
+        };
+        assert_eq!(complete("SELECT * FROM u").await.unwrap().table_suggestions, vec!["users"]);
+        let context = complete("SELECT users.").await.unwrap();
+        assert_eq!(context.column_suggestions.len(), 2);
+
+        // Until the pool is evicted, the cached tables and columns are used
+        sqlx::raw_sql("CREATE TABLE uploads (id INTEGER); ALTER TABLE users ADD COLUMN name TEXT")
+            .execute(&*pool)
+            .await
+            .unwrap();
+        assert_eq!(complete("SELECT * FROM u").await.unwrap().table_suggestions, vec!["users"]);
+        let context = complete("SELECT users.").await.unwrap();
+        assert_eq!(context.column_suggestions.len(), 2);
+
+        db.pools.evict("sqlite-1").await;
+        let mut suggestions = complete("SELECT * FROM u").await.unwrap().table_suggestions;
+        suggestions.sort();
+        assert_eq!(suggestions, vec!["uploads", "users"]);
+        let context = complete("SELECT users.").await.unwrap();
+        assert_eq!(context.column_suggestions.len(), 3);
+    }
+
+    #[test]
+    fn test_completion_position() {
+        let sql = "SELECT * FROM us";
+        assert_eq!(
+            completion_position(sql, sql.len()),
+            CompletionPosition::Table {
+                prefix: "us".to_string()
+            }
+        );
+
+        let sql = "SELECT u.em FROM users AS u JOIN orders o ON o.user_id = u.id";
+        assert_eq!(
+            completion_position(sql, "SELECT u.em".len()),
+            CompletionPosition::Column {
+                table: "users".to_string(),
+                prefix: "em".to_string()
+            }
+        );
+        assert_eq!(
+            completion_position(sql, "SELECT u.em FROM users AS u JOIN orders o ON o.".len()),
+            CompletionPosition::Column {
+                table: "orders".to_string(),
+                prefix: String::new()
+            }
+        );
+
+        let sql = "SELECT id FROM \"public\".\"users\" WH";
+        assert_eq!(
+            completion_position(sql, sql.len()),
+            CompletionPosition::Other {
+                prefix: "WH".to_string(),
+                tables: vec!["users".to_string()]
+            }
+        );
+    }
+
+    #[test]
+    fn test_utf16_to_byte_offset() {
+        // 'é' is one UTF-16 unit and two bytes, '😀' two units and four bytes
+        let sql = "SELECT 'é😀' FROM us";
+        assert_eq!(utf16_to_byte_offset(sql, 0), 0);
+        assert_eq!(utf16_to_byte_offset(sql, 9), "SELECT 'é".len());
+        assert_eq!(utf16_to_byte_offset(sql, 11), "SELECT 'é😀".len());
+        // Inside the surrogate pair
+        assert_eq!(utf16_to_byte_offset(sql, 10), "SELECT 'é".len());
+        assert_eq!(utf16_to_byte_offset(sql, 100), sql.len());
+
+        let end = sql.encode_utf16().count();
+        assert_eq!(
+            completion_position(sql, utf16_to_byte_offset(sql, end)),
+            CompletionPosition::Table {
+                prefix: "us".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_format_query() {
+        let sql = "select u.id, u.email, count(o.id) as orders from users u left join orders o on o.user_id = u.id and o.status = 'paid' inner join accounts a on a.id = u.account_id where u.id in (select user_id from admins) group by u.id, u.email order by orders desc";
+
+        let formatted = format_query(sql, Some("postgres")).unwrap();
+        let lines: Vec<&str> = formatted.lines().collect();
+
+        assert_eq!(lines[0], "SELECT");
+        assert!(lines.contains(&"FROM"));
+        assert!(lines.contains(&"WHERE"));
+        assert!(formatted.contains("LEFT JOIN orders o ON o.user_id = u.id"));
+        assert!(formatted.contains("INNER JOIN accounts a ON a.id = u.account_id"));
+        // The subquery is on its own, indented lines
+        assert!(lines.iter().any(|line| line.starts_with("    SELECT")));
+        assert!(format_query(sql, Some("oracle")).is_err());
+    }
 }
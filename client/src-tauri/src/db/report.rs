@@ -0,0 +1,270 @@
+use super::connection::PoolRegistry;
+use super::credentials::CredentialStore;
+use super::metadata::diff_schemas;
+use super::types::{
+    with_context, ColumnInfo, DatabaseError, DatabaseErrorContext, IndexInfo, SchemaDiff, TableDiff,
+};
+use handlebars::Handlebars;
+use serde_json::{json, Value};
+use tauri::State;
+
+const SCHEMA_DIFF_TEMPLATE: &str = include_str!("templates/schema_diff.hbs");
+
+/// Diff two database schemas and render the result as a self-contained HTML page
+pub async fn generate_schema_diff_report(
+    source_id: &str,
+    target_id: &str,
+    store: &CredentialStore,
+    pools: &PoolRegistry,
+) -> Result<String, DatabaseError> {
+    let source_name = store.get(source_id)?.name;
+    let target_name = store.get(target_id)?.name;
+    let diff = diff_schemas(source_id, target_id, store, pools).await?;
+
+    render_schema_diff(&source_name, &target_name, &diff)
+}
+
+/// Render a schema diff with the embedded template
+///
+/// Added rows are green, removed rows red and changed rows yellow. Values are
+/// HTML-escaped by the template engine.
+pub fn render_schema_diff(
+    source_name: &str,
+    target_name: &str,
+    diff: &SchemaDiff,
+) -> Result<String, DatabaseError> {
+    let mut rows = Vec::new();
+
+    for table in &diff.added_tables {
+        rows.push(diff_row(
+            "added",
+            &table.table_name,
+            "",
+            "",
+            &table_summary(table.columns.len()),
+        ));
+    }
+    for table in &diff.removed_tables {
+        rows.push(diff_row(
+            "removed",
+            &table.table_name,
+            "",
+            &table_summary(table.columns.len()),
+            "",
+        ));
+    }
+    for table in &diff.modified_tables {
+        for column in &table.added_columns {
+            rows.push(diff_row(
+                "added",
+                &table.table_name,
+                &column.name,
+                "",
+                &describe_column(column),
+            ));
+        }
+        for column in &table.removed_columns {
+            rows.push(diff_row(
+                "removed",
+                &table.table_name,
+                &column.name,
+                &describe_column(column),
+                "",
+            ));
+        }
+        for change in &table.changed_columns {
+            rows.push(diff_row(
+                "changed",
+                &table.table_name,
+                &change.column_name,
+                &describe_column(&change.source),
+                &describe_column(&change.target),
+            ));
+        }
+    }
+    for index in &diff.added_indexes {
+        rows.push(diff_row(
+            "added",
+            &index.table_name,
+            &index.name,
+            "",
+            &describe_index(index),
+        ));
+    }
+    for index in &diff.removed_indexes {
+        rows.push(diff_row(
+            "removed",
+            &index.table_name,
+            &index.name,
+            &describe_index(index),
+            "",
+        ));
+    }
+
+    let count_columns =
+        |f: fn(&TableDiff) -> usize| -> usize { diff.modified_tables.iter().map(f).sum() };
+    let summary = vec![
+        summary_row("added", "Added tables", diff.added_tables.len()),
+        summary_row("removed", "Removed tables", diff.removed_tables.len()),
+        summary_row("changed", "Modified tables", diff.modified_tables.len()),
+        summary_row(
+            "added",
+            "Added columns",
+            count_columns(|t| t.added_columns.len()),
+        ),
+        summary_row(
+            "removed",
+            "Removed columns",
+            count_columns(|t| t.removed_columns.len()),
+        ),
+        summary_row(
+            "changed",
+            "Changed columns",
+            count_columns(|t| t.changed_columns.len()),
+        ),
+        summary_row("added", "Added indexes", diff.added_indexes.len()),
+        summary_row("removed", "Removed indexes", diff.removed_indexes.len()),
+    ];
+
+    let data = json!({
+        "source": source_name,
+        "target": target_name,
+        "generated_at": chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        "summary": summary,
+        "rows": rows,
+    });
+
+    Handlebars::new()
+        .render_template(SCHEMA_DIFF_TEMPLATE, &data)
+        .map_err(|e| DatabaseError::Other(format!("Failed to render diff report: {}", e)))
+}
+
+fn diff_row(kind: &str, table: &str, column: &str, before: &str, after: &str) -> Value {
+    json!({
+        "kind": kind,
+        "table": table,
+        "column": column,
+        "before": before,
+        "after": after,
+    })
+}
+
+fn summary_row(kind: &str, label: &str, count: usize) -> Value {
+    json!({ "kind": kind, "label": label, "count": count })
+}
+
+fn table_summary(column_count: usize) -> String {
+    format!(
+        "table with {} column{}",
+        column_count,
+        if column_count == 1 { "" } else { "s" }
+    )
+}
+
+/// One-line column definition, e.g. `varchar(255) NOT NULL DEFAULT ''`
+fn describe_column(column: &ColumnInfo) -> String {
+    let mut description = match column.character_max_length {
+        Some(length) if !column.data_type.contains('(') => {
+            format!("{}({})", column.data_type, length)
+        }
+        _ => column.data_type.clone(),
+    };
+
+    if !column.is_nullable {
+        description.push_str(" NOT NULL");
+    }
+    if let Some(default) = &column.default_value {
+        description.push_str(&format!(" DEFAULT {}", default));
+    }
+    if column.is_primary_key {
+        description.push_str(" PRIMARY KEY");
+    }
+    description
+}
+
+/// One-line index definition, e.g. `UNIQUE btree (email)`
+fn describe_index(index: &IndexInfo) -> String {
+    let kind = if index.is_primary {
+        "PRIMARY KEY "
+    } else if index.is_unique {
+        "UNIQUE "
+    } else {
+        ""
+    };
+    format!(
+        "{}{} ({})",
+        kind,
+        index.index_type,
+        index.columns.join(", ")
+    )
+}
+
+// Tauri command for schema diff reports
+#[tauri::command]
+pub async fn generate_diff_html_report(
+    source_id: String,
+    target_id: String,
+    store: State<'_, CredentialStore>,
+    pools: State<'_, PoolRegistry>,
+) -> Result<String, DatabaseErrorContext> {
+    generate_schema_diff_report(&source_id, &target_id, &store, &pools)
+        .await
+        .map_err(|e| with_context(e, Some(source_id.as_str()), "generate_schema_diff_report"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::metadata::compute_schema_diff;
+    use crate::db::types::TableSchema;
+
+    fn column(name: &str, data_type: &str) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            is_nullable: true,
+            is_primary_key: false,
+            default_value: None,
+            character_max_length: None,
+            is_unique: false,
+            unique_constraint_name: None,
+            is_inherited: false,
+        }
+    }
+
+    fn table(name: &str, columns: Vec<ColumnInfo>) -> TableSchema {
+        TableSchema {
+            table_name: name.to_string(),
+            schema: None,
+            columns,
+        }
+    }
+
+    #[test]
+    fn test_render_schema_diff() {
+        let source = vec![
+            table(
+                "users",
+                vec![column("id", "integer"), column("bio", "text")],
+            ),
+            table("legacy", vec![column("id", "integer")]),
+        ];
+        let target = vec![
+            table(
+                "users",
+                vec![column("id", "bigint"), column("<email>", "text")],
+            ),
+            table("orders", vec![column("id", "integer")]),
+        ];
+
+        let diff = compute_schema_diff(&source, &target);
+        assert_eq!(diff.added_tables.len(), 1);
+        assert_eq!(diff.removed_tables.len(), 1);
+        assert_eq!(diff.modified_tables[0].changed_columns[0].column_name, "id");
+
+        let html = render_schema_diff("prod", "staging", &diff).unwrap();
+        assert!(html.contains("<tr class=\"changed\">"));
+        assert!(html.contains("&lt;email&gt;"));
+        assert!(!html.contains("identical"));
+    }
+}
@@ -0,0 +1,298 @@
+use super::credentials::write_atomically;
+use super::types::{with_context, DatabaseError, DatabaseErrorContext, SavedQuery};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::State;
+
+/// Version of the on-disk saved queries file format
+const SAVED_QUERIES_FILE_VERSION: u8 = 1;
+
+/// On-disk representation of the saved queries
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedQueriesFile {
+    version: u8,
+    queries: Vec<SavedQuery>,
+}
+
+/// Saved queries persisted as a JSON file in the app data directory
+///
+/// Like `CredentialStore`, every change is written to disk before it becomes
+/// visible in memory.
+pub struct SavedQueryStore {
+    queries: Mutex<Vec<SavedQuery>>,
+    path: PathBuf,
+}
+
+impl SavedQueryStore {
+    /// Load the saved queries from `path`; a missing file yields an empty store
+    pub fn load(path: PathBuf) -> Result<Self, DatabaseError> {
+        if !path.exists() {
+            return Ok(Self::empty(path));
+        }
+
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            DatabaseError::StorageError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        let file: SavedQueriesFile = serde_json::from_str(&contents).map_err(|e| {
+            DatabaseError::StorageError(format!("Invalid saved queries file: {}", e))
+        })?;
+
+        Ok(Self {
+            queries: Mutex::new(file.queries),
+            path,
+        })
+    }
+
+    /// Create a store backed by `path`, loading existing queries when the file is
+    /// readable and starting empty otherwise
+    pub fn new(path: PathBuf) -> Self {
+        Self::load(path.clone()).unwrap_or_else(|_| Self::empty(path))
+    }
+
+    fn empty(path: PathBuf) -> Self {
+        Self {
+            queries: Mutex::new(Vec::new()),
+            path,
+        }
+    }
+
+    /// Save a new query and return it with its generated id
+    pub fn add(
+        &self,
+        name: &str,
+        sql: &str,
+        database_id: Option<&str>,
+        tags: Vec<String>,
+    ) -> Result<SavedQuery, DatabaseError> {
+        validate_name(name)?;
+        let query = SavedQuery {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.trim().to_string(),
+            database_id: database_id.map(str::to_string),
+            sql: sql.to_string(),
+            created_at: Utc::now(),
+            tags: normalize_tags(tags),
+        };
+
+        let mut queries = self.queries.lock().unwrap();
+        let mut updated = queries.clone();
+        updated.push(query.clone());
+        self.persist(&updated)?;
+        *queries = updated;
+        Ok(query)
+    }
+
+    pub fn get(&self, id: &str) -> Result<SavedQuery, DatabaseError> {
+        let queries = self.queries.lock().unwrap();
+        queries
+            .iter()
+            .find(|query| query.id == id)
+            .cloned()
+            .ok_or_else(|| not_found(id))
+    }
+
+    /// Queries sorted by name, optionally restricted to one database and one tag
+    ///
+    /// Queries without a database apply to every database and are always included.
+    pub fn list(&self, database_id: Option<&str>, tag: Option<&str>) -> Vec<SavedQuery> {
+        let queries = self.queries.lock().unwrap();
+        let mut matching: Vec<SavedQuery> = queries
+            .iter()
+            .filter(|query| match (database_id, query.database_id.as_deref()) {
+                (Some(id), Some(query_db)) => id == query_db,
+                _ => true,
+            })
+            .filter(|query| tag.is_none_or(|tag| query.tags.iter().any(|t| t == tag)))
+            .cloned()
+            .collect();
+        matching.sort_by_key(|query| query.name.to_lowercase());
+        matching
+    }
+
+    /// Replace the name, SQL and tags of a saved query
+    pub fn update(
+        &self,
+        id: &str,
+        name: &str,
+        sql: &str,
+        tags: Vec<String>,
+    ) -> Result<SavedQuery, DatabaseError> {
+        validate_name(name)?;
+        let mut queries = self.queries.lock().unwrap();
+        let mut updated = queries.clone();
+        let query = updated
+            .iter_mut()
+            .find(|query| query.id == id)
+            .ok_or_else(|| not_found(id))?;
+        query.name = name.trim().to_string();
+        query.sql = sql.to_string();
+        query.tags = normalize_tags(tags);
+        let query = query.clone();
+
+        self.persist(&updated)?;
+        *queries = updated;
+        Ok(query)
+    }
+
+    pub fn remove(&self, id: &str) -> Result<(), DatabaseError> {
+        let mut queries = self.queries.lock().unwrap();
+        let mut updated = queries.clone();
+        let before = updated.len();
+        updated.retain(|query| query.id != id);
+        if updated.len() == before {
+            return Err(not_found(id));
+        }
+
+        self.persist(&updated)?;
+        *queries = updated;
+        Ok(())
+    }
+
+    fn persist(&self, queries: &[SavedQuery]) -> Result<(), DatabaseError> {
+        let file = SavedQueriesFile {
+            version: SAVED_QUERIES_FILE_VERSION,
+            queries: queries.to_vec(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| DatabaseError::StorageError(e.to_string()))?;
+        write_atomically(&self.path, json.as_bytes())
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), DatabaseError> {
+    if name.trim().is_empty() {
+        return Err(DatabaseError::StorageError(
+            "Saved query name cannot be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Trim tags and drop empty and duplicate ones, keeping their order
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|t| t == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
+fn not_found(id: &str) -> DatabaseError {
+    DatabaseError::StorageError(format!("Saved query not found: {}", id))
+}
+
+// Tauri commands for saved queries
+#[tauri::command]
+pub async fn save_query(
+    name: String,
+    sql: String,
+    database_id: Option<String>,
+    tags: Vec<String>,
+    saved: State<'_, SavedQueryStore>,
+) -> Result<SavedQuery, DatabaseErrorContext> {
+    saved
+        .add(&name, &sql, database_id.as_deref(), tags)
+        .map_err(|e| with_context(e, database_id.as_deref(), "save_query"))
+}
+
+#[tauri::command]
+pub async fn get_saved_query(
+    id: String,
+    saved: State<'_, SavedQueryStore>,
+) -> Result<SavedQuery, DatabaseErrorContext> {
+    saved
+        .get(&id)
+        .map_err(|e| with_context(e, None, "get_saved_query"))
+}
+
+#[tauri::command]
+pub async fn list_saved_queries(
+    database_id: Option<String>,
+    tag: Option<String>,
+    saved: State<'_, SavedQueryStore>,
+) -> Result<Vec<SavedQuery>, DatabaseErrorContext> {
+    Ok(saved.list(database_id.as_deref(), tag.as_deref()))
+}
+
+#[tauri::command]
+pub async fn delete_saved_query(
+    id: String,
+    saved: State<'_, SavedQueryStore>,
+) -> Result<(), DatabaseErrorContext> {
+    saved
+        .remove(&id)
+        .map_err(|e| with_context(e, None, "delete_saved_query"))
+}
+
+#[tauri::command]
+pub async fn update_saved_query(
+    id: String,
+    name: String,
+    sql: String,
+    tags: Vec<String>,
+    saved: State<'_, SavedQueryStore>,
+) -> Result<SavedQuery, DatabaseErrorContext> {
+    saved
+        .update(&id, &name, &sql, tags)
+        .map_err(|e| with_context(e, None, "update_saved_query"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_queries_are_filtered_and_persisted() {
+        let path = std::env::temp_dir().join(format!(
+            "inspektor-saved-queries-{}.json",
+            uuid::Uuid::new_v4()
+        ));
+        let store = SavedQueryStore::new(path.clone());
+
+        let active = store
+            .add(
+                "Active users",
+                "SELECT * FROM users WHERE active",
+                Some("db-a"),
+                vec!["users".to_string(), " users ".to_string(), "".to_string()],
+            )
+            .unwrap();
+        assert_eq!(active.tags, vec!["users"]);
+        store
+            .add("Table sizes", "SELECT 1", None, vec!["admin".to_string()])
+            .unwrap();
+        store
+            .add("Orders", "SELECT * FROM orders", Some("db-b"), Vec::new())
+            .unwrap();
+        assert!(store.add("  ", "SELECT 1", None, Vec::new()).is_err());
+
+        let reloaded = SavedQueryStore::load(path.clone()).unwrap();
+        let names = |queries: Vec<SavedQuery>| -> Vec<String> {
+            queries.into_iter().map(|query| query.name).collect()
+        };
+        assert_eq!(
+            names(reloaded.list(Some("db-a"), None)),
+            vec!["Active users", "Table sizes"]
+        );
+        assert_eq!(
+            names(reloaded.list(None, Some("admin"))),
+            vec!["Table sizes"]
+        );
+
+        let renamed = reloaded
+            .update(&active.id, "All users", "SELECT * FROM users", Vec::new())
+            .unwrap();
+        assert_eq!(renamed.created_at, active.created_at);
+        reloaded.remove(&active.id).unwrap();
+        assert!(reloaded.get(&active.id).is_err());
+        assert!(reloaded.remove(&active.id).is_err());
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,58 @@
+use super::connection::PoolRegistry;
+use super::credentials::CredentialStore;
+use super::types::DatabaseCredentials;
+use sqlx::AnyPool;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Id of the database registered by `SqliteFixture`
+pub(crate) const SQLITE_ID: &str = "sqlite-1";
+
+/// A SQLite database in its own temporary directory, registered in a credential
+/// store, with an empty pool registry
+///
+/// Dropping the fixture closes its pools and removes the directory, so tests
+/// clean up after themselves even when an assertion fails.
+pub(crate) struct SqliteFixture {
+    pub dir: PathBuf,
+    pub creds: DatabaseCredentials,
+    pub store: CredentialStore,
+    pub pools: PoolRegistry,
+}
+
+impl SqliteFixture {
+    pub fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!("inspektor-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let creds: DatabaseCredentials = serde_json::from_value(serde_json::json!({
+            "id": SQLITE_ID,
+            "name": "Local SQLite",
+            "db_type": "sqlite",
+            "database": "main",
+            "file_path": dir.join("app.db").to_string_lossy(),
+            "extra_options": { "mode": "rwc" },
+        }))
+        .unwrap();
+        let store = CredentialStore::new(dir.join("credentials.json"));
+        store.add(creds.clone()).unwrap();
+        Self {
+            dir,
+            creds,
+            store,
+            pools: PoolRegistry::new(),
+        }
+    }
+
+    /// Pool for the fixture database, connecting on first use
+    pub async fn pool(&self) -> Arc<AnyPool> {
+        self.pools.get_or_create(SQLITE_ID, &self.creds).await.unwrap()
+    }
+}
+
+impl Drop for SqliteFixture {
+    fn drop(&mut self) {
+        // Drop the pools first so no connection holds the database file open
+        drop(std::mem::take(&mut self.pools));
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
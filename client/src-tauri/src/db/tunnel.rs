@@ -0,0 +1,237 @@
+use super::types::{DatabaseCredentials, DatabaseError, DatabaseType};
+use russh::client;
+use russh_keys::key;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio::task::{JoinHandle, JoinSet};
+
+/// SSH client callbacks for tunnel sessions
+struct TunnelHandler {
+    host: String,
+    port: u16,
+    pinned_fingerprint: Option<String>,
+    rejection: Arc<Mutex<Option<String>>>, // Why the server key was rejected
+}
+
+#[async_trait::async_trait]
+impl client::Handler for TunnelHandler {
+    type Error = russh::Error;
+
+    async fn check_server_key(
+        &mut self,
+        server_public_key: &key::PublicKey,
+    ) -> Result<bool, Self::Error> {
+        match check_host_key(
+            &self.host,
+            self.port,
+            self.pinned_fingerprint.as_deref(),
+            server_public_key,
+        ) {
+            Ok(()) => Ok(true),
+            Err(reason) => {
+                *self.rejection.lock().unwrap() = Some(reason);
+                Ok(false)
+            }
+        }
+    }
+}
+
+/// Accept the server key only if it matches the fingerprint pinned in the credentials
+/// or, without a pinned fingerprint, an entry in `~/.ssh/known_hosts`
+///
+/// Unknown hosts are rejected rather than trusted on first use, the error names the
+/// fingerprint so it can be checked and pinned.
+fn check_host_key(
+    host: &str,
+    port: u16,
+    pinned_fingerprint: Option<&str>,
+    server_public_key: &key::PublicKey,
+) -> Result<(), String> {
+    let fingerprint = server_public_key.fingerprint();
+    if let Some(pinned) = pinned_fingerprint {
+        let pinned = pinned.trim();
+        let pinned = pinned.strip_prefix("SHA256:").unwrap_or(pinned);
+        return if pinned.trim_end_matches('=') == fingerprint {
+            Ok(())
+        } else {
+            Err(format!(
+                "Host key SHA256:{} of {} does not match the pinned fingerprint",
+                fingerprint, host
+            ))
+        };
+    }
+
+    match russh_keys::check_known_hosts(host, port, server_public_key) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!(
+            "Host {} is not in known_hosts; verify and pin its fingerprint SHA256:{}",
+            host, fingerprint
+        )),
+        Err(russh_keys::Error::KeyChanged { line }) => Err(format!(
+            "Host key SHA256:{} of {} does not match known_hosts line {}",
+            fingerprint, host, line
+        )),
+        Err(e) => Err(format!("Failed to read known_hosts: {}", e)),
+    }
+}
+
+/// Local port forward to a database through an SSH jump host
+///
+/// Connections to `127.0.0.1:<local_port>` are forwarded over the SSH session to the
+/// database host. Dropping the tunnel stops forwarding and closes the session.
+pub struct SshTunnel {
+    local_port: u16,
+    forwarder: JoinHandle<()>,
+}
+
+impl SshTunnel {
+    /// Open an SSH session and start forwarding a random local port to
+    /// `target_host:target_port`
+    pub async fn open(
+        creds: &DatabaseCredentials,
+        target_host: &str,
+        target_port: u16,
+    ) -> Result<Self, DatabaseError> {
+        let ssh_host = creds
+            .ssh_host
+            .as_deref()
+            .ok_or_else(|| DatabaseError::SSHError("SSH host is required".to_string()))?;
+        let ssh_port = creds.ssh_port.unwrap_or(22);
+        let ssh_user = creds
+            .ssh_user
+            .as_deref()
+            .ok_or_else(|| DatabaseError::SSHError("SSH user is required".to_string()))?;
+        let key_path = creds.ssh_private_key_path.as_deref().ok_or_else(|| {
+            DatabaseError::SSHError("SSH private key path is required".to_string())
+        })?;
+
+        let key_pair =
+            russh_keys::load_secret_key(key_path, creds.ssh_private_key_passphrase.as_deref())
+                .map_err(|e| {
+                    DatabaseError::SSHError(format!(
+                        "Failed to load private key {}: {}",
+                        key_path, e
+                    ))
+                })?;
+
+        let config = Arc::new(client::Config::default());
+        let rejection = Arc::new(Mutex::new(None));
+        let handler = TunnelHandler {
+            host: ssh_host.to_string(),
+            port: ssh_port,
+            pinned_fingerprint: creds.ssh_host_key_fingerprint.clone(),
+            rejection: rejection.clone(),
+        };
+        let mut session = client::connect(config, (ssh_host, ssh_port), handler)
+            .await
+            .map_err(|e| {
+                let reason = rejection.lock().unwrap().take();
+                DatabaseError::SSHError(reason.unwrap_or_else(|| {
+                    format!("Failed to connect to {}:{}: {}", ssh_host, ssh_port, e)
+                }))
+            })?;
+
+        let authenticated = session
+            .authenticate_publickey(ssh_user, Arc::new(key_pair))
+            .await
+            .map_err(|e| DatabaseError::SSHError(format!("Authentication failed: {}", e)))?;
+        if !authenticated {
+            return Err(DatabaseError::SSHError(format!(
+                "Authentication failed for {}@{}",
+                ssh_user, ssh_host
+            )));
+        }
+
+        let listener = TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .map_err(|e| DatabaseError::SSHError(format!("Failed to bind local port: {}", e)))?;
+        let local_port = listener
+            .local_addr()
+            .map_err(|e| DatabaseError::SSHError(e.to_string()))?
+            .port();
+
+        let target_host = target_host.to_string();
+        let forwarder = tokio::spawn(async move {
+            // Forwarded connections live in the set so aborting the forwarder ends them too
+            let mut connections = JoinSet::new();
+
+            while let Ok((mut socket, peer)) = listener.accept().await {
+                // Reap connections that already ended so the set does not grow forever
+                while connections.try_join_next().is_some() {}
+
+                let channel = match session
+                    .channel_open_direct_tcpip(
+                        target_host.clone(),
+                        target_port as u32,
+                        peer.ip().to_string(),
+                        peer.port() as u32,
+                    )
+                    .await
+                {
+                    Ok(channel) => channel,
+                    // Dropping the socket fails the database connection, which reports
+                    // the error. A closed session cannot forward again, so stop listening
+                    // and let new connections be refused.
+                    Err(_) if session.is_closed() => break,
+                    Err(_) => continue,
+                };
+
+                connections.spawn(async move {
+                    let mut stream = channel.into_stream();
+                    let _ = tokio::io::copy_bidirectional(&mut socket, &mut stream).await;
+                });
+            }
+        });
+
+        Ok(Self {
+            local_port,
+            forwarder,
+        })
+    }
+
+    pub fn local_port(&self) -> u16 {
+        self.local_port
+    }
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        self.forwarder.abort();
+    }
+}
+
+/// Whether connections for these credentials go through an SSH tunnel
+pub fn uses_ssh_tunnel(creds: &DatabaseCredentials) -> bool {
+    let has_ssh_config = creds.ssh_host.is_some()
+        || creds.ssh_port.is_some()
+        || creds.ssh_user.is_some()
+        || creds.ssh_private_key_path.is_some()
+        || creds.ssh_private_key_passphrase.is_some();
+
+    has_ssh_config && !matches!(creds.db_type, DatabaseType::SQLite)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinned_host_key_fingerprint() {
+        let key = key::KeyPair::generate_ed25519()
+            .unwrap()
+            .clone_public_key()
+            .unwrap();
+        let fingerprint = key.fingerprint();
+
+        assert!(check_host_key("jump", 22, Some(&fingerprint), &key).is_ok());
+        let prefixed = format!("SHA256:{}", fingerprint);
+        assert!(check_host_key("jump", 22, Some(&prefixed), &key).is_ok());
+
+        let other = key::KeyPair::generate_ed25519()
+            .unwrap()
+            .clone_public_key()
+            .unwrap();
+        let err = check_host_key("jump", 22, Some(&fingerprint), &other).unwrap_err();
+        assert!(err.contains("does not match the pinned fingerprint"), "{}", err);
+    }
+}
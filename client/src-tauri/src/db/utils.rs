@@ -0,0 +1,33 @@
+use super::types::DatabaseType;
+
+/// Quote an identifier for the given database, doubling embedded delimiters
+///
+/// Quoted names keep their case, so reserved words such as `order` and names with
+/// spaces or hyphens can be used anywhere SQL expects an identifier, including
+/// SQLite PRAGMA arguments.
+pub(crate) fn quote_identifier(name: &str, db_type: &DatabaseType) -> String {
+    match db_type {
+        DatabaseType::MySQL => format!("`{}`", name.replace('`', "``")),
+        DatabaseType::Postgres | DatabaseType::SQLite | DatabaseType::ClickHouse => {
+            format!("\"{}\"", name.replace('"', "\"\""))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(
+            quote_identifier("order", &DatabaseType::Postgres),
+            "\"order\""
+        );
+        assert_eq!(
+            quote_identifier("my-\"table\"", &DatabaseType::SQLite),
+            "\"my-\"\"table\"\"\""
+        );
+        assert_eq!(quote_identifier("a`b", &DatabaseType::MySQL), "`a``b`");
+    }
+}
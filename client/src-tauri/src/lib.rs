@@ -1,6 +1,13 @@
 mod db;
 
+use db::connection::{
+    ConnectionHealthCache, KeepaliveTask, PoolRegistry, DEFAULT_KEEPALIVE_INTERVAL_SECS,
+};
 use db::credentials::CredentialStore;
+use db::history::QueryHistory;
+use db::notify::NotificationListeners;
+use db::saved_queries::SavedQueryStore;
+use tauri::Manager;
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
@@ -12,26 +19,158 @@ fn greet(name: &str) -> String {
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .manage(CredentialStore::new())
+        .setup(|app| {
+            let data_dir = app.path().app_data_dir()?;
+            let path = data_dir.join("credentials.json");
+            let store = CredentialStore::load(path.clone()).unwrap_or_else(|e| {
+                // Keep the unreadable file around instead of overwriting it on the next save
+                eprintln!("Failed to load saved credentials: {}", e);
+                std::fs::rename(&path, path.with_extension("json.corrupt")).ok();
+                CredentialStore::new(path)
+            });
+            app.manage(store);
+            app.manage(PoolRegistry::new());
+            app.manage(ConnectionHealthCache::new());
+            let keepalive = KeepaliveTask::new();
+            keepalive.restart(app.handle(), DEFAULT_KEEPALIVE_INTERVAL_SECS);
+            app.manage(keepalive);
+            app.manage(NotificationListeners::new());
+            app.manage(QueryHistory::load(data_dir.join("history.json")));
+            let saved_path = data_dir.join("saved_queries.json");
+            let saved_queries = SavedQueryStore::load(saved_path.clone()).unwrap_or_else(|e| {
+                eprintln!("Failed to load saved queries: {}", e);
+                std::fs::rename(&saved_path, saved_path.with_extension("json.corrupt")).ok();
+                SavedQueryStore::new(saved_path)
+            });
+            app.manage(saved_queries);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             // Credential management
             db::credentials::save_credentials,
             db::credentials::get_credentials,
+            db::credentials::duplicate_credential,
             db::credentials::list_credentials,
+            db::credentials::search_credentials,
+            db::credentials::parse_connection_url,
+            db::credentials::add_tag_to_credential,
+            db::credentials::remove_tag_from_credential,
             db::credentials::delete_credentials,
             db::credentials::update_credentials,
+            db::credentials::update_credential_password,
+            db::credentials::export_credentials,
+            db::credentials::import_credentials,
+            db::credentials::validate_connection_name,
+            db::credentials::set_connection_name_prefixes,
             // Connection testing
             db::connection::test_database_connection,
+            db::connection::test_database_connection_extended,
+            db::connection::resolve_credential,
+            db::connection::get_connection_health,
+            db::connection::get_pool_status,
+            db::connection::ping_database_connection,
+            db::connection::set_keepalive_interval_secs,
             // Query execution
             db::query::execute_sql_query,
+            db::query::execute_sorted_sql_query,
+            db::query::execute_multi_result_query,
+            db::query::execute_parameterized_query,
+            db::query::execute_sql_transaction,
+            db::query::search_table_data,
+            db::query::sample_table_rows,
+            db::query::get_table_page,
+            db::query::explain_sql_query,
+            db::query::get_sql_autocomplete,
+            db::query::format_sql,
+            db::diff::diff_query_results,
+            db::query::clear_query_cache,
+            db::query::query_cache_stats,
+            db::query::set_default_row_limit,
+            db::query::get_default_row_limit,
+            db::query::set_query_max_page_size,
+            db::query::get_query_max_page_size,
+            db::export::export_query_result_csv,
+            db::export::export_query_result_json,
+            db::export::export_table,
+            db::diagnostics::export_diagnostic_report,
+            // Query history
+            db::history::get_query_history,
+            db::history::clear_query_history,
+            // Saved queries
+            db::saved_queries::save_query,
+            db::saved_queries::get_saved_query,
+            db::saved_queries::list_saved_queries,
+            db::saved_queries::delete_saved_query,
+            db::saved_queries::update_saved_query,
+            db::notify::get_postgres_listening_channels,
+            db::notify::get_postgres_active_notifications,
+            db::notify::notify_channel,
+            db::notify::listen_postgres_channel,
+            db::notify::unlisten_postgres_channel,
+            db::report::generate_diff_html_report,
             // Metadata extraction
             db::metadata::get_database_tables,
             db::metadata::get_database_table_schema,
+            db::metadata::get_database_indexes,
+            db::metadata::get_table_row_count,
+            db::metadata::get_column_statistics,
+            db::metadata::get_all_table_row_counts,
+            db::metadata::get_table_ddl,
+            db::metadata::get_database_routines,
+            db::metadata::get_database_enum_types,
+            db::metadata::get_database_sequences,
+            db::metadata::get_database_schemas,
+            db::metadata::get_database_triggers,
+            db::metadata::get_database_check_constraints,
+            db::metadata::get_dependent_tables,
+            db::metadata::find_table_usages,
+            db::metadata::find_column_across_tables,
+            db::metadata::search_database_schema,
+            db::metadata::diff_database_schemas,
+            db::metadata::fetch_schema_streaming,
             db::metadata::get_database_relationships,
+            db::metadata::get_all_databases_relationships,
+            db::metadata::get_database_vacuum_progress,
+            db::metadata::get_database_analyze_progress,
+            db::metadata::get_table_dead_tuples,
+            db::metadata::get_table_statistics,
+            db::metadata::get_database_size,
+            db::metadata::get_blocked_queries,
+            db::metadata::get_lock_wait_summary,
+            db::metadata::get_database_mysql_replicas,
+            db::metadata::analyze_sqlite_pragmas,
+            db::metadata::get_database_sqlite_pragmas,
+            db::metadata::set_sqlite_journal_mode,
+            // Server administration
+            db::admin::get_active_sessions,
+            db::admin::terminate_session,
+            db::admin::reset_table_sequence,
+            db::admin::refresh_database_materialized_view,
+            db::admin::vacuum_database_table,
+            db::admin::check_database_integrity,
+            db::admin::get_database_locks,
+            db::admin::get_database_replication_status,
+            db::admin::get_database_users,
+            db::admin::get_table_privileges,
+            // Schema export
+            db::json_schema::export_json_schema,
+            // DML generation
+            db::dml::generate_insert_statement,
+            db::dml::check_unique_constraints,
+            // DDL
+            db::ddl::create_table_partition,
+            db::ddl::detach_partition,
+            db::erd::save_erd_layout,
+            db::erd::load_erd_layout,
+            db::erd::reset_erd_layout,
             // Encryption
             db::encryption::encrypt_connection,
             db::encryption::decrypt_connection,
+            db::encryption::rotate_connection_key,
+            db::encryption::save_encrypted_connection,
+            db::encryption::load_encrypted_connection,
+            db::encryption::rotate_credential_password,
             db::encryption::verify_connection_password,
         ])
         .run(tauri::generate_context!())
@@ -4,6 +4,7 @@ import type { DatabaseCredentials, DatabaseType } from '../types/database';
 import {
   saveCredentials,
   listCredentials,
+  getCredentials,
   deleteCredentials,
   testDatabaseConnection,
 } from '../services/tauri';
@@ -86,7 +87,8 @@ export function ConnectionManager({ onSelectConnection }: ConnectionManagerProps
   const handleTest = async (cred: DatabaseCredentials) => {
     setTestingConnection(cred.id);
     try {
-      const result = await testDatabaseConnection(cred);
+      // Listed credentials leave out the password, so load the saved ones
+      const result = await testDatabaseConnection(await getCredentials(cred.id));
       setAlertModal({
         message: `${result.success ? 'Success' : 'Failed'}: ${result.message}\n${result.server_version ? `Version: ${result.server_version}` : ''
         }`,
@@ -125,7 +127,7 @@ export function ConnectionManager({ onSelectConnection }: ConnectionManagerProps
     setFormData({
       ...formData,
       db_type: type,
-      port: type === 'postgres' ? 5432 : type === 'mysql' ? 3306 : undefined,
+      port: type === 'postgres' ? 5432 : type === 'mysql' ? 3306 : type === 'clickhouse' ? 8123 : undefined,
     });
   };
 
@@ -176,6 +178,7 @@ export function ConnectionManager({ onSelectConnection }: ConnectionManagerProps
                   <option value="postgres">PostgreSQL</option>
                   <option value="mysql">MySQL</option>
                   <option value="sqlite">SQLite</option>
+                  <option value="clickhouse">ClickHouse</option>
                 </select>
               </div>
             </div>
@@ -175,7 +175,7 @@ export function WorkspaceConnectionManager({
     setFormData({
       ...formData,
       db_type: type,
-      port: type === 'postgres' ? 5432 : type === 'mysql' ? 3306 : undefined,
+      port: type === 'postgres' ? 5432 : type === 'mysql' ? 3306 : type === 'clickhouse' ? 8123 : undefined,
     });
   };
 
@@ -234,6 +234,7 @@ export function WorkspaceConnectionManager({
                   <option value="postgres">PostgreSQL</option>
                   <option value="mysql">MySQL</option>
                   <option value="sqlite">SQLite</option>
+                  <option value="clickhouse">ClickHouse</option>
                 </select>
               </div>
             </div>
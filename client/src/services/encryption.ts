@@ -1,4 +1,4 @@
-import { invoke } from "@tauri-apps/api/core";
+import { invokeCommand } from "./tauri";
 import type { DatabaseCredentials } from "../types/database";
 
 export interface EncryptedConnection {
@@ -18,7 +18,7 @@ export async function encryptConnection(
   // Convert credentials to JSON string
   const credentialsJson = JSON.stringify(credentials);
 
-  const result = await invoke<EncryptedConnection>("encrypt_connection", {
+  const result = await invokeCommand<EncryptedConnection>("encrypt_connection", {
     credentialsJson,
     password,
     connectionName: credentials.name,
@@ -34,7 +34,7 @@ export async function decryptConnection(
   encrypted: EncryptedConnection,
   password: string
 ): Promise<DatabaseCredentials> {
-  const decryptedJson = await invoke<string>("decrypt_connection", {
+  const decryptedJson = await invokeCommand<string>("decrypt_connection", {
     encryptedConn: encrypted,
     password,
   });
@@ -50,7 +50,7 @@ export async function verifyPassword(
   password: string
 ): Promise<boolean> {
   try {
-    return await invoke<boolean>("verify_connection_password", {
+    return await invokeCommand<boolean>("verify_connection_password", {
       encryptedConn: encrypted,
       password,
     });
@@ -58,3 +58,55 @@ export async function verifyPassword(
     return false;
   }
 }
+
+/**
+ * Re-encrypt connection data with a new password
+ */
+export async function rotateConnectionKey(
+  encrypted: EncryptedConnection,
+  oldPassword: string,
+  newPassword: string
+): Promise<EncryptedConnection> {
+  return invokeCommand<EncryptedConnection>("rotate_connection_key", {
+    encryptedConn: encrypted,
+    oldPassword,
+    newPassword,
+  });
+}
+
+/**
+ * Store password-protected connection data on disk under an id
+ */
+export async function saveEncryptedConnection(
+  id: string,
+  encrypted: EncryptedConnection
+): Promise<void> {
+  return invokeCommand<void>("save_encrypted_connection", {
+    id,
+    encryptedConn: encrypted,
+  });
+}
+
+/**
+ * Read password-protected connection data stored under an id
+ */
+export async function loadEncryptedConnection(
+  id: string
+): Promise<EncryptedConnection> {
+  return invokeCommand<EncryptedConnection>("load_encrypted_connection", { id });
+}
+
+/**
+ * Re-encrypt the connection data stored under an id with a new password
+ */
+export async function rotateCredentialPassword(
+  id: string,
+  oldPassword: string,
+  newPassword: string
+): Promise<void> {
+  return invokeCommand<void>("rotate_credential_password", {
+    id,
+    oldPassword,
+    newPassword,
+  });
+}
@@ -2,62 +2,209 @@ import { invoke } from "@tauri-apps/api/core";
 import type {
   DatabaseCredentials,
   ConnectionTestResult,
+  PingResult,
   QueryResult,
+  QueryResultDiff,
+  SortDirection,
   TableInfo,
   TableSchema,
   Relationship,
+  SaveCredentialsResponse,
+  DatabaseErrorCode,
+  DatabaseErrorContext,
 } from "../types/database";
 
+// Error thrown when a database command fails, carrying the backend's context
+export class DatabaseCommandError extends Error {
+  readonly context: DatabaseErrorContext;
+
+  constructor(context: DatabaseErrorContext) {
+    super(context.message);
+    this.name = "DatabaseCommandError";
+    this.context = context;
+  }
+
+  get code(): DatabaseErrorCode {
+    return this.context.code;
+  }
+
+  toString(): string {
+    const { operation, table_name } = this.context;
+    return table_name
+      ? `${this.message} (${operation} on ${table_name})`
+      : `${this.message} (${operation})`;
+  }
+}
+
+// Invoke a command, turning structured backend errors into DatabaseCommandError
+export async function invokeCommand<T>(
+  command: string,
+  args?: Record<string, unknown>
+): Promise<T> {
+  try {
+    return await invoke<T>(command, args);
+  } catch (error) {
+    if (error && typeof error === "object" && "operation" in error) {
+      throw new DatabaseCommandError(error as DatabaseErrorContext);
+    }
+    throw error;
+  }
+}
+
 // Credential Management
 export async function saveCredentials(
   credentials: DatabaseCredentials
-): Promise<string> {
-  return invoke<string>("save_credentials", { credentials });
+): Promise<SaveCredentialsResponse> {
+  return invokeCommand<SaveCredentialsResponse>("save_credentials", { credentials });
 }
 
 export async function getCredentials(id: string): Promise<DatabaseCredentials> {
-  return invoke<DatabaseCredentials>("get_credentials", { id });
+  return invokeCommand<DatabaseCredentials>("get_credentials", { id });
+}
+
+// Copy a saved connection under a new name, returning the id of the copy
+export async function duplicateCredential(id: string, newName: string): Promise<string> {
+  return invokeCommand<string>("duplicate_credential", { id, newName });
+}
+
+export async function listCredentials(filter?: {
+  tag?: string;
+  group?: string;
+}): Promise<DatabaseCredentials[]> {
+  return invokeCommand<DatabaseCredentials[]>("list_credentials", filter);
 }
 
-export async function listCredentials(): Promise<DatabaseCredentials[]> {
-  return invoke<DatabaseCredentials[]>("list_credentials");
+export async function parseConnectionUrl(url: string): Promise<DatabaseCredentials> {
+  return invokeCommand<DatabaseCredentials>("parse_connection_url", { url });
+}
+
+export async function addTagToCredential(id: string, tag: string): Promise<void> {
+  return invokeCommand<void>("add_tag_to_credential", { id, tag });
+}
+
+export async function removeTagFromCredential(id: string, tag: string): Promise<void> {
+  return invokeCommand<void>("remove_tag_from_credential", { id, tag });
 }
 
 export async function deleteCredentials(id: string): Promise<void> {
-  return invoke<void>("delete_credentials", { id });
+  return invokeCommand<void>("delete_credentials", { id });
 }
 
 export async function updateCredentials(
   credentials: DatabaseCredentials
 ): Promise<void> {
-  return invoke<void>("update_credentials", { credentials });
+  return invokeCommand<void>("update_credentials", { credentials });
 }
 
 // Connection Testing
 export async function testDatabaseConnection(
-  credentials: DatabaseCredentials
+  credentials: DatabaseCredentials,
+  samples?: number
 ): Promise<ConnectionTestResult> {
-  return invoke<ConnectionTestResult>("test_database_connection", {
+  return invokeCommand<ConnectionTestResult>("test_database_connection", {
     credentials,
+    samples,
   });
 }
 
+export async function pingDatabaseConnection(databaseId: string): Promise<PingResult> {
+  return invokeCommand<PingResult>("ping_database_connection", { databaseId });
+}
+
+// Seconds between keepalive pings of the open pools, 0 disables them
+export async function setKeepaliveIntervalSecs(intervalSecs: number): Promise<void> {
+  return invokeCommand<void>("set_keepalive_interval_secs", { intervalSecs });
+}
+
 // Query Execution
 export async function executeSqlQuery(
   databaseId: string,
-  sql: string
+  sql: string,
+  pageSize?: number,
+  page?: number,
+  timeoutSecs?: number,
+  maxRows?: number
+): Promise<QueryResult> {
+  return invokeCommand<QueryResult>("execute_sql_query", {
+    databaseId,
+    sql,
+    pageSize,
+    page,
+    timeoutSecs,
+    maxRows,
+  });
+}
+
+// Run a query ordered by one of its result columns
+export async function executeSortedSqlQuery(
+  databaseId: string,
+  sql: string,
+  sortColumn: string,
+  sortDirection: SortDirection,
+  pageSize?: number,
+  page?: number,
+  timeoutSecs?: number
 ): Promise<QueryResult> {
-  return invoke<QueryResult>("execute_sql_query", {
+  return invokeCommand<QueryResult>("execute_sorted_sql_query", {
+    databaseId,
+    sql,
+    sortColumn,
+    sortDirection,
+    pageSize,
+    page,
+    timeoutSecs,
+  });
+}
+
+// Every result set of a MySQL CALL; other queries come back as a single result
+export async function executeMultiResultQuery(
+  databaseId: string,
+  sql: string,
+  timeoutSecs?: number,
+  allowMutations = false
+): Promise<QueryResult[]> {
+  return invokeCommand<QueryResult[]>("execute_multi_result_query", {
     databaseId,
     sql,
+    timeoutSecs,
+    allowMutations,
   });
 }
 
+// Compare two runs of a query, matching rows on keyColumn
+export async function diffQueryResults(
+  resultA: QueryResult,
+  resultB: QueryResult,
+  keyColumn: string
+): Promise<QueryResultDiff> {
+  return invokeCommand<QueryResultDiff>("diff_query_results", {
+    resultA,
+    resultB,
+    keyColumn,
+  });
+}
+
+export async function setDefaultRowLimit(limit: number): Promise<void> {
+  return invokeCommand<void>("set_default_row_limit", { limit });
+}
+
+export async function getDefaultRowLimit(): Promise<number> {
+  return invokeCommand<number>("get_default_row_limit");
+}
+
+export async function setQueryMaxPageSize(maxPageSize: number): Promise<void> {
+  return invokeCommand<void>("set_query_max_page_size", { maxPageSize });
+}
+
+export async function getQueryMaxPageSize(): Promise<number> {
+  return invokeCommand<number>("get_query_max_page_size");
+}
+
 // Metadata Extraction
 export async function getDatabaseTables(
   databaseId: string
 ): Promise<TableInfo[]> {
-  return invoke<TableInfo[]>("get_database_tables", { databaseId });
+  return invokeCommand<TableInfo[]>("get_database_tables", { databaseId });
 }
 
 export async function getDatabaseTableSchema(
@@ -65,7 +212,7 @@ export async function getDatabaseTableSchema(
   tableNames: string,
   schema?: string
 ): Promise<TableSchema[]> {
-  return invoke<TableSchema[]>("get_database_table_schema", {
+  return invokeCommand<TableSchema[]>("get_database_table_schema", {
     databaseId,
     tableNames,
     schema,
@@ -75,5 +222,5 @@ export async function getDatabaseTableSchema(
 export async function getDatabaseRelationships(
   databaseId: string
 ): Promise<Relationship[]> {
-  return invoke<Relationship[]>("get_database_relationships", { databaseId });
+  return invokeCommand<Relationship[]>("get_database_relationships", { databaseId });
 }
@@ -1,4 +1,18 @@
-export type DatabaseType = 'postgres' | 'mysql' | 'sqlite';
+export type DatabaseType = 'postgres' | 'mysql' | 'sqlite' | 'clickhouse';
+
+export type SslMode = 'disabled' | 'prefer' | 'require' | 'verify_ca' | 'verify_full';
+
+export type MaskingStrategy =
+  | { type: 'redact' }
+  | { type: 'partial'; visible_prefix: number; visible_suffix: number }
+  | { type: 'hash' }
+  | { type: 'nullify' };
+
+export interface MaskingRule {
+  column_pattern: string;
+  strategy: MaskingStrategy;
+  except_roles: string[];
+}
 
 export interface DatabaseCredentials {
   id: string;
@@ -11,25 +25,145 @@ export interface DatabaseCredentials {
   password?: string;
   file_path?: string;
   schema?: string;  // Optional PostgreSQL schema (e.g., 'public')
+  environment_variables?: Record<string, string>;
+  ssh_host?: string;
+  ssh_port?: number;
+  ssh_user?: string;
+  ssh_private_key_path?: string;
+  ssh_private_key_passphrase?: string;
+  ssh_host_key_fingerprint?: string;
+  masking_rules?: MaskingRule[];
+  ssl_mode?: SslMode;
+  ssl_ca_cert_path?: string;
+  ssl_client_cert_path?: string;
+  ssl_client_key_path?: string;
+  application_name?: string;
+  connect_timeout?: number;
+  statement_timeout?: number;
+  search_path?: string;
+  charset?: string;
+  collation?: string;
+  extra_options?: Record<string, string>;
+  pool_config?: PoolConfig;
+  tags?: string[];
+  color?: string;  // Hex color for grouping in the UI
+  group?: string;
+}
+
+export interface PoolConfig {
+  max_connections?: number;
+  min_connections?: number;
+  connect_timeout_secs?: number;
+  idle_timeout_secs?: number;
+  max_lifetime_secs?: number;
+}
+
+export interface PoolStatus {
+  size: number;
+  idle: number;
+  acquire_timeout_ms: number;
+}
+
+export interface PasswordStrength {
+  level: 'weak' | 'fair' | 'strong' | 'very_strong';
+  score: number;
+  suggestions: string[];
+}
+
+export interface SaveCredentialsResponse {
+  id: string;
+  password_strength?: PasswordStrength;
 }
 
 export interface ConnectionTestResult {
   success: boolean;
   message: string;
   server_version?: string;
+  latency_samples: number[];  // Milliseconds
+  latency_min_ms: number;
+  latency_max_ms: number;
+  latency_avg_ms: number;
+  failed_samples: number;
 }
 
+export type ColumnType =
+  | 'integer'
+  | 'float'
+  | 'boolean'
+  | 'text'
+  | 'json'
+  | 'binary'
+  | 'timestamp'
+  | 'date'
+  | 'uuid'
+  | 'unknown';
+
 export interface QueryResult {
   columns: string[];
   rows: Record<string, any>[];
   row_count: number;
   execution_time_ms: number;
+  matched_columns: string[];
+  total_rows?: number;
+  page: number;
+  page_size: number;
+  masked_columns_applied: [string, string][];
+  value_coercions_applied: number;
+  sampling_method_used: string | null;
+  column_types: ColumnType[];
+  was_truncated: boolean;
+}
+
+export type SortDirection = 'asc' | 'desc';
+
+export interface RowDiff {
+  before: Record<string, any>;
+  after: Record<string, any>;
+  changed_columns: string[];
+}
+
+export interface QueryResultDiff {
+  added_rows: Record<string, any>[];
+  removed_rows: Record<string, any>[];
+  modified_rows: RowDiff[];
+  columns_added: string[];
+  columns_removed: string[];
+}
+
+export interface ColumnSuggestion {
+  table: string;
+  column: string;
+  data_type: string;
+}
+
+export interface AutocompleteContext {
+  table_suggestions: string[];
+  column_suggestions: ColumnSuggestion[];
+  keyword_suggestions: string[];
 }
 
+export interface PageResult {
+  rows: Record<string, any>[];
+  has_more: boolean;
+  next_cursor: any | null;
+}
+
+export interface TransactionResult {
+  results: QueryResult[];
+  committed: boolean;
+  rollback_reason: string | null;
+}
+
+export type TableType = 'base_table' | 'view' | 'materialized_view';
+
+export type SampleMethod = 'system' | 'bernoulli';
+
 export interface TableInfo {
   name: string;
   schema?: string;
   row_count?: number;
+  table_type: TableType;
+  parent_table: string | null;  // Postgres table this one inherits from
 }
 
 export interface ColumnInfo {
@@ -38,6 +172,9 @@ export interface ColumnInfo {
   is_nullable: boolean;
   is_primary_key: boolean;
   default_value?: string;
+  is_unique: boolean;
+  unique_constraint_name: string | null;
+  is_inherited: boolean;
 }
 
 export interface TableSchema {
@@ -46,12 +183,157 @@ export interface TableSchema {
   columns: ColumnInfo[];
 }
 
+export type RelationshipSource = 'explicit' | 'inferred' | 'learned' | 'many_to_many';
+
 export interface Relationship {
   table_name: string;
   column_name: string;
   foreign_table: string;
   foreign_column: string;
-  constraint_name: string;
+  constraint_name: string | null;
+  source: RelationshipSource;
+  junction_table?: string;
+  is_self_referential: boolean;
+  relationship_cardinality?: 'self';
+  schema: string | null; // null on MySQL and SQLite
+  foreign_schema: string | null;
+}
+
+export interface TableDDL {
+  table_name: string;
+  ddl: string;
+  database_type: DatabaseType;
+}
+
+export type DatabaseErrorCode =
+  | 'CONNECTION_ERROR'
+  | 'QUERY_ERROR'
+  | 'QUERY_TIMEOUT'
+  | 'CREDENTIALS_ERROR'
+  | 'UNSUPPORTED_TYPE'
+  | 'SQL_INJECTION'
+  | 'DESTRUCTIVE_OPERATION'
+  | 'CONSTRAINT_VIOLATION'
+  | 'ENCRYPTION_ERROR'
+  | 'SSH_ERROR'
+  | 'STORAGE_ERROR'
+  | 'OTHER';
+
+export interface SerializableDatabaseError {
+  code: DatabaseErrorCode;
+  message: string;
+  detail: string | null;  // SQL of a failed query
+}
+
+export interface DatabaseErrorContext extends SerializableDatabaseError {
+  database_id: string | null;
+  operation: string;
+  table_name: string | null;
+}
+
+export interface DependencyInfo {
+  dependent_table: string;
+  via_column: string;
+  via_constraint: string | null;
+  dependency_type: 'foreign_key' | 'inferred' | 'learned' | 'view' | 'function';
+}
+
+export interface TableReference {
+  from_table: string;
+  from_column: string;
+  constraint_name: string | null;
+  relationship_type: DependencyInfo['dependency_type'];
+}
+
+export interface ColumnSearchResult {
+  table_name: string;
+  schema: string | null;
+  column: ColumnInfo;
+}
+
+export type ColumnSearchHit = ColumnSearchResult;
+
+export interface SchemaSearchResult {
+  matching_tables: TableInfo[];
+  matching_columns: ColumnSearchHit[];
+}
+
+export type RoutineType = 'procedure' | 'function';
+
+export interface RoutineInfo {
+  name: string;
+  schema: string;
+  routine_type: RoutineType;
+  return_type: string | null;
+  language: string | null;
+  definition: string | null;
+}
+
+export interface TriggerInfo {
+  name: string;
+  table_name: string;
+  event: string;
+  timing: string;
+  statement: string | null;
+  enabled: boolean;
+}
+
+export interface EnumTypeInfo {
+  schema: string;
+  name: string;
+  values: string[];
+}
+
+export interface SchemaInfo {
+  name: string;
+  owner: string | null;
+  is_default: boolean;
+}
+
+export interface SequenceInfo {
+  name: string;
+  schema: string;
+  data_type: string;
+  start_value: number;
+  increment: number;
+  min_value: number;
+  max_value: number;
+  current_value: number | null;
+  is_cycled: boolean;
+  owned_by: string | null;
+}
+
+export interface CheckConstraintInfo {
+  name: string;
+  table_name: string;
+  column_name: string | null;
+  expression: string;
+}
+
+export type ConnectionStatus = 'online' | 'offline';
+
+export interface ConnectionHealth {
+  status: ConnectionStatus;
+  error: string | null;
+  checked_at: string;
+}
+
+// Payload of the `connection-status-changed` event
+export interface ConnectionStatusChangedEvent {
+  database_id: string;
+  status: ConnectionStatus;
+  error: string | null;
+}
+
+export interface PingResult {
+  latency_ms: number;
+  success: boolean;
+}
+
+// Payload of the `connection_lost` event
+export interface ConnectionLostEvent {
+  database_id: string;
+  error: string;
 }
 
 export interface MetadataRequest {
@@ -80,3 +362,140 @@ export interface ConversationMessage {
   role: 'user' | 'assistant';
   content: string;
 }
+
+export type CollisionStrategy = 'skip' | 'overwrite' | 'rename_imported';
+
+export interface TableStatistics {
+  table_name: string;
+  schema: string | null;
+  seq_scans: number | null;
+  idx_scans: number | null;
+  rows_inserted: number | null;
+  rows_updated: number | null;
+  rows_deleted: number | null;
+  last_vacuum: string | null;
+  last_analyze: string | null;
+}
+
+export interface ColumnStatistics {
+  min_value: any | null;
+  max_value: any | null;
+  avg_value: number | null;
+  distinct_count: number;
+  null_count: number;
+  total_count: number;
+  sample_values: any[];
+}
+
+export interface TableSizeInfo {
+  table_name: string;
+  data_bytes: number;
+  index_bytes: number;
+  total_bytes: number;
+}
+
+export interface DatabaseSizeInfo {
+  database_name: string;
+  total_size_bytes: number;
+  tables: TableSizeInfo[];
+}
+
+export type CopyFormat = 'csv' | 'text' | 'binary';
+
+export interface ExportResult {
+  rows_exported: number;
+  bytes_written: number;
+  duration_ms: number;
+}
+
+export interface SavedQuery {
+  id: string;
+  name: string;
+  database_id: string | null;
+  sql: string;
+  created_at: string;
+  tags: string[];
+}
+
+export interface QueryCacheStats {
+  hits: number;
+  misses: number;
+  entries: number;
+}
+
+export interface SessionInfo {
+  pid: number;
+  database: string | null;
+  username: string | null;
+  state: string | null;
+  query: string | null;
+  query_start: string | null;
+  duration_secs: number | null;
+  wait_event: string | null;
+}
+
+export interface LockInfo {
+  pid: number;
+  relation_name: string | null;
+  lock_type: string;
+  mode: string;
+  granted: boolean;
+  blocking_pid: number | null;
+  query: string | null;
+}
+
+export interface ReplicationSlotInfo {
+  name: string;
+  plugin: string;
+  active: boolean;
+  lag_bytes: number | null;
+}
+
+export interface ReplicationStatus {
+  is_in_recovery: boolean;
+  wal_lsn: string | null;
+  replay_lsn: string | null;
+  lag_bytes: number | null;
+  slots: ReplicationSlotInfo[];
+  warning: string | null;
+}
+
+export interface DatabaseUser {
+  name: string;
+  superuser: boolean;
+  can_create_db: boolean;
+  can_login: boolean;
+  roles: string[];
+}
+
+export interface TablePrivilege {
+  grantee: string;
+  privilege_type: string;
+  is_grantable: boolean;
+}
+
+export interface SqlitePragmas {
+  journal_mode: string;
+  synchronous: 'OFF' | 'NORMAL' | 'FULL' | 'EXTRA';
+  page_size: number;
+  cache_size: number;
+  foreign_keys: boolean;
+  wal_autocheckpoint: number;
+}
+
+export type JournalMode = 'delete' | 'wal' | 'memory' | 'off' | 'truncate';
+
+export interface RefreshResult {
+  view_name: string;
+  duration_ms: number;
+}
+
+export interface VacuumResult {
+  duration_ms: number;
+  success: boolean;
+}
+
+export interface IntegrityCheckResult {
+  ok: boolean;
+  errors: string[];
+}
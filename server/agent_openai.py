@@ -598,7 +598,8 @@ CRITICAL ERROR HANDLING RULES:
             db_name = {
                 "postgres": "PostgreSQL",
                 "mysql": "MySQL",
-                "sqlite": "SQLite"
+                "sqlite": "SQLite",
+                "clickhouse": "ClickHouse"
             }.get(db_type, db_type)
             parts.append(f"**DATABASE TYPE: {db_name}** ← Use {db_name}-specific syntax!")
 